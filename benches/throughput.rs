@@ -0,0 +1,104 @@
+// Measures AES throughput for each block mode, key size and backend.
+// Run with `cargo bench --bench throughput`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use generic_array::typenum::U16;
+use kaidoku::{
+    cipher::{
+        block::{
+            ciphers::{
+                aes::{Aes, AesBackend, BitslicedAes},
+                BlockCipher,
+            },
+            modes::{BlockMode, Cbc, CounterLayout, Ctr, Ecb},
+            pads::pkcs7::Pkcs7,
+        },
+        StreamCipher,
+    },
+    rng::MT,
+};
+use rand::SeedableRng;
+
+const MESSAGE_LEN: usize = 1 << 20; // 1MiB
+const MIN_DURATION: Duration = Duration::from_secs(1);
+
+fn main() {
+    println!(
+        "{:<6} {:<8} {:<10} {:>12} {:>12}",
+        "mode", "key", "backend", "enc MiB/s", "dec MiB/s"
+    );
+
+    for key_len in [16, 24, 32] {
+        let key = (0..key_len as u8).collect::<Vec<u8>>();
+
+        for backend in [AesBackend::Reference, AesBackend::TTable] {
+            bench_modes(&format!("{:?}", backend), key_len, || {
+                Aes::with_backend(key.clone(), backend).unwrap()
+            });
+        }
+        bench_modes("Bitsliced", key_len, || {
+            BitslicedAes::new(key.clone()).unwrap()
+        });
+    }
+}
+
+fn bench_modes<C: BlockCipher<U16>>(backend: &str, key_len: usize, cipher: impl Fn() -> C) {
+    let message = vec![0x5a; MESSAGE_LEN];
+    let report = |mode: &str, (encrypt, decrypt): (f64, f64)| {
+        println!(
+            "{:<6} {:<8} {:<10} {:>12.2} {:>12.2}",
+            mode,
+            format!("AES-{}", key_len * 8),
+            backend,
+            encrypt,
+            decrypt
+        );
+    };
+
+    let mut ecb = Ecb::new(cipher(), Pkcs7::new());
+    let ciphertext = ecb.encrypt(message.clone());
+    report(
+        "ECB",
+        (
+            throughput(|| ecb.encrypt(message.clone())),
+            throughput(|| ecb.decrypt(ciphertext.clone()).unwrap()),
+        ),
+    );
+
+    let rng = MT::from_seed(1u32.to_be_bytes());
+    let mut cbc = Cbc::new(rng, cipher(), Pkcs7::new());
+    let ciphertext = cbc.encrypt(message.clone());
+    report(
+        "CBC",
+        (
+            throughput(|| cbc.encrypt(message.clone())),
+            throughput(|| cbc.decrypt(ciphertext.clone()).unwrap()),
+        ),
+    );
+
+    let mut ctr = Ctr::new(cipher(), vec![0u8; 8], CounterLayout::BE64).unwrap();
+    let ciphertext = ctr.encrypt(message.clone());
+    report(
+        "CTR",
+        (
+            throughput(|| ctr.encrypt(message.clone())),
+            throughput(|| ctr.decrypt(ciphertext.clone())),
+        ),
+    );
+}
+
+// throughput repeats f over a MESSAGE_LEN input until MIN_DURATION has passed,
+// and returns the average rate in MiB/s.
+fn throughput<T>(mut f: impl FnMut() -> T) -> f64 {
+    let start = Instant::now();
+    let mut iterations = 0;
+    while start.elapsed() < MIN_DURATION {
+        black_box(f());
+        iterations += 1;
+    }
+
+    let mebibytes = (iterations * MESSAGE_LEN) as f64 / (1 << 20) as f64;
+    mebibytes / start.elapsed().as_secs_f64()
+}
@@ -0,0 +1,94 @@
+use generic_array::GenericArray;
+
+use crate::digest::{
+    hash::merkle_damguard::{Compression, MerkleDamgard},
+    Digest, DigestError, Output,
+};
+
+// Length extension attacks forge H(M || padding || extension) from H(M) and
+// the length of M alone, against any Merkle–Damgård hash whose digest is its
+// whole chaining state (MD4, MD5, SHA-1, SHA-256, SHA-512).
+
+// The classic target is the secret-prefix MAC, tag = H(key || message). An
+// attacker who sees one (message, tag) pair and guesses the key length can
+// produce a valid tag for message || padding || extension, for an extension
+// of their choosing, without learning the key. HMAC, truncated hashes such as
+// SHA-384, and sponges such as SHA-3 are not vulnerable.
+
+/// Forges the digest of `M || padding || extension` from the digest of a
+/// message `M` of `message_len` bytes. Returns the bytes to append to `M`,
+/// `padding || extension`, and the forged digest.
+pub fn extend<C: Compression>(
+    digest: &GenericArray<u8, C::OutputSize>,
+    message_len: u128,
+    extension: &[u8],
+) -> Result<(Vec<u8>, Output<MerkleDamgard<C>>), DigestError> {
+    let mut suffix = MerkleDamgard::<C>::padding(message_len);
+
+    let mut hasher = MerkleDamgard::<C>::from_digest(digest, message_len + suffix.len() as u128)?;
+    hasher.update(extension);
+    suffix.extend_from_slice(extension);
+
+    Ok((suffix, hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::hash::merkle_damguard::{
+        Md4Compression, Md5Compression, Sha1Compression, Sha256Compression, Sha384Compression,
+    };
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const MESSAGE: &[u8] =
+        b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+    const EXTENSION: &[u8] = b";admin=true";
+
+    // secret_prefix_mac is the vulnerable MAC H(key || message).
+    fn secret_prefix_mac<C: Compression>(message: &[u8]) -> Output<MerkleDamgard<C>> {
+        let mut hasher = MerkleDamgard::<C>::new();
+        hasher.update(KEY);
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    // forge guesses the key length until the forged tag verifies, as an
+    // attacker with access to a verification oracle would.
+    fn forge<C: Compression>() -> Option<(Vec<u8>, usize)> {
+        let tag = secret_prefix_mac::<C>(MESSAGE);
+
+        (0..64).find_map(|key_len| {
+            let (suffix, forged_tag) =
+                extend::<C>(&tag, (key_len + MESSAGE.len()) as u128, EXTENSION).unwrap();
+            let forged_message = [MESSAGE, &suffix].concat();
+
+            (secret_prefix_mac::<C>(&forged_message) == forged_tag)
+                .then_some((forged_message, key_len))
+        })
+    }
+
+    #[test]
+    fn test_secret_prefix_mac_forgery() {
+        for (forgery, name) in [
+            (forge::<Md4Compression>(), "MD4"),
+            (forge::<Md5Compression>(), "MD5"),
+            (forge::<Sha1Compression>(), "SHA-1"),
+            (forge::<Sha256Compression>(), "SHA-256"),
+        ] {
+            let (forged_message, key_len) =
+                forgery.unwrap_or_else(|| panic!("{} forgery failed", name));
+            assert_eq!(key_len, KEY.len());
+            assert!(forged_message.starts_with(MESSAGE));
+            assert!(forged_message.ends_with(EXTENSION));
+        }
+    }
+
+    #[test]
+    fn test_truncated_hash() {
+        let tag = secret_prefix_mac::<Sha384Compression>(MESSAGE);
+        assert_eq!(
+            extend::<Sha384Compression>(&tag, 100, EXTENSION).err(),
+            Some(DigestError::TruncatedOutput)
+        );
+    }
+}
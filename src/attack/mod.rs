@@ -1 +1,2 @@
+pub mod length_extension;
 pub mod vernam;
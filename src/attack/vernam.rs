@@ -81,7 +81,7 @@ pub fn monoalphabetic_attack(
     let mut plaintext_score_tuples = plaintext_scores
         .into_iter()
         .collect::<Vec<((u8, String), i32)>>();
-    plaintext_score_tuples.sort_by(|a, b| b.1.cmp(&a.1));
+    plaintext_score_tuples.sort_by_key(|b| std::cmp::Reverse(b.1));
 
     if plaintext_score_tuples.is_empty() {
         return Ok(None);
@@ -160,9 +160,7 @@ pub fn polyalphabetic_attack(path_location: &str) -> String {
         cipher::stream::VernamCipher::new(ciphertext_bytes.into_iter(), probable_key_stretched)
             .collect::<Result<Vec<u8>, cipher::stream::VernamCipherError>>()
             .unwrap();
-    let plaintext = String::from_utf8(plain_bytes).unwrap();
-
-    plaintext
+    String::from_utf8(plain_bytes).unwrap()
 }
 
 fn parse_and_decode_file(path_location: &str) -> Vec<u8> {
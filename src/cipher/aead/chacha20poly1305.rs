@@ -0,0 +1,298 @@
+use super::AeadError;
+use crate::{
+    cipher::{
+        stream::{ChaCha20, XChaCha20},
+        KeyError, StreamCipher,
+    },
+    mac::Poly1305,
+    utils::constant_time_eq,
+};
+
+// ChaCha20Poly1305 implements the AEAD construction defined in RFC 8439.
+// The first ChaCha20 block under the nonce derives a one-time Poly1305 key,
+// and the plaintext is encrypted from block 1 onwards. The tag authenticates
+// the associated data and ciphertext, each zero padded to 16 bytes, followed
+// by their lengths.
+
+// otk = chacha20_block(K, 0, N)[..32]
+// C   = ChaCha20(K, N, 1) XOR P
+// T   = Poly1305(otk, A || pad16(A) || C || pad16(C) || [len(A)]_64 || [len(C)]_64)
+
+// Nonces are 96 bits, so they should come from a counter rather than an rng.
+// Reusing a nonce leaks the XOR of the plaintexts and the one-time key,
+// which allows forgeries under that nonce.
+pub struct ChaCha20Poly1305 {
+    key: Vec<u8>,
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: Vec<u8>) -> Result<Self, AeadError> {
+        check_key(&key)?;
+
+        Ok(ChaCha20Poly1305 { key })
+    }
+
+    // encrypt returns the ciphertext with the tag appended.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        Ok(seal(self.cipher(nonce)?, aad, plaintext))
+    }
+
+    // decrypt verifies the tag appended to the ciphertext before decrypting,
+    // and releases no plaintext if verification fails.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        open(self.cipher(nonce)?, aad, ciphertext)
+    }
+
+    fn cipher(&self, nonce: &[u8]) -> Result<ChaCha20, AeadError> {
+        if nonce.len() != 12 {
+            return Err(AeadError::InvalidNonceLength(nonce.len()));
+        }
+
+        // SAFETY: the key and nonce lengths have been checked
+        Ok(ChaCha20::new(self.key.clone(), nonce.to_vec()).unwrap())
+    }
+}
+
+// XChaCha20Poly1305 is ChaCha20Poly1305 with XChaCha20's 192 bit nonces, as
+// defined in draft-irtf-cfrg-xchacha. Nonces this long can safely be drawn at
+// random for every message.
+pub struct XChaCha20Poly1305 {
+    key: Vec<u8>,
+}
+
+impl XChaCha20Poly1305 {
+    pub fn new(key: Vec<u8>) -> Result<Self, AeadError> {
+        check_key(&key)?;
+
+        Ok(XChaCha20Poly1305 { key })
+    }
+
+    // encrypt returns the ciphertext with the tag appended.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        Ok(seal(self.cipher(nonce)?, aad, plaintext))
+    }
+
+    // decrypt verifies the tag appended to the ciphertext before decrypting,
+    // and releases no plaintext if verification fails.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        open(self.cipher(nonce)?, aad, ciphertext)
+    }
+
+    fn cipher(&self, nonce: &[u8]) -> Result<XChaCha20, AeadError> {
+        if nonce.len() != 24 {
+            return Err(AeadError::InvalidNonceLength(nonce.len()));
+        }
+
+        // SAFETY: the key and nonce lengths have been checked
+        Ok(XChaCha20::new(self.key.clone(), nonce.to_vec()).unwrap())
+    }
+}
+
+fn check_key(key: &[u8]) -> Result<(), KeyError> {
+    if key.len() != 32 {
+        return Err(KeyError::InvalidKeyLength {
+            expected: &[32],
+            actual: key.len(),
+        });
+    }
+
+    Ok(())
+}
+
+// seal and open take a cipher positioned at block 0, which is consumed for
+// the one-time key.
+fn seal<S: StreamCipher>(mut cipher: S, aad: &[u8], plaintext: Vec<u8>) -> Vec<u8> {
+    let otk = poly1305_key_gen(&mut cipher);
+
+    let mut ciphertext = cipher.encrypt(plaintext);
+    let tag = tag(otk, aad, &ciphertext);
+    ciphertext.extend_from_slice(&tag);
+
+    ciphertext
+}
+
+fn open<S: StreamCipher>(
+    mut cipher: S,
+    aad: &[u8],
+    ciphertext: Vec<u8>,
+) -> Result<Vec<u8>, AeadError> {
+    if ciphertext.len() < 16 {
+        return Err(AeadError::TruncatedCiphertext {
+            len: ciphertext.len(),
+            min: 16,
+        });
+    }
+
+    let otk = poly1305_key_gen(&mut cipher);
+
+    let (ciphertext, tag_bytes) = ciphertext.split_at(ciphertext.len() - 16);
+    let expected_tag = tag(otk, aad, ciphertext);
+    if !constant_time_eq(&expected_tag, tag_bytes) {
+        return Err(AeadError::AuthenticationFailed);
+    }
+
+    Ok(cipher.decrypt(ciphertext.to_vec()))
+}
+
+// poly1305_key_gen returns the first 32 bytes of keystream block 0, and
+// leaves the cipher at the start of block 1.
+fn poly1305_key_gen<S: StreamCipher>(cipher: &mut S) -> Vec<u8> {
+    let mut block = cipher.encrypt(vec![0u8; 64]);
+    block.truncate(32);
+
+    block
+}
+
+fn tag(otk: Vec<u8>, aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let mut poly1305 = Poly1305::new(otk).unwrap(); // SAFETY: otk is always 32 bytes
+
+    poly1305.update(aad);
+    poly1305.update(&[0u8; 16][..(16 - aad.len() % 16) % 16]);
+    poly1305.update(ciphertext);
+    poly1305.update(&[0u8; 16][..(16 - ciphertext.len() % 16) % 16]);
+    poly1305.update(&(aad.len() as u64).to_le_bytes());
+    poly1305.update(&(ciphertext.len() as u64).to_le_bytes());
+
+    poly1305.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    const AAD: &str = "50515253c0c1c2c3c4c5c6c7";
+
+    fn key() -> Vec<u8> {
+        (0x80..=0x9f).collect()
+    }
+
+    // RFC 8439 section 2.6.2
+    #[test]
+    fn test_poly1305_key_gen() {
+        let mut chacha = ChaCha20::new(key(), decode("000000000001020304050607")).unwrap();
+
+        assert_eq!(
+            poly1305_key_gen(&mut chacha),
+            decode("8ad5a08b905f81cc815040274ab29471a833b637e3fd0da508dbb8e2fdd1a646")
+        );
+    }
+
+    // RFC 8439 section 2.8.2
+    #[test]
+    fn test_rfc8439_aead() {
+        let aead = ChaCha20Poly1305::new(key()).unwrap();
+        let nonce = decode("070000004041424344454647");
+
+        let expected_ciphertext = decode(concat!(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+            "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+            "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+            "3ff4def08e4b7a9de576d26586cec64b6116",
+            // tag
+            "1ae10b594f09e26a7e902ecbd0600691",
+        ));
+
+        let encrypted = aead
+            .encrypt(&nonce, &decode(AAD), SUNSCREEN.to_vec())
+            .unwrap();
+        assert_eq!(encrypted, expected_ciphertext);
+
+        let decrypted = aead.decrypt(&nonce, &decode(AAD), encrypted).unwrap();
+        assert_eq!(decrypted, SUNSCREEN);
+    }
+
+    // draft-irtf-cfrg-xchacha-03 appendix A.3.1
+    #[test]
+    fn test_xchacha20poly1305() {
+        let aead = XChaCha20Poly1305::new(key()).unwrap();
+        let nonce = decode("404142434445464748494a4b4c4d4e4f5051525354555657");
+
+        let expected_ciphertext = decode(concat!(
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb",
+            "731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452",
+            "2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9",
+            "21f9664c97637da9768812f615c68b13b52e",
+            // tag
+            "c0875924c1c7987947deafd8780acf49",
+        ));
+
+        let encrypted = aead
+            .encrypt(&nonce, &decode(AAD), SUNSCREEN.to_vec())
+            .unwrap();
+        assert_eq!(encrypted, expected_ciphertext);
+
+        let decrypted = aead.decrypt(&nonce, &decode(AAD), encrypted).unwrap();
+        assert_eq!(decrypted, SUNSCREEN);
+    }
+
+    #[test]
+    fn test_tampering_fails() {
+        let aead = ChaCha20Poly1305::new(key()).unwrap();
+        let nonce = decode("070000004041424344454647");
+        let encrypted = aead
+            .encrypt(&nonce, &decode(AAD), SUNSCREEN.to_vec())
+            .unwrap();
+
+        let mut tampered = encrypted.clone();
+        tampered[0] ^= 0x01;
+        assert_eq!(
+            aead.decrypt(&nonce, &decode(AAD), tampered),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            aead.decrypt(&nonce, b"other aad", encrypted.clone()),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            aead.decrypt(&nonce, &decode(AAD), encrypted[..15].to_vec()),
+            Err(AeadError::TruncatedCiphertext { len: 15, min: 16 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert!(matches!(
+            ChaCha20Poly1305::new(vec![0u8; 16]),
+            Err(AeadError::InvalidKey(KeyError::InvalidKeyLength {
+                actual: 16,
+                ..
+            }))
+        ));
+
+        let aead = ChaCha20Poly1305::new(key()).unwrap();
+        assert_eq!(
+            aead.encrypt(&[0u8; 24], &[], Vec::new()),
+            Err(AeadError::InvalidNonceLength(24))
+        );
+
+        let aead = XChaCha20Poly1305::new(key()).unwrap();
+        assert_eq!(
+            aead.encrypt(&[0u8; 12], &[], Vec::new()),
+            Err(AeadError::InvalidNonceLength(12))
+        );
+    }
+}
@@ -0,0 +1,495 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::AeadError;
+use crate::{
+    cipher::{
+        block::{
+            ciphers::BlockCipher,
+            modes::{CounterLayout, Keystream},
+            Block,
+        },
+        stream::{VernamCipher, VernamCipherError},
+    },
+    utils::constant_time_eq,
+};
+
+// Gcm implements Galois/Counter Mode as defined in NIST SP 800-38D.
+// GCM encrypts with CTR mode (32 bit big-endian counter), and authenticates
+// the associated data and ciphertext with GHASH, a polynomial evaluation
+// MAC over GF(2^128) keyed with H = encrypt_block(0^128, K).
+
+// J_0 = IV || 0^31 || 1                                 if len(IV) = 96
+//       GHASH_H(IV || 0^(s+64) || [len(IV)]_64)         otherwise
+// C   = CTR(inc32(J_0), P)
+// S   = GHASH_H(A || 0^v || C || 0^u || [len(A)]_64 || [len(C)]_64)
+// T   = MSB_t(encrypt_block(J_0, K) XOR S)
+
+// SP 800-38D limits the plaintext to 2^39 - 256 bits, the 2^32 - 2 blocks the
+// 32 bit counter covers before it wraps around to J_0 and reuses keystream.
+const MAX_MESSAGE_LEN: u64 = (1 << 36) - 32;
+
+// Reusing a nonce under the same key leaks both the XOR of the plaintexts
+// (through CTR) and H (through GHASH), which allows universal forgeries.
+pub struct Gcm<C>
+where
+    C: BlockCipher<U16>,
+{
+    cipher: C,
+    h: Block<U16>,
+    tag_len: usize,
+}
+
+impl<C> Gcm<C>
+where
+    C: BlockCipher<U16>,
+{
+    pub fn new(cipher: C) -> Self {
+        let h = cipher.encrypt_block(GenericArray::default());
+
+        Gcm {
+            cipher,
+            h,
+            tag_len: 16,
+        }
+    }
+
+    // with_tag_len truncates tags to tag_len bytes. SP 800-38D permits 128,
+    // 120, 112, 104 and 96 bit tags, and 64 and 32 bit tags for applications
+    // that bound the number of decryption attempts.
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, AeadError> {
+        match tag_len {
+            4 | 8 | 12..=16 => {
+                let mut gcm = Gcm::new(cipher);
+                gcm.tag_len = tag_len;
+
+                Ok(gcm)
+            }
+            _ => Err(AeadError::InvalidTagLength(tag_len)),
+        }
+    }
+
+    pub fn tag_len(&self) -> usize {
+        self.tag_len
+    }
+
+    // encrypt returns the ciphertext with the tag appended.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        check_message_len(plaintext.len())?;
+        let j0 = self.j0(nonce)?;
+
+        let mut ciphertext = self.ctr(&j0, plaintext);
+        let tag = self.tag(&j0, aad, &ciphertext);
+        ciphertext.extend_from_slice(&tag);
+
+        Ok(ciphertext)
+    }
+
+    // decrypt verifies the tag appended to the ciphertext before decrypting,
+    // and releases no plaintext if verification fails.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        if ciphertext.len() < self.tag_len {
+            return Err(AeadError::TruncatedCiphertext {
+                len: ciphertext.len(),
+                min: self.tag_len,
+            });
+        }
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+        check_message_len(ciphertext.len())?;
+        let j0 = self.j0(nonce)?;
+
+        let expected_tag = self.tag(&j0, aad, ciphertext);
+        if !constant_time_eq(&expected_tag, tag) {
+            return Err(AeadError::AuthenticationFailed);
+        }
+
+        Ok(self.ctr(&j0, ciphertext.to_vec()))
+    }
+
+    fn j0(&self, nonce: &[u8]) -> Result<Block<U16>, AeadError> {
+        if nonce.is_empty() {
+            return Err(AeadError::InvalidNonceLength(0));
+        }
+
+        if nonce.len() == 12 {
+            let mut j0 = GenericArray::default();
+            j0[..12].copy_from_slice(nonce);
+            j0[15] = 1;
+
+            return Ok(j0);
+        }
+
+        let mut ghash = Ghash::new(self.h);
+        ghash.update(nonce);
+        ghash.update(&(nonce.len() as u128 * 8).to_be_bytes());
+
+        Ok(ghash.finalize())
+    }
+
+    // ctr applies the CTR keystream starting from inc32(J_0)
+    fn ctr(&self, j0: &Block<U16>, input: Vec<u8>) -> Vec<u8> {
+        let counter = u32::from_be_bytes([j0[12], j0[13], j0[14], j0[15]]) as u64 + 1;
+        let keystream = Keystream::new(&self.cipher, &j0[..12], CounterLayout::BE32, counter);
+
+        let len = input.len();
+        VernamCipher::new(input.into_iter(), keystream.take(len))
+            .collect::<Result<Vec<u8>, VernamCipherError>>()
+            .unwrap() // SAFETY: the keystream is infinite, so take(len) always yields len bytes
+    }
+
+    fn tag(&self, j0: &Block<U16>, aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        let mut ghash = Ghash::new(self.h);
+        ghash.update(aad);
+        ghash.update(ciphertext);
+
+        let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+        ghash.update(&lengths.to_be_bytes());
+
+        let s = ghash.finalize();
+        let encrypted_j0 = self.cipher.encrypt_block(*j0);
+
+        encrypted_j0
+            .into_iter()
+            .zip(s)
+            .map(|(a, b)| a ^ b)
+            .take(self.tag_len)
+            .collect()
+    }
+}
+
+fn check_message_len(len: usize) -> Result<(), AeadError> {
+    if len as u64 > MAX_MESSAGE_LEN {
+        return Err(AeadError::MessageTooLong {
+            len: len as u64,
+            max: MAX_MESSAGE_LEN,
+        });
+    }
+
+    Ok(())
+}
+
+/// `Ghash` is the universal hash function used by GCM, exposed for
+/// constructions such as GMAC and for nonce-reuse experiments.
+///
+/// Each call to `update` absorbs its input and zero-pads the final partial
+/// block, matching how GCM pads the associated data and ciphertext separately.
+///
+/// Y_i = (Y_{i-1} XOR X_i) * H
+pub struct Ghash {
+    h: u128,
+    y: u128,
+}
+
+impl Ghash {
+    pub fn new(h: Block<U16>) -> Self {
+        Ghash {
+            h: u128::from_be_bytes(h.into()),
+            y: 0,
+        }
+    }
+
+    pub fn update(&mut self, input: &[u8]) {
+        for chunk in input.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+
+            self.y = gf128_mul(self.y ^ u128::from_be_bytes(block), self.h);
+        }
+    }
+
+    pub fn finalize(&self) -> Block<U16> {
+        GenericArray::clone_from_slice(&self.y.to_be_bytes())
+    }
+}
+
+/// Multiplies two elements of GF(2^128) with modulus x^128 + x^7 + x^2 + x + 1,
+/// GCM's finite field.
+///
+/// GCM uses a "reflected" bit order: the most significant bit of the first
+/// byte of a block is the coefficient of x^0. Blocks are therefore loaded as
+/// big-endian `u128`s, where x^0 is the most significant bit.
+///
+/// see more: NIST SP 800-38D, section 6.3
+pub fn gf128_mul(x: u128, y: u128) -> u128 {
+    // R = 11100001 || 0^120, the reduction polynomial without its x^128 term
+    const R: u128 = 0xe1 << 120;
+
+    let mut z = 0;
+    let mut v = y;
+
+    for i in (0..128).rev() {
+        // 1. if the coefficient of x^(127 - i) of x is set, add v to the product
+        if (x >> i) & 1 == 1 {
+            z ^= v;
+        }
+
+        // 2. multiply v by x, reducing by R if the coefficient of x^127 overflows
+        v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+    }
+
+    z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cipher::block::ciphers::aes::Aes, encode::hex::decode};
+
+    const KEY: &str = "feffe9928665731c6d6a8f9467308308";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const PLAINTEXT: &str = concat!(
+        "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+        "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+    );
+
+    // The GCM test cases are from McGrew and Viega's "The Galois/Counter Mode
+    // of Operation (GCM)", which were adopted by NIST's GCM validation suite.
+    struct TestCase {
+        key: &'static str,
+        nonce: &'static str,
+        aad: &'static str,
+        plaintext: &'static str,
+        ciphertext: &'static str,
+        tag: &'static str,
+    }
+
+    #[rustfmt::skip]
+    const TEST_CASES: [TestCase; 7] = [
+        // test case 1: empty plaintext
+        TestCase {
+            key: "00000000000000000000000000000000",
+            nonce: "000000000000000000000000",
+            aad: "",
+            plaintext: "",
+            ciphertext: "",
+            tag: "58e2fccefa7e3061367f1d57a4e7455a",
+        },
+        // test case 2: single zero block
+        TestCase {
+            key: "00000000000000000000000000000000",
+            nonce: "000000000000000000000000",
+            aad: "",
+            plaintext: "00000000000000000000000000000000",
+            ciphertext: "0388dace60b6a392f328c2b971b2fe78",
+            tag: "ab6e47d42cec13bdf53a67b21257bddf",
+        },
+        // test case 3: four blocks, no associated data
+        TestCase {
+            key: KEY,
+            nonce: "cafebabefacedbaddecaf888",
+            aad: "",
+            plaintext: concat!(
+                "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+                "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+            ),
+            ciphertext: concat!(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+                "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+            ),
+            tag: "4d5c2af327cd64a62cf35abd2ba6fab4",
+        },
+        // test case 4: partial final block with associated data
+        TestCase {
+            key: KEY,
+            nonce: "cafebabefacedbaddecaf888",
+            aad: AAD,
+            plaintext: PLAINTEXT,
+            ciphertext: concat!(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+                "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            ),
+            tag: "5bc94fbc3221a5db94fae95ae7121a47",
+        },
+        // test case 5: 64 bit nonce
+        TestCase {
+            key: KEY,
+            nonce: "cafebabefacedbad",
+            aad: AAD,
+            plaintext: PLAINTEXT,
+            ciphertext: concat!(
+                "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c7423",
+                "73806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+            ),
+            tag: "3612d2e79e3b0785561be14aaca2fccb",
+        },
+        // test case 6: 480 bit nonce
+        TestCase {
+            key: KEY,
+            nonce: concat!(
+                "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728",
+                "c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+            ),
+            aad: AAD,
+            plaintext: PLAINTEXT,
+            ciphertext: concat!(
+                "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca7",
+                "01e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+            ),
+            tag: "619cc5aefffe0bfa462af43c1699d050",
+        },
+        // test case 16: aes-256
+        TestCase {
+            key: "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+            nonce: "cafebabefacedbaddecaf888",
+            aad: AAD,
+            plaintext: PLAINTEXT,
+            ciphertext: concat!(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa",
+                "8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+            ),
+            tag: "76fc6ece0f4e1768cddf8853bb2d551b",
+        },
+    ];
+
+    #[test]
+    fn test_gcm_test_cases() {
+        for test_case in TEST_CASES.iter() {
+            let gcm = Gcm::new(Aes::new(decode(test_case.key)).unwrap());
+            let nonce = decode(test_case.nonce);
+            let aad = decode(test_case.aad);
+
+            let mut expected_output = decode(test_case.ciphertext);
+            expected_output.extend(decode(test_case.tag));
+
+            let encrypted = gcm
+                .encrypt(&nonce, &aad, decode(test_case.plaintext))
+                .unwrap();
+            assert_eq!(encrypted, expected_output);
+
+            let decrypted = gcm.decrypt(&nonce, &aad, encrypted).unwrap();
+            assert_eq!(decrypted, decode(test_case.plaintext));
+        }
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        let gcm = Gcm::with_tag_len(Aes::new(decode(KEY)).unwrap(), 12).unwrap();
+        let nonce = decode("cafebabefacedbaddecaf888");
+        let aad = decode(AAD);
+
+        let encrypted = gcm.encrypt(&nonce, &aad, decode(PLAINTEXT)).unwrap();
+        let (_, tag) = encrypted.split_at(encrypted.len() - 12);
+        assert_eq!(tag, &decode("5bc94fbc3221a5db94fae95a")[..]);
+
+        let decrypted = gcm.decrypt(&nonce, &aad, encrypted).unwrap();
+        assert_eq!(decrypted, decode(PLAINTEXT));
+    }
+
+    #[test]
+    fn test_invalid_tag_len() {
+        let gcm = Gcm::with_tag_len(Aes::new(decode(KEY)).unwrap(), 10);
+        assert!(matches!(gcm, Err(AeadError::InvalidTagLength(10))));
+    }
+
+    #[test]
+    fn test_decrypt_fails_closed() {
+        let gcm = Gcm::new(Aes::new(decode(KEY)).unwrap());
+        let nonce = decode("cafebabefacedbaddecaf888");
+        let aad = decode(AAD);
+        let encrypted = gcm.encrypt(&nonce, &aad, decode(PLAINTEXT)).unwrap();
+
+        // flipped ciphertext bit
+        let mut tampered = encrypted.clone();
+        tampered[0] ^= 0x01;
+        assert_eq!(
+            gcm.decrypt(&nonce, &aad, tampered),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        // flipped tag bit
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0x80;
+        assert_eq!(
+            gcm.decrypt(&nonce, &aad, tampered),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        // different associated data
+        assert_eq!(
+            gcm.decrypt(&nonce, b"", encrypted),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            gcm.decrypt(&nonce, &aad, vec![0u8; 15]),
+            Err(AeadError::TruncatedCiphertext { len: 15, min: 16 })
+        );
+    }
+
+    #[test]
+    fn test_empty_nonce() {
+        let gcm = Gcm::new(Aes::new(decode(KEY)).unwrap());
+        assert_eq!(
+            gcm.encrypt(&[], &[], Vec::new()),
+            Err(AeadError::InvalidNonceLength(0))
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_message_too_long() {
+        // too long to allocate, so the length check is tested on its own
+        assert_eq!(check_message_len((1 << 36) - 32), Ok(()));
+        assert_eq!(
+            check_message_len(1 << 36),
+            Err(AeadError::MessageTooLong {
+                len: 1 << 36,
+                max: (1 << 36) - 32,
+            })
+        );
+    }
+
+    #[test]
+    fn test_gmac() {
+        // GMAC is GCM with an empty plaintext, authenticating only the associated
+        // data (gcmEncryptExtIV128.rsp, PTlen = 0, AADlen = 128, Count = 0)
+        let gcm = Gcm::new(Aes::new(decode("77be63708971c4e240d1cb79e8d77feb")).unwrap());
+        let tag = gcm
+            .encrypt(
+                &decode("e0e00f19fed7ba0136a797f3"),
+                &decode("7a43ec1d9c0a5a78a0b16533a6213cab"),
+                Vec::new(),
+            )
+            .unwrap();
+
+        assert_eq!(tag, decode("209fcc8d3675ed938e9c7166709dd946"));
+    }
+
+    #[test]
+    fn test_ghash() {
+        // GCM test case 2: H = encrypt_block(0^128, 0^128), GHASH(A, C) from the spec
+        let h = decode("66e94bd4ef8a2c3b884cfa59ca342b2e");
+        let ciphertext = decode("0388dace60b6a392f328c2b971b2fe78");
+
+        let mut ghash = Ghash::new(GenericArray::clone_from_slice(&h));
+        ghash.update(&ciphertext);
+        ghash.update(&(128u128).to_be_bytes());
+
+        assert_eq!(
+            ghash.finalize().as_slice(),
+            decode("f38cbb1ad69223dcc3457ae5b6b0f885")
+        );
+    }
+
+    #[test]
+    fn test_gf128_mul_identity() {
+        // x^0 is the most significant bit
+        let one = 1u128 << 127;
+        let a = 0x66e94bd4ef8a2c3b884cfa59ca342b2e;
+
+        assert_eq!(gf128_mul(a, one), a);
+        assert_eq!(gf128_mul(one, a), a);
+        assert_eq!(gf128_mul(a, 0), 0);
+    }
+}
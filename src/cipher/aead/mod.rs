@@ -0,0 +1,54 @@
+use thiserror::Error;
+
+use crate::cipher::KeyError;
+
+mod chacha20poly1305;
+mod gcm;
+mod ocb;
+mod secretbox;
+mod siv;
+
+pub use chacha20poly1305::*;
+pub use gcm::*;
+pub use ocb::*;
+pub use secretbox::*;
+pub use siv::*;
+
+// Authenticated encryption with associated data (AEAD) schemes provide both
+// *confidentiality* and *integrity*. Alongside the plaintext, callers may pass
+// associated data (e.g. packet headers) which is authenticated but not encrypted.
+
+// Decryption fails closed: if the tag does not verify, no plaintext is released.
+
+/// Errors that can occur when encrypting or decrypting with an AEAD scheme.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AeadError {
+    /// Occurs when the scheme or its block cipher rejects a (sub)key.
+    #[error(transparent)]
+    InvalidKey(#[from] KeyError),
+
+    /// Occurs when the nonce length is not supported by the scheme.
+    #[error("nonce length {0} is not supported")]
+    InvalidNonceLength(usize),
+
+    /// Occurs when the requested tag length is not supported by the scheme.
+    #[error("tag length {0} is not supported")]
+    InvalidTagLength(usize),
+
+    /// Occurs when more associated data components are passed than the scheme supports.
+    #[error("{0} associated data components exceed the maximum")]
+    TooManyAssociatedData(usize),
+
+    /// Occurs when the plaintext or ciphertext is longer than the scheme supports.
+    #[error("message length {len} exceeds the maximum {max}")]
+    MessageTooLong { len: u64, max: u64 },
+
+    /// Occurs when the ciphertext is too short to contain a tag.
+    #[error("ciphertext length {len} is shorter than the minimum {min}")]
+    TruncatedCiphertext { len: usize, min: usize },
+
+    /// Occurs when the tag does not match the ciphertext and associated data.
+    #[error("authentication tag mismatch")]
+    AuthenticationFailed,
+}
@@ -0,0 +1,387 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::AeadError;
+use crate::{cipher::block::ciphers::BlockCipher, mac::dbl, utils::constant_time_eq};
+
+// Ocb implements OCB3 as defined in RFC 7253. OCB encrypts and authenticates
+// in a single pass with one block cipher call per block, where GCM needs a
+// block cipher call and a GF(2^128) multiplication per block.
+
+// Each block is whitened with an offset, which changes from block to block by
+// XORing in one of a table of key dependent values:
+// L_*   = encrypt_block(0^128, K)
+// L_$   = double(L_*)
+// L_0   = double(L_$), L_i = double(L_{i-1})
+// Offset_i = Offset_{i-1} XOR L_{ntz(i)}
+// C_i      = Offset_i XOR encrypt_block(P_i XOR Offset_i, K)
+
+// The initial offset is derived from the nonce and tag length. A final partial
+// block is encrypted by XORing with encrypt_block(Offset_* , K), where
+// Offset_* = Offset_m XOR L_*. The tag encrypts the XOR checksum of the
+// plaintext blocks, and is masked with HASH(K, A), a sum of whitened,
+// encrypted associated data blocks.
+// T = encrypt_block(Checksum XOR Offset XOR L_$, K) XOR HASH(K, A)
+
+// Reusing a nonce under the same key reveals the XOR of plaintext blocks at
+// the same positions, and breaks authenticity.
+pub struct Ocb<C>
+where
+    C: BlockCipher<U16>,
+{
+    cipher: C,
+    l_star: u128,
+    l_dollar: u128,
+    l: Vec<u128>, // L_i for every ntz(i) of a 64 bit block index
+    tag_len: usize,
+}
+
+impl<C> Ocb<C>
+where
+    C: BlockCipher<U16>,
+{
+    pub fn new(cipher: C) -> Self {
+        let l_star = cipher.encrypt_block(GenericArray::default());
+        let l_dollar = dbl(l_star);
+
+        let mut l = Vec::with_capacity(64);
+        let mut l_i = dbl(l_dollar);
+        for _ in 0..64 {
+            l.push(u128::from_be_bytes(l_i.into()));
+            l_i = dbl(l_i);
+        }
+
+        Ocb {
+            cipher,
+            l_star: u128::from_be_bytes(l_star.into()),
+            l_dollar: u128::from_be_bytes(l_dollar.into()),
+            l,
+            tag_len: 16,
+        }
+    }
+
+    // with_tag_len truncates tags to tag_len bytes. RFC 7253 registers 128,
+    // 96 and 64 bit tags, and this accepts any whole number of bytes in between.
+    pub fn with_tag_len(cipher: C, tag_len: usize) -> Result<Self, AeadError> {
+        match tag_len {
+            8..=16 => {
+                let mut ocb = Ocb::new(cipher);
+                ocb.tag_len = tag_len;
+
+                Ok(ocb)
+            }
+            _ => Err(AeadError::InvalidTagLength(tag_len)),
+        }
+    }
+
+    pub fn tag_len(&self) -> usize {
+        self.tag_len
+    }
+
+    // encrypt returns the ciphertext with the tag appended.
+    pub fn encrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let offset = self.initial_offset(nonce)?;
+
+        let (mut ciphertext, tag) = self.process(offset, aad, &plaintext, Direction::Encrypt);
+        ciphertext.extend_from_slice(&tag[..self.tag_len]);
+
+        Ok(ciphertext)
+    }
+
+    // decrypt verifies the tag appended to the ciphertext, and releases no
+    // plaintext if verification fails.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8],
+        aad: &[u8],
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        if ciphertext.len() < self.tag_len {
+            return Err(AeadError::TruncatedCiphertext {
+                len: ciphertext.len(),
+                min: self.tag_len,
+            });
+        }
+
+        let offset = self.initial_offset(nonce)?;
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - self.tag_len);
+        let (plaintext, expected_tag) = self.process(offset, aad, ciphertext, Direction::Decrypt);
+        if !constant_time_eq(&expected_tag[..self.tag_len], tag) {
+            return Err(AeadError::AuthenticationFailed);
+        }
+
+        Ok(plaintext)
+    }
+
+    // initial_offset derives Offset_0 from nonces of 1 to 15 bytes.
+    // Nonce    = [TAGLEN mod 128]_7 || 0^(120 - len(N)) || 1 || N
+    // Ktop     = encrypt_block(Nonce[1..122] || 0^6, K)
+    // Stretch  = Ktop || (Ktop[1..64] XOR Ktop[9..72])
+    // Offset_0 = Stretch[1 + bottom..128 + bottom], bottom = Nonce[123..128]
+    fn initial_offset(&self, nonce: &[u8]) -> Result<u128, AeadError> {
+        if nonce.is_empty() || nonce.len() > 15 {
+            return Err(AeadError::InvalidNonceLength(nonce.len()));
+        }
+
+        let mut block = [0u8; 16];
+        block[16 - nonce.len()..].copy_from_slice(nonce);
+        block[15 - nonce.len()] |= 0x01;
+        block[0] |= (((self.tag_len * 8) % 128) << 1) as u8;
+
+        let bottom = (block[15] & 0x3f) as u32;
+        block[15] &= 0xc0;
+
+        let ktop = u128::from_be_bytes(self.encipher(block));
+        let stretch = ((ktop >> 64) ^ (ktop >> 56)) as u64;
+
+        Ok(if bottom == 0 {
+            ktop
+        } else {
+            (ktop << bottom) | (stretch >> (64 - bottom)) as u128
+        })
+    }
+
+    // process encrypts or decrypts input, and returns the output with the full
+    // length tag.
+    fn process(
+        &self,
+        mut offset: u128,
+        aad: &[u8],
+        input: &[u8],
+        direction: Direction,
+    ) -> (Vec<u8>, [u8; 16]) {
+        let mut output = Vec::with_capacity(input.len() + 16);
+        let mut checksum = 0u128;
+
+        let mut blocks = input.chunks_exact(16);
+        for (i, block) in (1..).zip(&mut blocks) {
+            offset ^= self.l(i);
+
+            let block = u128::from_be_bytes(block.try_into().unwrap()); // SAFETY: chunks_exact(16)
+            let processed = offset
+                ^ u128::from_be_bytes(match direction {
+                    Direction::Encrypt => self.encipher((block ^ offset).to_be_bytes()),
+                    Direction::Decrypt => self.decipher((block ^ offset).to_be_bytes()),
+                });
+
+            checksum ^= match direction {
+                Direction::Encrypt => block,
+                Direction::Decrypt => processed,
+            };
+            output.extend_from_slice(&processed.to_be_bytes());
+        }
+
+        let remainder = blocks.remainder();
+        if !remainder.is_empty() {
+            offset ^= self.l_star;
+            let pad = self.encipher(offset.to_be_bytes());
+
+            let processed = remainder
+                .iter()
+                .zip(pad)
+                .map(|(x, y)| x ^ y)
+                .collect::<Vec<u8>>();
+
+            // the plaintext is padded with 10* into the checksum
+            let plaintext = match direction {
+                Direction::Encrypt => remainder,
+                Direction::Decrypt => &processed,
+            };
+            checksum ^= pad_block(plaintext);
+            output.extend(processed);
+        }
+
+        let tag =
+            u128::from_be_bytes(self.encipher((checksum ^ offset ^ self.l_dollar).to_be_bytes()))
+                ^ self.hash(aad);
+
+        (output, tag.to_be_bytes())
+    }
+
+    // hash is HASH(K, A), which whitens and encrypts each block of the
+    // associated data independently, and sums the results.
+    fn hash(&self, aad: &[u8]) -> u128 {
+        let mut offset = 0u128;
+        let mut sum = 0u128;
+
+        let mut blocks = aad.chunks_exact(16);
+        for (i, block) in (1..).zip(&mut blocks) {
+            offset ^= self.l(i);
+
+            let block = u128::from_be_bytes(block.try_into().unwrap()); // SAFETY: chunks_exact(16)
+            sum ^= u128::from_be_bytes(self.encipher((block ^ offset).to_be_bytes()));
+        }
+
+        let remainder = blocks.remainder();
+        if !remainder.is_empty() {
+            offset ^= self.l_star;
+            sum ^=
+                u128::from_be_bytes(self.encipher((pad_block(remainder) ^ offset).to_be_bytes()));
+        }
+
+        sum
+    }
+
+    // l returns L_{ntz(i)} for block index i >= 1.
+    fn l(&self, i: u64) -> u128 {
+        self.l[i.trailing_zeros() as usize]
+    }
+
+    fn encipher(&self, block: [u8; 16]) -> [u8; 16] {
+        self.cipher.encrypt_block(block.into()).into()
+    }
+
+    fn decipher(&self, block: [u8; 16]) -> [u8; 16] {
+        self.cipher.decrypt_block(block.into()).into()
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+// pad_block appends 10* to a partial block.
+fn pad_block(partial: &[u8]) -> u128 {
+    let mut block = [0u8; 16];
+    block[..partial.len()].copy_from_slice(partial);
+    block[partial.len()] = 0x80;
+
+    u128::from_be_bytes(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cipher::block::ciphers::aes::Aes, encode::hex::decode};
+
+    fn ocb() -> Ocb<Aes> {
+        Ocb::new(Aes::new(decode("000102030405060708090a0b0c0d0e0f")).unwrap())
+    }
+
+    // RFC 7253 appendix A sample results, with AES-128 and 128 bit tags.
+    // The associated data and plaintext are prefixes of 000102...
+    #[rustfmt::skip]
+    const SAMPLE_RESULTS: [(&str, usize, usize, &str); 9] = [
+        ("bbaa99887766554433221100", 0, 0, "785407bfffc8ad9edcc5520ac9111ee6"),
+        ("bbaa99887766554433221101", 8, 8, "6820b3657b6f615a5725bda0d3b4eb3a257c9af1f8f03009"),
+        ("bbaa99887766554433221102", 8, 0, "81017f8203f081277152fade694a0a00"),
+        ("bbaa99887766554433221103", 0, 8, "45dd69f8f5aae72414054cd1f35d82760b2cd00d2f99bfa9"),
+        ("bbaa99887766554433221104", 16, 16, "571d535b60b277188be5147170a9a22c3ad7a4ff3835b8c5701c1ccec8fc3358"),
+        ("bbaa99887766554433221105", 16, 0, "8cf761b6902ef764462ad86498ca6b97"),
+        ("bbaa99887766554433221106", 0, 16, "5ce88ec2e0692706a915c00aeb8b2396f40e1c743f52436bdf06d8fa1eca343d"),
+        ("bbaa99887766554433221107", 24, 24, "1ca2207308c87c010756104d8840ce1952f09673a448a122c92c62241051f57356d7f3c90bb0e07f"),
+        ("bbaa99887766554433221108", 24, 0, "6dc225a071fc1b9f7c69f93b0f1e10de"),
+    ];
+
+    #[test]
+    fn test_rfc7253_sample_results() {
+        let ocb = ocb();
+        let data = (0..40).collect::<Vec<u8>>();
+
+        for (nonce, aad_len, plaintext_len, ciphertext) in SAMPLE_RESULTS {
+            let nonce = decode(nonce);
+            let aad = &data[..aad_len];
+            let plaintext = data[..plaintext_len].to_vec();
+
+            let encrypted = ocb.encrypt(&nonce, aad, plaintext.clone()).unwrap();
+            assert_eq!(encrypted, decode(ciphertext));
+
+            let decrypted = ocb.decrypt(&nonce, aad, encrypted).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    // RFC 7253 appendix A: the iterated check over every plaintext and
+    // associated data length from 0 to 127 bytes
+    fn all_lengths(key_len: usize, tag_len: usize) -> Vec<u8> {
+        let mut key = vec![0u8; key_len];
+        key[key_len - 1] = (tag_len * 8) as u8;
+        let ocb = Ocb::with_tag_len(Aes::new(key).unwrap(), tag_len).unwrap();
+        let nonce = |n: u32| {
+            let mut nonce = vec![0u8; 8];
+            nonce.extend_from_slice(&n.to_be_bytes());
+            nonce
+        };
+
+        let mut c = Vec::new();
+        for i in 0..128u32 {
+            let s = vec![0u8; i as usize];
+            c.extend(ocb.encrypt(&nonce(3 * i + 1), &s, s.clone()).unwrap());
+            c.extend(ocb.encrypt(&nonce(3 * i + 2), &[], s.clone()).unwrap());
+            c.extend(ocb.encrypt(&nonce(3 * i + 3), &s, Vec::new()).unwrap());
+        }
+
+        ocb.encrypt(&nonce(385), &c, Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_rfc7253_all_lengths() {
+        assert_eq!(
+            all_lengths(16, 16),
+            decode("67e944d23256c5e0b6c61fa22fdf1ea2")
+        );
+        assert_eq!(all_lengths(16, 12), decode("77a3d8e73589158d25d01209"));
+        assert_eq!(all_lengths(16, 8), decode("192c9b7bd90ba06a"));
+        assert_eq!(
+            all_lengths(24, 16),
+            decode("f673f2c3e7174aae7bae986ca9f29e17")
+        );
+        assert_eq!(
+            all_lengths(32, 16),
+            decode("d90eb8e9c977c88b79dd793d7ffa161c")
+        );
+    }
+
+    #[test]
+    fn test_tampering_fails() {
+        let ocb = ocb();
+        let nonce = decode("bbaa99887766554433221107");
+        let data = (0..24).collect::<Vec<u8>>();
+        let encrypted = ocb.encrypt(&nonce, &data, data.clone()).unwrap();
+
+        // flipping any bit of the ciphertext, tag or associated data is detected
+        for i in [0, 16, 23, 24, 39] {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 0x01;
+            assert_eq!(
+                ocb.decrypt(&nonce, &data, tampered),
+                Err(AeadError::AuthenticationFailed)
+            );
+        }
+        assert_eq!(
+            ocb.decrypt(&nonce, &data[1..], encrypted.clone()),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            ocb.decrypt(&nonce, &data, encrypted[..15].to_vec()),
+            Err(AeadError::TruncatedCiphertext { len: 15, min: 16 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let ocb = ocb();
+        assert_eq!(
+            ocb.encrypt(&[], &[], Vec::new()),
+            Err(AeadError::InvalidNonceLength(0))
+        );
+        assert_eq!(
+            ocb.encrypt(&[0u8; 16], &[], Vec::new()),
+            Err(AeadError::InvalidNonceLength(16))
+        );
+
+        let cipher = Aes::new(vec![0u8; 16]).unwrap();
+        assert!(matches!(
+            Ocb::with_tag_len(cipher, 4),
+            Err(AeadError::InvalidTagLength(4))
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+use super::AeadError;
+use crate::{
+    cipher::{stream::XSalsa20, KeyError, StreamCipher},
+    mac::Poly1305,
+    utils::constant_time_eq,
+};
+
+// SecretBox implements NaCl's crypto_secretbox (XSalsa20-Poly1305), in the
+// combined format of libsodium's crypto_secretbox_easy: the 16 byte tag
+// followed by the ciphertext. Boxes produced by NaCl bindings in other
+// languages (e.g. PyNaCl, TweetNaCl.js) can be opened given the nonce, which
+// those bindings usually prepend to the box.
+
+// The first 32 bytes of the XSalsa20 keystream are the one-time Poly1305 key,
+// and the plaintext is encrypted with the keystream that follows them.
+// otk = XSalsa20(K, N)[..32]
+// C   = XSalsa20(K, N)[32..] XOR P
+// T   = Poly1305(otk, C)
+
+// Unlike ChaCha20Poly1305, there is no associated data. The 192 bit nonce
+// can be drawn at random for every box.
+pub struct SecretBox {
+    key: Vec<u8>,
+}
+
+impl SecretBox {
+    pub fn new(key: Vec<u8>) -> Result<Self, AeadError> {
+        if key.len() != 32 {
+            return Err(KeyError::InvalidKeyLength {
+                expected: &[32],
+                actual: key.len(),
+            }
+            .into());
+        }
+
+        Ok(SecretBox { key })
+    }
+
+    // seal returns the tag followed by the ciphertext.
+    pub fn seal(&self, nonce: &[u8], plaintext: Vec<u8>) -> Result<Vec<u8>, AeadError> {
+        let mut cipher = self.cipher(nonce)?;
+        let otk = cipher.encrypt(vec![0u8; 32]);
+
+        let ciphertext = cipher.encrypt(plaintext);
+        let mut sealed = tag(otk, &ciphertext).to_vec();
+        sealed.extend(ciphertext);
+
+        Ok(sealed)
+    }
+
+    // open verifies the tag at the front of the box before decrypting, and
+    // releases no plaintext if verification fails.
+    pub fn open(&self, nonce: &[u8], sealed: Vec<u8>) -> Result<Vec<u8>, AeadError> {
+        let mut cipher = self.cipher(nonce)?;
+        if sealed.len() < 16 {
+            return Err(AeadError::TruncatedCiphertext {
+                len: sealed.len(),
+                min: 16,
+            });
+        }
+
+        let otk = cipher.encrypt(vec![0u8; 32]);
+
+        let (tag_bytes, ciphertext) = sealed.split_at(16);
+        let expected_tag = tag(otk, ciphertext);
+        if !constant_time_eq(&expected_tag, tag_bytes) {
+            return Err(AeadError::AuthenticationFailed);
+        }
+
+        Ok(cipher.decrypt(ciphertext.to_vec()))
+    }
+
+    fn cipher(&self, nonce: &[u8]) -> Result<XSalsa20, AeadError> {
+        if nonce.len() != 24 {
+            return Err(AeadError::InvalidNonceLength(nonce.len()));
+        }
+
+        // SAFETY: the key and nonce lengths have been checked
+        Ok(XSalsa20::new(self.key.clone(), nonce.to_vec()).unwrap())
+    }
+}
+
+fn tag(otk: Vec<u8>, ciphertext: &[u8]) -> [u8; 16] {
+    let mut poly1305 = Poly1305::new(otk).unwrap(); // SAFETY: otk is always 32 bytes
+    poly1305.update(ciphertext);
+
+    poly1305.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    const KEY: &str = "1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389";
+    const NONCE: &str = "69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37";
+
+    // NaCl tests/secretbox.c
+    #[test]
+    fn test_nacl_vector() {
+        let plaintext = decode(concat!(
+            "be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffc",
+            "e5ecbaaf33bd751a1ac728d45e6c61296cdc3c01233561f41db66cce314adb31",
+            "0e3be8250c46f06dceea3a7fa1348057e2f6556ad6b1318a024a838f21af1fde",
+            "048977eb48f59ffd4924ca1c60902e52f0a089bc76897040e082f93776384864",
+            "5e0705",
+        ));
+        let expected_box = decode(concat!(
+            // tag
+            "f3ffc7703f9400e52a7dfb4b3d3305d9",
+            "8e993b9f48681273c29650ba32fc76ce48332ea7164d96a4476fb8c531a1186a",
+            "c0dfc17c98dce87b4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738",
+            "b48eeee314a7cc8ab932164548e526ae90224368517acfeabd6bb3732bc0e9da",
+            "99832b61ca01b6de56244a9e88d5f9b37973f622a43d14a6599b1f654cb45a74",
+            "e355a5",
+        ));
+
+        let secretbox = SecretBox::new(decode(KEY)).unwrap();
+
+        let sealed = secretbox.seal(&decode(NONCE), plaintext.clone()).unwrap();
+        assert_eq!(sealed, expected_box);
+
+        let opened = secretbox.open(&decode(NONCE), sealed).unwrap();
+        assert_eq!(opened, plaintext);
+    }
+
+    #[test]
+    fn test_empty_message() {
+        let secretbox = SecretBox::new(decode(KEY)).unwrap();
+
+        let sealed = secretbox.seal(&decode(NONCE), Vec::new()).unwrap();
+        assert_eq!(sealed.len(), 16);
+        assert_eq!(secretbox.open(&decode(NONCE), sealed), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_tampering_fails() {
+        let secretbox = SecretBox::new(decode(KEY)).unwrap();
+        let sealed = secretbox
+            .seal(&decode(NONCE), b"attack at dawn".to_vec())
+            .unwrap();
+
+        let mut tampered = sealed.clone();
+        tampered[20] ^= 0x01;
+        assert_eq!(
+            secretbox.open(&decode(NONCE), tampered),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        let mut other_nonce = decode(NONCE);
+        other_nonce[0] ^= 0x01;
+        assert_eq!(
+            secretbox.open(&other_nonce, sealed.clone()),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            secretbox.open(&decode(NONCE), sealed[..15].to_vec()),
+            Err(AeadError::TruncatedCiphertext { len: 15, min: 16 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert!(matches!(
+            SecretBox::new(vec![0u8; 16]),
+            Err(AeadError::InvalidKey(KeyError::InvalidKeyLength {
+                actual: 16,
+                ..
+            }))
+        ));
+
+        let secretbox = SecretBox::new(decode(KEY)).unwrap();
+        assert_eq!(
+            secretbox.seal(&[0u8; 12], Vec::new()),
+            Err(AeadError::InvalidNonceLength(12))
+        );
+    }
+}
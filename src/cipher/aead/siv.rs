@@ -0,0 +1,276 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::AeadError;
+use crate::{
+    cipher::{
+        block::{
+            ciphers::BlockCipher,
+            modes::{CounterLayout, Keystream},
+            Block,
+        },
+        stream::{VernamCipher, VernamCipherError},
+    },
+    mac::{dbl, Cmac},
+    utils::constant_time_eq,
+};
+
+// S2V accepts at most 127 input vectors, the last of which is the plaintext.
+const MAX_ASSOCIATED_DATA: usize = 126;
+
+// Siv implements Synthetic Initialization Vector mode as defined in RFC 5297.
+// SIV derives its IV deterministically from the associated data and the
+// plaintext with S2V, a vector-input PRF built on CMAC, and then encrypts the
+// plaintext with CTR mode under that IV.
+
+// V = S2V(K_1, AD_1, ..., AD_n, P)
+// Q = V AND 1^64 || 0^1 || 1^31 || 0^1 || 1^31
+// C = V || CTR(K_2, Q, P)
+
+// Since the IV depends on the plaintext, SIV is nonce-misuse resistant: if a
+// nonce is repeated (or omitted altogether, i.e. deterministic mode), an
+// attacker only learns whether the same (associated data, plaintext) pair was
+// encrypted twice. Nonce-based callers pass the nonce as the final
+// associated data component.
+pub struct Siv<C>
+where
+    C: BlockCipher<U16>,
+{
+    cmac: Cmac<C>,
+    cipher: C,
+}
+
+impl<C> Siv<C>
+where
+    C: BlockCipher<U16>,
+{
+    // new splits the key in half, with the first half keying S2V and the
+    // second half keying CTR. Each half must be a key of the block cipher, so
+    // AES-SIV takes 256, 384 or 512 bit keys.
+    pub fn new(key: Vec<u8>) -> Result<Self, AeadError> {
+        let (mac_key, ctr_key) = key.split_at(key.len() / 2);
+
+        Ok(Siv {
+            cmac: Cmac::new(C::new(mac_key.to_vec())?),
+            cipher: C::new(ctr_key.to_vec())?,
+        })
+    }
+
+    // encrypt returns the synthetic IV followed by the ciphertext.
+    pub fn encrypt(
+        &self,
+        associated_data: &[&[u8]],
+        plaintext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        let v = self.s2v(associated_data, &plaintext)?;
+
+        let mut output = v.to_vec();
+        output.extend(self.ctr(&v, plaintext));
+
+        Ok(output)
+    }
+
+    // decrypt recomputes the synthetic IV from the recovered plaintext and
+    // releases no plaintext if it does not match the received IV.
+    pub fn decrypt(
+        &self,
+        associated_data: &[&[u8]],
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, AeadError> {
+        if ciphertext.len() < 16 {
+            return Err(AeadError::TruncatedCiphertext {
+                len: ciphertext.len(),
+                min: 16,
+            });
+        }
+
+        let (v, ciphertext) = ciphertext.split_at(16);
+        let v = GenericArray::clone_from_slice(v);
+
+        let plaintext = self.ctr(&v, ciphertext.to_vec());
+        let expected_v = self.s2v(associated_data, &plaintext)?;
+        if !constant_time_eq(&expected_v, &v) {
+            return Err(AeadError::AuthenticationFailed);
+        }
+
+        Ok(plaintext)
+    }
+
+    // S2V turns a vector of strings into a single 128 bit PRF output.
+    // D = CMAC(K, 0^128)
+    // D = dbl(D) XOR CMAC(K, S_i)                      for 1 <= i < n
+    // T = S_n xorend D                                 if len(S_n) >= 128
+    //     dbl(D) XOR pad(S_n)                          otherwise
+    // V = CMAC(K, T)
+    fn s2v(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Result<Block<U16>, AeadError> {
+        if associated_data.len() > MAX_ASSOCIATED_DATA {
+            return Err(AeadError::TooManyAssociatedData(associated_data.len()));
+        }
+
+        let mut d = self.cmac.mac(&[0u8; 16]);
+        for ad in associated_data {
+            d = xor(dbl(d), &self.cmac.mac(ad));
+        }
+
+        let t = if plaintext.len() >= 16 {
+            // xorend: XOR D into the final 16 bytes of S_n
+            let (head, tail) = plaintext.split_at(plaintext.len() - 16);
+            let mut t = head.to_vec();
+            t.extend(xor(d, tail));
+
+            t
+        } else {
+            let mut padded = [0u8; 16];
+            padded[..plaintext.len()].copy_from_slice(plaintext);
+            padded[plaintext.len()] = 0x80;
+
+            xor(dbl(d), &padded).to_vec()
+        };
+
+        Ok(self.cmac.mac(&t))
+    }
+
+    // ctr applies the CTR keystream with the synthetic IV as the initial
+    // counter block. Clearing bits 63 and 31 of the IV lets implementations
+    // use 64 (or 32) bit counter arithmetic, so a 64 bit big-endian counter
+    // over the low half of Q matches RFC 5297's 128 bit increment.
+    fn ctr(&self, v: &Block<U16>, input: Vec<u8>) -> Vec<u8> {
+        let mut q = *v;
+        q[8] &= 0x7f;
+        q[12] &= 0x7f;
+
+        let counter = u64::from_be_bytes([q[8], q[9], q[10], q[11], q[12], q[13], q[14], q[15]]);
+        let keystream = Keystream::new(&self.cipher, &q[..8], CounterLayout::BE64, counter);
+
+        let len = input.len();
+        VernamCipher::new(input.into_iter(), keystream.take(len))
+            .collect::<Result<Vec<u8>, VernamCipherError>>()
+            .unwrap() // SAFETY: the keystream is infinite, so take(len) always yields len bytes
+    }
+}
+
+fn xor(a: Block<U16>, b: &[u8]) -> Block<U16> {
+    a.into_iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cipher::{block::ciphers::aes::Aes, KeyError},
+        encode::hex::decode,
+    };
+
+    // RFC 5297 A.1 deterministic authenticated encryption example
+    #[test]
+    fn test_rfc5297_deterministic() {
+        let key = decode("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let ad = decode("101112131415161718191a1b1c1d1e1f2021222324252627");
+        let plaintext = decode("112233445566778899aabbccddee");
+        let expected_output =
+            decode("85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c");
+
+        let siv = Siv::<Aes>::new(key).unwrap();
+        let encrypted = siv.encrypt(&[&ad], plaintext.clone()).unwrap();
+        assert_eq!(encrypted, expected_output);
+
+        let decrypted = siv.decrypt(&[&ad], encrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    // RFC 5297 A.2 nonce-based authenticated encryption example
+    #[test]
+    fn test_rfc5297_nonce_based() {
+        let key = decode("7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f");
+        let ad1 = decode(concat!(
+            "00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa9988",
+            "7766554433221100",
+        ));
+        let ad2 = decode("102030405060708090a0");
+        let nonce = decode("09f911029d74e35bd84156c5635688c0");
+        let plaintext = decode(concat!(
+            "7468697320697320736f6d6520706c61696e7465787420746f20656e63727970",
+            "74207573696e67205349562d414553",
+        ));
+        let expected_output = decode(concat!(
+            "7bdb6e3b432667eb06f4d14bff2fbd0fcb900f2fddbe404326601965c889bf17",
+            "dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d",
+        ));
+
+        let siv = Siv::<Aes>::new(key).unwrap();
+        let encrypted = siv
+            .encrypt(&[&ad1, &ad2, &nonce], plaintext.clone())
+            .unwrap();
+        assert_eq!(encrypted, expected_output);
+
+        let decrypted = siv.decrypt(&[&ad1, &ad2, &nonce], encrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_empty_plaintext() {
+        let key = decode("7f7e7d7c7b7a79787776757473727170404142434445464748494a4b4c4d4e4f");
+        let siv = Siv::<Aes>::new(key).unwrap();
+
+        let encrypted = siv.encrypt(&[], Vec::new()).unwrap();
+        assert_eq!(encrypted, decode("0c425c17acc305c2caa2cfc64360fad8"));
+        assert_eq!(siv.decrypt(&[], encrypted).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let key = decode("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let siv = Siv::<Aes>::new(key).unwrap();
+
+        // the same inputs always produce the same ciphertext, which is the
+        // point of deterministic encryption (e.g. for indexed database keys)
+        let first = siv.encrypt(&[b"users"], b"alice".to_vec()).unwrap();
+        let second = siv.encrypt(&[b"users"], b"alice".to_vec()).unwrap();
+        assert_eq!(first, second);
+
+        let other = siv.encrypt(&[b"users"], b"alicf".to_vec()).unwrap();
+        assert_ne!(first[..16], other[..16]);
+    }
+
+    #[test]
+    fn test_decrypt_fails_closed() {
+        let key = decode("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let ad = decode("101112131415161718191a1b1c1d1e1f2021222324252627");
+        let siv = Siv::<Aes>::new(key).unwrap();
+
+        let encrypted = siv
+            .encrypt(&[&ad], decode("112233445566778899aabbccddee"))
+            .unwrap();
+
+        let mut tampered = encrypted.clone();
+        tampered[20] ^= 0x01;
+        assert_eq!(
+            siv.decrypt(&[&ad], tampered),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        // associated data components are not interchangeable with concatenations
+        assert_eq!(
+            siv.decrypt(&[&ad[..8], &ad[8..]], encrypted.clone()),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        assert_eq!(
+            siv.decrypt(&[&ad], encrypted[..15].to_vec()),
+            Err(AeadError::TruncatedCiphertext { len: 15, min: 16 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_length() {
+        // the error is AES's, for the first half that is not an AES key
+        for (len, half_len) in [(0, 0), (16, 8), (33, 17), (40, 20)] {
+            assert!(matches!(
+                Siv::<Aes>::new(vec![0u8; len]),
+                Err(AeadError::InvalidKey(KeyError::InvalidKeyLength {
+                    expected: &[16, 24, 32],
+                    actual,
+                })) if actual == half_len
+            ));
+        }
+    }
+}
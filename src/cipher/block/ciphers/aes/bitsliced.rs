@@ -0,0 +1,427 @@
+use generic_array::typenum::U16;
+
+use super::{KEY_LENGTHS, RCON};
+use crate::cipher::{
+    block::{ciphers::BlockCipher, Block},
+    KeyError,
+};
+
+// The state is stored bitsliced: bit b of byte i is held in bit i of plane b,
+// so each of the 8 planes carries one bit position of all 16 bytes.
+type State = [u16; 8];
+
+// BitslicedAes implements BlockCipher for AES without secret-dependent memory
+// accesses or branches. Aes's S-box lookups index a table with key-dependent
+// bytes, so an attacker sharing the CPU cache can tell which cache lines were
+// touched and recover the key[0]. BitslicedAes instead evaluates the S-box as a
+// boolean circuit over all 16 bytes of the state at once, and implements
+// ShiftRows and MixColumns with fixed shifts and XORs, so every block takes the
+// same instructions and memory accesses regardless of the key or plaintext.
+
+// The trade-off is speed: with a single block per call each plane is only 16
+// bits wide, so most of every register goes unused. Implementations such as
+// BearSSL's aes_ct64 bitslice several blocks at once to win that back.
+
+// [0]: https://cr.yp.to/antiforgery/cachetiming-20050414.pdf
+pub struct BitslicedAes {
+    rounds: usize,
+    round_keys: Vec<State>,
+}
+
+impl BlockCipher<U16> for BitslicedAes {
+    fn new(key: Vec<u8>) -> Result<Self, KeyError> {
+        let rounds = match key.len() {
+            16 => 10,
+            24 => 12,
+            32 => 14,
+            actual => {
+                return Err(KeyError::InvalidKeyLength {
+                    expected: &KEY_LENGTHS,
+                    actual,
+                })
+            }
+        };
+
+        Ok(BitslicedAes {
+            rounds,
+            round_keys: BitslicedAes::key_expansion(&key, rounds),
+        })
+    }
+
+    fn encrypt_block(&self, block: Block<U16>) -> Block<U16> {
+        let mut state = bitslice(&block);
+        state = xor(state, self.round_keys[0]);
+
+        for &round_key in self.round_keys.iter().take(self.rounds).skip(1) {
+            state = sub_bytes(state);
+            state = shift_rows(state);
+            state = mix_cols(state);
+            state = xor(state, round_key);
+        }
+
+        state = sub_bytes(state);
+        state = shift_rows(state);
+        state = xor(state, self.round_keys[self.rounds]);
+
+        unbitslice(state)
+    }
+
+    fn decrypt_block(&self, block: Block<U16>) -> Block<U16> {
+        let mut state = bitslice(&block);
+        state = xor(state, self.round_keys[self.rounds]);
+        state = inv_shift_rows(state);
+        state = inv_sub_bytes(state);
+
+        for &round_key in self.round_keys[1..self.rounds].iter().rev() {
+            state = xor(state, round_key);
+            state = inv_mix_cols(state);
+            state = inv_shift_rows(state);
+            state = inv_sub_bytes(state);
+        }
+
+        state = xor(state, self.round_keys[0]);
+
+        unbitslice(state)
+    }
+}
+
+impl BitslicedAes {
+    // key_expansion follows the same key schedule as Aes, but SubWord goes
+    // through the S-box circuit since the cipher key is just as secret as the
+    // state. The round keys are stored bitsliced, ready to XOR into the state.
+    fn key_expansion(key: &[u8], rounds: usize) -> Vec<State> {
+        let words_per_key_size = key.len() / 4; // 4, 6, or 8
+        let mut words = key
+            .chunks_exact(4)
+            .map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]]))
+            .collect::<Vec<u32>>();
+
+        for i in words_per_key_size..4 * (rounds + 1) {
+            let mut prev = words[i - 1];
+            if i % words_per_key_size == 0 {
+                prev = sub_word(prev.rotate_left(8)) ^ RCON[i / words_per_key_size];
+            } else if words_per_key_size > 6 && i % words_per_key_size == 4 {
+                prev = sub_word(prev);
+            }
+
+            words.push(words[i - words_per_key_size] ^ prev);
+        }
+
+        words
+            .chunks_exact(4)
+            .map(|round_key| {
+                bitslice(
+                    &round_key
+                        .iter()
+                        .flat_map(|word| word.to_be_bytes())
+                        .collect::<Vec<u8>>(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn sub_word(word: u32) -> u32 {
+    let mut block = [0u8; 16];
+    block[..4].copy_from_slice(&word.to_be_bytes());
+    let substituted = unbitslice(sub_bytes(bitslice(&block)));
+
+    u32::from_be_bytes([
+        substituted[0],
+        substituted[1],
+        substituted[2],
+        substituted[3],
+    ])
+}
+
+fn bitslice(bytes: &[u8]) -> State {
+    let mut state = [0u16; 8];
+    for (i, byte) in bytes.iter().enumerate() {
+        for (b, plane) in state.iter_mut().enumerate() {
+            *plane |= (((byte >> b) & 1) as u16) << i;
+        }
+    }
+
+    state
+}
+
+fn unbitslice(state: State) -> Block<U16> {
+    (0..16)
+        .map(|i| {
+            state.iter().enumerate().fold(0u8, |byte, (b, plane)| {
+                byte | ((((plane >> i) & 1) as u8) << b)
+            })
+        })
+        .collect()
+}
+
+fn xor(a: State, b: State) -> State {
+    std::array::from_fn(|i| a[i] ^ b[i])
+}
+
+// sub_bytes evaluates the S-box on all 16 bytes with Boyar and Peralta's 113
+// gate circuit[0]. The circuit computes inversion in GF(2^8) through the tower
+// field GF(((2^2)^2)^2), with the affine transformation folded into its top and
+// bottom linear layers. x_0 and s_0 are the most significant bits.
+
+// [0]: https://eprint.iacr.org/2011/332.pdf
+fn sub_bytes(q: State) -> State {
+    let (x0, x1, x2, x3) = (q[7], q[6], q[5], q[4]);
+    let (x4, x5, x6, x7) = (q[3], q[2], q[1], q[0]);
+
+    // top linear transformation
+    let y14 = x3 ^ x5;
+    let y13 = x0 ^ x6;
+    let y9 = x0 ^ x3;
+    let y8 = x0 ^ x5;
+    let t0 = x1 ^ x2;
+    let y1 = t0 ^ x7;
+    let y4 = y1 ^ x3;
+    let y12 = y13 ^ y14;
+    let y2 = y1 ^ x0;
+    let y5 = y1 ^ x6;
+    let y3 = y5 ^ y8;
+    let t1 = x4 ^ y12;
+    let y15 = t1 ^ x5;
+    let y20 = t1 ^ x1;
+    let y6 = y15 ^ x7;
+    let y10 = y15 ^ t0;
+    let y11 = y20 ^ y9;
+    let y7 = x7 ^ y11;
+    let y17 = y10 ^ y11;
+    let y19 = y10 ^ y8;
+    let y16 = t0 ^ y11;
+    let y21 = y13 ^ y16;
+    let y18 = x0 ^ y16;
+
+    // non-linear section
+    let t2 = y12 & y15;
+    let t3 = y3 & y6;
+    let t4 = t3 ^ t2;
+    let t5 = y4 & x7;
+    let t6 = t5 ^ t2;
+    let t7 = y13 & y16;
+    let t8 = y5 & y1;
+    let t9 = t8 ^ t7;
+    let t10 = y2 & y7;
+    let t11 = t10 ^ t7;
+    let t12 = y9 & y11;
+    let t13 = y14 & y17;
+    let t14 = t13 ^ t12;
+    let t15 = y8 & y10;
+    let t16 = t15 ^ t12;
+    let t17 = t4 ^ t14;
+    let t18 = t6 ^ t16;
+    let t19 = t9 ^ t14;
+    let t20 = t11 ^ t16;
+    let t21 = t17 ^ y20;
+    let t22 = t18 ^ y19;
+    let t23 = t19 ^ y21;
+    let t24 = t20 ^ y18;
+
+    let t25 = t21 ^ t22;
+    let t26 = t21 & t23;
+    let t27 = t24 ^ t26;
+    let t28 = t25 & t27;
+    let t29 = t28 ^ t22;
+    let t30 = t23 ^ t24;
+    let t31 = t22 ^ t26;
+    let t32 = t31 & t30;
+    let t33 = t32 ^ t24;
+    let t34 = t23 ^ t33;
+    let t35 = t27 ^ t33;
+    let t36 = t24 & t35;
+    let t37 = t36 ^ t34;
+    let t38 = t27 ^ t36;
+    let t39 = t29 & t38;
+    let t40 = t25 ^ t39;
+
+    let t41 = t40 ^ t37;
+    let t42 = t29 ^ t33;
+    let t43 = t29 ^ t40;
+    let t44 = t33 ^ t37;
+    let t45 = t42 ^ t41;
+    let z0 = t44 & y15;
+    let z1 = t37 & y6;
+    let z2 = t33 & x7;
+    let z3 = t43 & y16;
+    let z4 = t40 & y1;
+    let z5 = t29 & y7;
+    let z6 = t42 & y11;
+    let z7 = t45 & y17;
+    let z8 = t41 & y10;
+    let z9 = t44 & y12;
+    let z10 = t37 & y3;
+    let z11 = t33 & y4;
+    let z12 = t43 & y13;
+    let z13 = t40 & y5;
+    let z14 = t29 & y2;
+    let z15 = t42 & y9;
+    let z16 = t45 & y14;
+    let z17 = t41 & y8;
+
+    // bottom linear transformation
+    let t46 = z15 ^ z16;
+    let t47 = z10 ^ z11;
+    let t48 = z5 ^ z13;
+    let t49 = z9 ^ z10;
+    let t50 = z2 ^ z12;
+    let t51 = z2 ^ z5;
+    let t52 = z7 ^ z8;
+    let t53 = z0 ^ z3;
+    let t54 = z6 ^ z7;
+    let t55 = z16 ^ z17;
+    let t56 = z12 ^ t48;
+    let t57 = t50 ^ t53;
+    let t58 = z4 ^ t46;
+    let t59 = z3 ^ t54;
+    let t60 = t46 ^ t57;
+    let t61 = z14 ^ t57;
+    let t62 = t52 ^ t58;
+    let t63 = t49 ^ t58;
+    let t64 = z4 ^ t59;
+    let t65 = t61 ^ t62;
+    let t66 = z1 ^ t63;
+    let s0 = t59 ^ t63;
+    let s6 = t56 ^ !t62;
+    let s7 = t48 ^ !t60;
+    let t67 = t64 ^ t65;
+    let s3 = t53 ^ t66;
+    let s4 = t51 ^ t66;
+    let s5 = t47 ^ t65;
+    let s1 = t64 ^ !s3;
+    let s2 = t55 ^ !t67;
+
+    [s7, s6, s5, s4, s3, s2, s1, s0]
+}
+
+// Since S(x) = A(x^-1) for the affine transformation A, and inversion is its
+// own inverse, the inverse S-box is S^-1(x) = A^-1(S(A^-1(x))). This reuses the
+// forward circuit at the cost of two cheap linear layers.
+fn inv_sub_bytes(q: State) -> State {
+    inv_affine(sub_bytes(inv_affine(q)))
+}
+
+// inv_affine computes A^-1(x) = (x <<< 1) ^ (x <<< 3) ^ (x <<< 6) ^ 0x05, where
+// rotating a byte left moves plane b to plane b + 1.
+fn inv_affine(q: State) -> State {
+    let mut output: State =
+        std::array::from_fn(|b| q[(b + 7) % 8] ^ q[(b + 5) % 8] ^ q[(b + 2) % 8]);
+    output[0] = !output[0];
+    output[2] = !output[2];
+
+    output
+}
+
+// Byte i of the state sits in row i % 4 of column i / 4, so within a plane each
+// row is every fourth bit (e.g. row 1 is 0x2222). ShiftRows rotates row r left
+// by r columns, which moves its bits down by 4r positions.
+fn shift_rows(q: State) -> State {
+    q.map(|plane| {
+        (plane & 0x1111)
+            | (plane & 0x2222).rotate_right(4)
+            | (plane & 0x4444).rotate_right(8)
+            | (plane & 0x8888).rotate_right(12)
+    })
+}
+
+fn inv_shift_rows(q: State) -> State {
+    q.map(|plane| {
+        (plane & 0x1111)
+            | (plane & 0x2222).rotate_left(4)
+            | (plane & 0x4444).rotate_left(8)
+            | (plane & 0x8888).rotate_left(12)
+    })
+}
+
+// rotate_rows rotates every column of the state up by n rows, so that row r
+// holds what was in row r + n.
+fn rotate_rows(q: State, n: u32) -> State {
+    let low_rows = 0x1111 * ((1 << (4 - n)) - 1);
+
+    q.map(|plane| ((plane >> n) & low_rows) | ((plane << (4 - n)) & !low_rows))
+}
+
+// xtime multiplies every byte by x in GF(2^8). Shifting a byte left moves each
+// plane up by one, and the carry out of the top bit is reduced by 0x1b, i.e.
+// XORed into planes 0, 1, 3 and 4.
+fn xtime(q: State) -> State {
+    [
+        q[7],
+        q[0] ^ q[7],
+        q[1],
+        q[2] ^ q[7],
+        q[3] ^ q[7],
+        q[4],
+        q[5],
+        q[6],
+    ]
+}
+
+// MixColumns computes 2•a_r ^ 3•a_{r+1} ^ a_{r+2} ^ a_{r+3} for every row r,
+// which rearranges to 2•(a_r ^ a_{r+1}) ^ a_{r+1} ^ a_{r+2} ^ a_{r+3}.
+fn mix_cols(q: State) -> State {
+    let q1 = rotate_rows(q, 1);
+    let q2 = rotate_rows(q, 2);
+    let q3 = rotate_rows(q, 3);
+
+    xor(xor(xtime(xor(q, q1)), q1), xor(q2, q3))
+}
+
+// InvMixColumns factors into MixColumns after a cheap preprocessing step:
+// a_r ^= 4•(a_r ^ a_{r+2}). This avoids multiplying by 9, 11, 13 and 14.
+fn inv_mix_cols(q: State) -> State {
+    let q2 = rotate_rows(q, 2);
+
+    mix_cols(xor(q, xtime(xtime(xor(q, q2)))))
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::GenericArray;
+
+    use super::super::{INV_SBOX, SBOX};
+    use super::*;
+
+    #[test]
+    fn test_bitslice_round_trip() {
+        let block = GenericArray::from([
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ]);
+
+        let state = bitslice(&block);
+        assert_eq!(state[0], 0b1010_1010_1010_1010);
+        assert_eq!(unbitslice(state), block);
+    }
+
+    #[test]
+    fn test_sbox_circuit() {
+        for chunk in (0..=255).collect::<Vec<u8>>().chunks(16) {
+            let substituted = unbitslice(sub_bytes(bitslice(chunk)));
+            let inverted = unbitslice(inv_sub_bytes(bitslice(chunk)));
+
+            for (i, &byte) in chunk.iter().enumerate() {
+                assert_eq!(substituted[i], SBOX[byte as usize]);
+                assert_eq!(inverted[i], INV_SBOX[byte as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_cols() {
+        // FIPS-197 appendix B, round 1
+        let input = GenericArray::from([
+            0xd4, 0xbf, 0x5d, 0x30, 0xe0, 0xb4, 0x52, 0xae, 0xb8, 0x41, 0x11, 0xf1, 0x1e, 0x27,
+            0x98, 0xe5,
+        ]);
+        let expected_output = [
+            0x04, 0x66, 0x81, 0xe5, 0xe0, 0xcb, 0x19, 0x9a, 0x48, 0xf8, 0xd3, 0x7a, 0x28, 0x06,
+            0x26, 0x4c,
+        ];
+
+        let mixed = mix_cols(bitslice(&input));
+        assert_eq!(unbitslice(mixed).as_slice(), expected_output);
+        assert_eq!(unbitslice(inv_mix_cols(mixed)), input);
+    }
+}
@@ -6,6 +6,14 @@ use crate::cipher::stream::VernamCipherError;
 
 use super::Block;
 use super::BlockCipher;
+use crate::cipher::KeyError;
+
+mod bitsliced;
+
+pub use bitsliced::*;
+
+// AES keys are 128, 192 or 256 bits.
+const KEY_LENGTHS: [usize; 3] = [16, 24, 32];
 
 #[derive(Copy, Clone)]
 enum KeyLength {
@@ -14,29 +22,40 @@ enum KeyLength {
     Length256 = 256,
 }
 
+/// Selects how [`Aes`] evaluates its rounds. Both backends produce identical output.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AesBackend {
+    /// Applies SubBytes, ShiftRows, MixColumns and AddRoundKey step by step as
+    /// written in FIPS-197.
+    #[default]
+    Reference,
+
+    /// Merges SubBytes, ShiftRows and MixColumns into four lookups per column
+    /// into 1KiB precomputed tables. Much faster, but the table indices depend
+    /// on secret state, so an attacker sharing the CPU cache can recover the key
+    /// from timing measurements. See [`BitslicedAes`] for a constant-time AES.
+    TTable,
+}
+
 // Aes implements BlockCipher for the Advanced Encryption Standard
 // formerly known as Rijndael, as defined in the U.S. Federal Information
 // Processing Standards Publication 197. The implementation's conformance is
 // verified from the National Institute of Standards and Technology's AES Algorithm
 // Validaton Suite.
+
+// The key schedule is expanded once at construction, along with the round keys
+// of the equivalent inverse cipher (FIPS-197 section 5.3.5), so encrypting or
+// decrypting a block never touches the cipher key.
 pub struct Aes {
-    key: Vec<u8>,
-    key_length: KeyLength,
+    backend: AesBackend,
+    rounds: usize,
+    round_keys: Vec<Block<U16>>,
+    inv_round_keys: Vec<Block<U16>>,
 }
 
 impl BlockCipher<U16> for Aes {
-    fn new(key: Vec<u8>) -> Self {
-        let key_length = match key.len() {
-            16 => KeyLength::Length128,
-            24 => KeyLength::Length192,
-            32 => KeyLength::Length256,
-            _ => {
-                // error out
-                todo!()
-            }
-        };
-
-        Aes { key, key_length }
+    fn new(key: Vec<u8>) -> Result<Self, KeyError> {
+        Aes::with_backend(key, AesBackend::default())
     }
 
     // AES is based on a design principle known as a substitution–permutation network,
@@ -48,17 +67,57 @@ impl BlockCipher<U16> for Aes {
     // any multiple of 32 bits, with a minimum of 128 and a maximum of 256 bits. Most
     // AES calculations are done in a particular finite field.
 
-    // AES operates on a 4 × 4 column-major order array of 16 bytes b0, b1, ..., b15
+    // AES operates on a 4 × 4 column-major order array of 16 bytes b0, b1, ..., b15
     // termed the state.
     fn encrypt_block(&self, block: Block<U16>) -> Block<U16> {
-        // 1. KeyExpansion – round keys are derived from the cipher key using the AES key
-        // schedule. AES requires a separate 128-bit round key block for each round plus one more.
-        let rounds = match self.key_length {
+        match self.backend {
+            AesBackend::Reference => self.encrypt_block_reference(block),
+            AesBackend::TTable => self.encrypt_block_ttable(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: Block<U16>) -> Block<U16> {
+        match self.backend {
+            AesBackend::Reference => self.decrypt_block_reference(block),
+            AesBackend::TTable => self.decrypt_block_ttable(block),
+        }
+    }
+}
+
+impl Aes {
+    pub fn with_backend(key: Vec<u8>, backend: AesBackend) -> Result<Self, KeyError> {
+        let key_length = match key.len() {
+            16 => KeyLength::Length128,
+            24 => KeyLength::Length192,
+            32 => KeyLength::Length256,
+            actual => {
+                return Err(KeyError::InvalidKeyLength {
+                    expected: &KEY_LENGTHS,
+                    actual,
+                })
+            }
+        };
+        let rounds = match key_length {
             KeyLength::Length128 => 10,
             KeyLength::Length192 => 12,
             KeyLength::Length256 => 14,
         };
-        let round_keys = self.key_expansion(rounds);
+
+        let mut aes = Aes {
+            backend,
+            rounds,
+            round_keys: Aes::key_expansion(&key, key_length, rounds),
+            inv_round_keys: Vec::new(),
+        };
+        aes.inv_round_keys = aes.inv_key_expansion();
+
+        Ok(aes)
+    }
+
+    fn encrypt_block_reference(&self, block: Block<U16>) -> Block<U16> {
+        // 1. KeyExpansion – round keys are derived from the cipher key using the AES key
+        // schedule. AES requires a separate 128-bit round key block for each round plus one more.
+        let round_keys = &self.round_keys;
 
         // 2. Initial round key addition:
         // AddRoundKey – each byte of the state is combined with a byte of the round key
@@ -74,11 +133,11 @@ impl BlockCipher<U16> for Aes {
         // ---3. MixColumns – a linear mixing operation which operates on the columns of
         //                    the state, combining the four bytes in each column.
         // ---4. AddRoundKey
-        for r in 1..rounds {
+        for &round_key in round_keys.iter().take(self.rounds).skip(1) {
             encrypted_block = self.sub_bytes(encrypted_block);
             encrypted_block = self.shift_rows(encrypted_block);
             encrypted_block = self.mix_cols(encrypted_block);
-            encrypted_block = self.add_round_key(encrypted_block, round_keys[r]);
+            encrypted_block = self.add_round_key(encrypted_block, round_key);
         }
 
         // 4. Final round (making 10, 12 or 14 rounds in total):
@@ -87,41 +146,107 @@ impl BlockCipher<U16> for Aes {
         // ---3. AddRoundKey
         encrypted_block = self.sub_bytes(encrypted_block);
         encrypted_block = self.shift_rows(encrypted_block);
-        encrypted_block = self.add_round_key(encrypted_block, round_keys[rounds]);
+        encrypted_block = self.add_round_key(encrypted_block, round_keys[self.rounds]);
 
         encrypted_block
     }
 
-    fn decrypt_block(&self, block: Block<U16>) -> Block<U16> {
-        let rounds = match self.key_length {
-            KeyLength::Length128 => 10,
-            KeyLength::Length192 => 12,
-            KeyLength::Length256 => 14,
-        };
-        let round_keys = self.key_expansion(rounds);
+    // The equivalent inverse cipher has the same sequence of steps as the cipher,
+    // with each step replaced by its inverse and InvMixColumns folded into the
+    // round keys, which is what lets the T-table backend share its round structure
+    // between encryption and decryption.
+    fn decrypt_block_reference(&self, block: Block<U16>) -> Block<U16> {
+        let inv_round_keys = &self.inv_round_keys;
 
         let mut decrypted_block = block;
+        decrypted_block = self.add_round_key(decrypted_block, inv_round_keys[0]);
 
-        // final round, first
-        decrypted_block = self.add_round_key(decrypted_block, round_keys[rounds]);
-        decrypted_block = self.inv_shift_rows(decrypted_block);
-        decrypted_block = self.inv_sub_bytes(decrypted_block);
-
-        for r in (1..rounds).rev() {
-            // straightforward decryption algorithm
-            decrypted_block = self.add_round_key(decrypted_block, round_keys[r]);
-            decrypted_block = self.inv_mix_cols(decrypted_block);
-            decrypted_block = self.inv_shift_rows(decrypted_block);
+        for &inv_round_key in inv_round_keys.iter().take(self.rounds).skip(1) {
             decrypted_block = self.inv_sub_bytes(decrypted_block);
+            decrypted_block = self.inv_shift_rows(decrypted_block);
+            decrypted_block = self.inv_mix_cols(decrypted_block);
+            decrypted_block = self.add_round_key(decrypted_block, inv_round_key);
         }
 
-        decrypted_block = self.add_round_key(decrypted_block, round_keys[0]);
+        decrypted_block = self.inv_sub_bytes(decrypted_block);
+        decrypted_block = self.inv_shift_rows(decrypted_block);
+        decrypted_block = self.add_round_key(decrypted_block, inv_round_keys[self.rounds]);
 
         decrypted_block
     }
-}
 
-impl Aes {
+    // The T-table implementation treats each column of the state as a big-endian
+    // u32 and computes a full round of a column as
+    // TE[a_0] ^ TE[a_1] >>> 8 ^ TE[a_2] >>> 16 ^ TE[a_3] >>> 24 ^ k
+    // where a_r is the byte ShiftRows moves into row r, and TE[x] is the column
+    // MixColumns produces from S(x) in row 0, i.e. (2•S(x), S(x), S(x), 3•S(x)).
+    // Rotating the entry by 8 bits moves S(x) down a row.
+
+    // see more: https://csrc.nist.gov/csrc/media/projects/cryptographic-standards-and-guidelines/documents/aes-development/rijndael-ammended.pdf (section 5.2.1)
+    fn encrypt_block_ttable(&self, block: Block<U16>) -> Block<U16> {
+        let round_keys = &self.round_keys;
+        let mut state: [u32; 4] =
+            std::array::from_fn(|c| column(&block, c) ^ column(&round_keys[0], c));
+
+        for round_key in round_keys.iter().take(self.rounds).skip(1) {
+            state = std::array::from_fn(|c| {
+                TE[(state[c] >> 24) as usize]
+                    ^ TE[(state[(c + 1) % 4] >> 16) as usize & 0xff].rotate_right(8)
+                    ^ TE[(state[(c + 2) % 4] >> 8) as usize & 0xff].rotate_right(16)
+                    ^ TE[state[(c + 3) % 4] as usize & 0xff].rotate_right(24)
+                    ^ column(round_key, c)
+            });
+        }
+
+        // the final round has no MixColumns, so it falls back to the S-box
+        let final_key = &round_keys[self.rounds];
+        (0..4)
+            .flat_map(|c| {
+                let word = u32::from_be_bytes([
+                    SBOX[(state[c] >> 24) as usize],
+                    SBOX[(state[(c + 1) % 4] >> 16) as usize & 0xff],
+                    SBOX[(state[(c + 2) % 4] >> 8) as usize & 0xff],
+                    SBOX[state[(c + 3) % 4] as usize & 0xff],
+                ]);
+
+                (word ^ column(final_key, c)).to_be_bytes()
+            })
+            .collect()
+    }
+
+    // Decryption mirrors encryption through the equivalent inverse cipher, with
+    // TD[x] = (14•S^-1(x), 9•S^-1(x), 13•S^-1(x), 11•S^-1(x)) and InvShiftRows
+    // pulling row r from column c - r instead of c + r.
+    fn decrypt_block_ttable(&self, block: Block<U16>) -> Block<U16> {
+        let inv_round_keys = &self.inv_round_keys;
+        let mut state: [u32; 4] =
+            std::array::from_fn(|c| column(&block, c) ^ column(&inv_round_keys[0], c));
+
+        for inv_round_key in inv_round_keys.iter().take(self.rounds).skip(1) {
+            state = std::array::from_fn(|c| {
+                TD[(state[c] >> 24) as usize]
+                    ^ TD[(state[(c + 3) % 4] >> 16) as usize & 0xff].rotate_right(8)
+                    ^ TD[(state[(c + 2) % 4] >> 8) as usize & 0xff].rotate_right(16)
+                    ^ TD[state[(c + 1) % 4] as usize & 0xff].rotate_right(24)
+                    ^ column(inv_round_key, c)
+            });
+        }
+
+        let final_key = &inv_round_keys[self.rounds];
+        (0..4)
+            .flat_map(|c| {
+                let word = u32::from_be_bytes([
+                    INV_SBOX[(state[c] >> 24) as usize],
+                    INV_SBOX[(state[(c + 3) % 4] >> 16) as usize & 0xff],
+                    INV_SBOX[(state[(c + 2) % 4] >> 8) as usize & 0xff],
+                    INV_SBOX[state[(c + 1) % 4] as usize & 0xff],
+                ]);
+
+                (word ^ column(final_key, c)).to_be_bytes()
+            })
+            .collect()
+    }
+
     // AES uses a key schedule to expand a short key into a number of separate
     // round keys. The three AES variants have a different number of rounds.
     // Each variant requires a separate 128-bit round key for each round plus one
@@ -131,8 +256,8 @@ impl Aes {
     // [0]: https://en.wikipedia.org/wiki/Slide_attack
 
     // see more: https://en.wikipedia.org/wiki/AES_key_schedule
-    fn key_expansion(&self, rounds: usize) -> Vec<Block<U16>> {
-        let words_per_key_size = self.key_length as usize / 32; // 4, 6, or 8
+    fn key_expansion(key: &[u8], key_length: KeyLength, rounds: usize) -> Vec<Block<U16>> {
+        let words_per_key_size = key_length as usize / 32; // 4, 6, or 8
         let word_length = 4 * (rounds + 1); // because round_keys are always 128 bits, 4*32=128
         let mut u32_words = vec![0; word_length];
         let mut i = 0;
@@ -140,12 +265,8 @@ impl Aes {
         // 1. K0 is set to the root key
         // so build u32 words from the key's u8s and set them
         while i < words_per_key_size {
-            let word = u32::from_be_bytes([
-                self.key[i * 4],
-                self.key[i * 4 + 1],
-                self.key[i * 4 + 2],
-                self.key[i * 4 + 3],
-            ]);
+            let word =
+                u32::from_be_bytes([key[i * 4], key[i * 4 + 1], key[i * 4 + 2], key[i * 4 + 3]]);
 
             u32_words[i] = word;
             i += 1;
@@ -157,9 +278,9 @@ impl Aes {
         while i < u32_words.len() {
             prev = u32_words[i - 1];
             if i % words_per_key_size == 0 {
-                prev = self.sub_word(prev.rotate_left(8)) ^ RCON[i / words_per_key_size];
+                prev = Aes::sub_word(prev.rotate_left(8)) ^ RCON[i / words_per_key_size];
             } else if words_per_key_size > 6 && i % words_per_key_size == 4 {
-                prev = self.sub_word(prev);
+                prev = Aes::sub_word(prev);
             }
 
             u32_words[i] = u32_words[i - words_per_key_size] ^ prev;
@@ -185,7 +306,27 @@ impl Aes {
         round_key_buffers
     }
 
-    fn sub_word(&self, word: u32) -> u32 {
+    // inv_key_expansion derives the equivalent inverse cipher's round keys from the
+    // cipher's: the round keys are used in reverse order, and every round key but
+    // the first and last has InvMixColumns applied. Since InvMixColumns is linear,
+    // InvMixColumns(state XOR k) = InvMixColumns(state) XOR InvMixColumns(k), which
+    // lets decryption apply InvMixColumns before AddRoundKey like encryption does.
+    fn inv_key_expansion(&self) -> Vec<Block<U16>> {
+        self.round_keys
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &round_key)| {
+                if i == 0 || i == self.rounds {
+                    round_key
+                } else {
+                    self.inv_mix_cols(round_key)
+                }
+            })
+            .collect()
+    }
+
+    fn sub_word(word: u32) -> u32 {
         let mut bytes = u32::to_be_bytes(word);
         for i in 0..bytes.len() {
             bytes[i] = SBOX[bytes[i] as usize]; // SAFETY: u8 will never be larger than 32 bits
@@ -514,9 +655,80 @@ const RCON: [u32; 11] = [
     0x36_00_00_00,
 ];
 
+// column reads the c-th column of the state (or a round key) as a big-endian word.
+fn column(block: &Block<U16>, c: usize) -> u32 {
+    u32::from_be_bytes([
+        block[4 * c],
+        block[4 * c + 1],
+        block[4 * c + 2],
+        block[4 * c + 3],
+    ])
+}
+
+// xtime multiplies a byte by x (i.e. 0x02) in Rijndael's finite field.
+const fn xtime(b: u8) -> u8 {
+    (b << 1) ^ ((b >> 7) * 0x1b)
+}
+
+// Encryption T-table: TE[x] = (2•S(x), S(x), S(x), 3•S(x)).
+const TE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let s = SBOX[i];
+        table[i] = u32::from_be_bytes([xtime(s), s, s, xtime(s) ^ s]);
+        i += 1;
+    }
+
+    table
+};
+
+// Decryption T-table: TD[x] = (14•S^-1(x), 9•S^-1(x), 13•S^-1(x), 11•S^-1(x)).
+const TD: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let s = INV_SBOX[i];
+        let (s2, s4, s8) = (xtime(s), xtime(xtime(s)), xtime(xtime(xtime(s))));
+        table[i] = u32::from_be_bytes([s8 ^ s4 ^ s2, s8 ^ s, s8 ^ s4 ^ s, s8 ^ s2 ^ s]);
+        i += 1;
+    }
+
+    table
+};
+
 #[cfg(test)]
 mod tests {
+    use rand::{RngCore, SeedableRng};
+
     use super::*;
+    use crate::rng::MT;
+
+    const BACKENDS: [AesBackend; 2] = [AesBackend::Reference, AesBackend::TTable];
+
+    // assert_all_backends runs a known-answer test against every AES
+    // implementation, so the table-driven and bitsliced ciphers are held to the
+    // same FIPS-197 and AESAVS vectors.
+    fn assert_all_backends(key: &[u8], plaintext: &[u8], ciphertext: &[u8]) {
+        for backend in BACKENDS {
+            let aes = Aes::with_backend(key.to_vec(), backend).unwrap();
+            assert_known_answer(aes, plaintext, ciphertext);
+        }
+
+        assert_known_answer(
+            BitslicedAes::new(key.to_vec()).unwrap(),
+            plaintext,
+            ciphertext,
+        );
+    }
+
+    fn assert_known_answer<C: BlockCipher<U16>>(cipher: C, plaintext: &[u8], ciphertext: &[u8]) {
+        let encrypted_block = cipher.encrypt_block(GenericArray::clone_from_slice(plaintext));
+        assert_eq!(encrypted_block.as_slice(), ciphertext);
+
+        let decrypted_block = cipher.decrypt_block(GenericArray::clone_from_slice(ciphertext));
+        assert_eq!(decrypted_block.as_slice(), plaintext);
+    }
 
     #[test]
     fn test_add_round_key() {
@@ -531,10 +743,7 @@ mod tests {
         let key = *b"abcdefghijklmnop";
         let key_input = GenericArray::clone_from_slice(&key);
 
-        let aes = Aes {
-            key: Vec::new(),
-            key_length: KeyLength::Length128,
-        };
+        let aes = Aes::new(vec![0u8; 16]).unwrap();
 
         let actual_output = aes.add_round_key(state_input, key_input);
         #[rustfmt::skip]
@@ -600,17 +809,8 @@ mod tests {
             [0xf4, 0xa7, 0x0d, 0x8a, 0xf8, 0x77, 0xf9, 0xb0, 0x2b, 0x4c, 0x40, 0xdf, 0x57, 0xd4, 0x5b, 0x17]
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext)
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
         }
     }
 
@@ -672,17 +872,8 @@ mod tests {
             [0xa6, 0x7c, 0xf3, 0x33, 0xb3, 0x14, 0xd4, 0x11, 0xd3, 0xc0, 0xae, 0x6e, 0x1c, 0xfc, 0xd8, 0xf5]
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext)
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
         }
     }
 
@@ -728,26 +919,14 @@ mod tests {
             [0x17, 0x9a, 0x49, 0xc7, 0x12, 0x15, 0x4b, 0xbf, 0xfb, 0xe6, 0xe7, 0xa8, 0x4a, 0x18, 0xe2, 0x20],
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext)
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
         }
     }
 
     #[test]
     fn test_sub_bytes() {
-        let aes = Aes {
-            key: Vec::new(),
-            key_length: KeyLength::Length128,
-        };
+        let aes = Aes::new(vec![0u8; 16]).unwrap();
 
         let plaintext = *b"The quick brown ";
         let plaintext_block = GenericArray::clone_from_slice(&plaintext);
@@ -859,17 +1038,9 @@ mod tests {
             ]
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 16]; // aes-128
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -955,17 +1126,9 @@ mod tests {
             ],
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 24]; // aes-192
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -1039,17 +1202,9 @@ mod tests {
             ],
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 32]; // aes-256
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -1064,10 +1219,7 @@ mod tests {
         ];
         let input_state = GenericArray::clone_from_slice(&input);
 
-        let aes = Aes {
-            key: Vec::new(),
-            key_length: KeyLength::Length128,
-        };
+        let aes = Aes::new(vec![0u8; 16]).unwrap();
 
         let actual_output = aes.shift_rows(input_state);
         #[rustfmt::skip]
@@ -1091,10 +1243,7 @@ mod tests {
             [0xd4, 0xd4, 0xd4, 0xd5],
             [0x2d, 0x26, 0x31, 0x4c],
         ];
-        let aes = Aes {
-            key: Vec::new(),
-            key_length: KeyLength::Length128,
-        };
+        let aes = Aes::new(vec![0u8; 16]).unwrap();
 
         let expected_outputs = [
             [0x8e, 0x4d, 0xa1, 0xbc],
@@ -1119,10 +1268,7 @@ mod tests {
         ];
         let input_state = GenericArray::clone_from_slice(&input);
 
-        let aes = Aes {
-            key: Vec::new(),
-            key_length: KeyLength::Length128,
-        };
+        let aes = Aes::new(vec![0u8; 16]).unwrap();
 
         let actual_output = aes.mix_cols(input_state);
         #[rustfmt::skip]
@@ -1139,7 +1285,7 @@ mod tests {
     #[test]
     fn encrypt_block() {
         let key = *b"YELLOW SUBMARINE";
-        let aes = Aes::new(key.to_vec());
+        let aes = Aes::new(key.to_vec()).unwrap();
 
         let plaintext = b"ABCDEFGHIJKLMNOP";
         let plaintext_block = GenericArray::clone_from_slice(plaintext);
@@ -1159,7 +1305,7 @@ mod tests {
     #[test]
     fn decrypt_block() {
         let key = *b"YELLOW SUBMARINE";
-        let aes = Aes::new(key.to_vec());
+        let aes = Aes::new(key.to_vec()).unwrap();
 
         #[rustfmt::skip]
         let ciphertext = [
@@ -1443,17 +1589,9 @@ mod tests {
             [0x3f, 0x5b, 0x8c, 0xc9, 0xea, 0x85, 0x5a, 0x0a, 0xfa, 0x73, 0x47, 0xd2, 0x3e, 0x8d, 0x66, 0x4e],
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 16]; // aes-128
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -1723,17 +1861,9 @@ mod tests {
             [0xb1, 0x3d, 0xb4, 0xda, 0x1f, 0x71, 0x8b, 0xc6, 0x90, 0x47, 0x97, 0xc8, 0x2b, 0xcf, 0x2d, 0x32],
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 24]; // aes-192
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -2003,17 +2133,9 @@ mod tests {
             [0xac, 0xda, 0xce, 0x80, 0x78, 0xa3, 0x2b, 0x1a, 0x18, 0x2b, 0xfa, 0x49, 0x87, 0xca, 0x13, 0x47],
         ];
 
-        for i in 0..plaintexts.len() {
+        for (plaintext, ciphertext) in plaintexts.iter().zip(ciphertexts) {
             let key = [0u8; 32]; // aes-256
-            let aes = Aes::new(key.to_vec());
-
-            let plaintext_block = GenericArray::clone_from_slice(&plaintexts[i]);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(key.to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), &plaintexts[i])
+            assert_all_backends(&key, plaintext, &ciphertext);
         }
     }
 
@@ -2284,16 +2406,8 @@ mod tests {
 
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext);
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
         }
     }
 
@@ -2691,16 +2805,8 @@ mod tests {
             [0xdd, 0x8a, 0x49, 0x35, 0x14, 0x23, 0x1c, 0xbf, 0x56, 0xec, 0xce, 0xe4, 0xc4, 0x08, 0x89, 0xfb],
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
-
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext);
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
         }
     }
 
@@ -3227,16 +3333,90 @@ mod tests {
             [0x4b, 0xf8, 0x5f, 0x1b, 0x5d, 0x54, 0xad, 0xbc, 0x30, 0x7b, 0x0a, 0x04, 0x83, 0x89, 0xad, 0xcb]
         ];
 
-        for i in 0..keys.len() {
-            let aes = Aes::new(keys[i].to_vec());
-            let plaintext = [0u8; 16];
-            let plaintext_block = GenericArray::clone_from_slice(&plaintext);
-            let encrypted_block = aes.encrypt_block(plaintext_block);
-            assert_eq!(encrypted_block.as_slice(), ciphertexts[i]);
+        for (key, ciphertext) in keys.iter().zip(ciphertexts) {
+            assert_all_backends(key, &[0u8; 16], &ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_key_expansion_appendix_a_one() {
+        let key = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let aes = Aes::new(key.to_vec()).unwrap();
 
-            let aes_decrypt = Aes::new(keys[i].to_vec());
-            let decrypted_block = aes_decrypt.decrypt_block(ciphertexts[i].into());
-            assert_eq!(decrypted_block.as_slice(), plaintext);
+        let expected_last_round_key = [
+            0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
+            0x0c, 0xa6,
+        ];
+
+        assert_eq!(aes.round_keys.len(), 11);
+        assert_eq!(aes.round_keys[0].as_slice(), key);
+        assert_eq!(aes.round_keys[10].as_slice(), expected_last_round_key);
+
+        // the equivalent inverse cipher starts and ends with the unmixed round keys
+        assert_eq!(aes.inv_round_keys[0], aes.round_keys[10]);
+        assert_eq!(aes.inv_round_keys[10], aes.round_keys[0]);
+        assert_eq!(aes.inv_round_keys[1], aes.inv_mix_cols(aes.round_keys[9]));
+    }
+
+    // FIPS-197 appendix C example vectors
+    #[test]
+    fn test_example_vectors_appendix_c() {
+        let plaintext = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let key = (0..32).collect::<Vec<u8>>();
+
+        #[rustfmt::skip]
+        let ciphertexts: [(usize, [u8; 16]); 3] = [
+            (16, [0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a]),
+            (24, [0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d, 0x71, 0x91]),
+            (32, [0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49, 0x60, 0x89]),
+        ];
+
+        for (key_len, ciphertext) in ciphertexts {
+            assert_all_backends(&key[..key_len], &plaintext, &ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let seed: u32 = 42;
+        let mut rng = MT::from_seed(seed.to_be_bytes());
+
+        for key_len in [16, 24, 32] {
+            let mut key = vec![0u8; key_len];
+            rng.fill_bytes(&mut key);
+            let reference = Aes::with_backend(key.clone(), AesBackend::Reference).unwrap();
+            let ttable = Aes::with_backend(key.clone(), AesBackend::TTable).unwrap();
+            let bitsliced = BitslicedAes::new(key).unwrap();
+
+            for _ in 0..64 {
+                let mut block = GenericArray::default();
+                rng.fill_bytes(&mut block);
+
+                let encrypted_block = reference.encrypt_block(block);
+                assert_eq!(ttable.encrypt_block(block), encrypted_block);
+                assert_eq!(ttable.decrypt_block(encrypted_block), block);
+                assert_eq!(bitsliced.encrypt_block(block), encrypted_block);
+                assert_eq!(bitsliced.decrypt_block(encrypted_block), block);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_key_length() {
+        for key_len in [0, 15, 17, 33] {
+            let expected_error = KeyError::InvalidKeyLength {
+                expected: &[16, 24, 32],
+                actual: key_len,
+            };
+
+            assert!(matches!(Aes::new(vec![0u8; key_len]), Err(e) if e == expected_error));
+            assert!(matches!(BitslicedAes::new(vec![0u8; key_len]), Err(e) if e == expected_error));
         }
     }
 }
@@ -1,144 +1,214 @@
-use generic_array::{
-    typenum::{U4, U6, U8},
-    GenericArray,
-};
+use generic_array::{typenum::U8, GenericArray};
 
-use crate::cipher::{
-    block::Block,
-    stream::{VernamCipher, VernamCipherError},
-};
+use crate::cipher::{block::Block, KeyError};
 
 use super::BlockCipher;
 
 // Des implements BlockCipher for the Data Encryption Standard (DES) as defined
-// in the U.S. Federal Information Processing Standards Publication 46-3
+// in the U.S. Federal Information Processing Standards Publication 46-3.
+// The implementation's conformance is verified from NIST SP 800-20's known
+// answer tests.
+
+// DES is a Feistel network: each round only transforms the right half of the
+// block with the round function f, and XORs the output into the left half.
+// Decryption is therefore the same routine with the round keys reversed, and
+// f itself need not be invertible.
+
+// DES keys are 64 bits, of which only 56 are used: the least significant bit
+// of each byte is an odd parity bit. The 56 bit keyspace is small enough to be
+// searched exhaustively (the EFF's Deep Crack did so in 1998 in 56 hours).
+
+// new rejects keys with incorrect parity, and the weak and semi-weak keys
+// listed in NIST SP 800-67 section 3.3.2, whose key schedules produce only one
+// or two distinct round keys.
 pub struct Des {
-    key: [u8; 64],
+    key: [u8; 8],
+    round_keys: [u64; 16], // sixteen 48 bit keys packed into u64s
 }
 
 impl BlockCipher<U8> for Des {
-    fn new(key: Vec<u8>) -> Self {
-        if key.len() != 56 {
-            todo!()
-        }
+    fn new(key: Vec<u8>) -> Result<Self, KeyError> {
+        let des = Des::new_unchecked(key)?;
 
-        Des {
-            key: key.try_into().unwrap(),
+        if let Some(i) = des.key.iter().position(|byte| byte.count_ones() % 2 == 0) {
+            return Err(KeyError::InvalidParity(i));
+        }
+        if WEAK_KEYS.contains(&des.key) {
+            return Err(KeyError::WeakKey);
+        }
+        if SEMI_WEAK_KEYS.contains(&des.key) {
+            return Err(KeyError::SemiWeakKey);
         }
+
+        Ok(des)
     }
 
     fn encrypt_block(&self, block: Block<U8>) -> Block<U8> {
-        // 0. generate round keys
-        let round_keys = [0u64; 16]; // sixteen 48 bit keys packed into u64s
-        let round_keys = self.key_expansion();
+        self.feistel(block, self.round_keys.iter())
+    }
+
+    fn decrypt_block(&self, block: Block<U8>) -> Block<U8> {
+        self.feistel(block, self.round_keys.iter().rev())
+    }
+}
+
+impl Des {
+    // new_unchecked only checks the key length, accepting keys with incorrect
+    // parity as well as weak and semi-weak keys. NIST SP 800-20's known answer
+    // tests use the weak key 0101010101010101, and the weak keys themselves are
+    // worth experimenting with, e.g. encrypting twice under a weak key returns
+    // the plaintext.
+    pub fn new_unchecked(key: Vec<u8>) -> Result<Self, KeyError> {
+        let key: [u8; 8] = key
+            .try_into()
+            .map_err(|key: Vec<u8>| KeyError::InvalidKeyLength {
+                expected: &[8],
+                actual: key.len(),
+            })?;
+
+        Ok(Des {
+            key,
+            round_keys: Des::key_expansion(u64::from_be_bytes(key)),
+        })
+    }
 
+    fn feistel<'a>(
+        &self,
+        block: Block<U8>,
+        round_keys: impl Iterator<Item = &'a u64>,
+    ) -> Block<U8> {
         // 1. initial permutation, which is undone by final permutation at the end
         // of the routine. These have no cryptography significance, rather,
         // they were included to facilitate loading blocks in and out of
         // mid 1970's 8-bit based hardware.
-        let permuted_block = self.initial_permutation(block);
+        let block = u64::from_be_bytes(block.into());
+        let permuted_block = permute(block, 64, &IP);
 
-        let mut left = permuted_block.into_iter().take(4).collect();
-        let mut right = permuted_block.into_iter().take(4).collect();
+        let mut left = (permuted_block >> 32) as u32;
+        let mut right = permuted_block as u32;
 
         // 2. apply the round function 16 times
-        for r in 0..=16 {
-            // apply f to right with k_i
-            let pseudo_key = self.f(right, round_keys[r]);
-
-            // xor the pseudo key with left
+        // L_i = R_{i-1}
+        // R_i = L_{i-1} XOR f(R_{i-1}, K_i)
+        for &round_key in round_keys {
             let temp = right;
-            let xor_cipher = VernamCipher::new(left.into_iter(), pseudo_key.into_iter());
-            let output = xor_cipher
-                .collect::<Result<Vec<u8>, VernamCipherError>>()
-                .unwrap();
-
-            // swap
-            right = output;
+            right = left ^ f(right, round_key);
             left = temp;
         }
 
-        let final_block = left.append(&mut right);
-        let inverted_permuted_block = self.final_permutation(block);
-
-        todo!()
-    }
-
-    fn decrypt_block(&self, block: Block<U8>) -> crate::cipher::block::Block<U8> {
-        todo!()
-    }
-}
+        // 3. undo the final swap, and apply the final permutation
+        let preoutput = ((right as u64) << 32) | left as u64;
+        let output = permute(preoutput, 64, &FP);
 
-impl Des {
-    fn key_expansion(&self) -> Vec<Block<U6>> {
-        todo!()
+        GenericArray::clone_from_slice(&output.to_be_bytes())
     }
 
-    fn permuted_choice_one(&self, key: [u8; 64]) -> [u8; 56] {
-        let mut output = [0u8; 56];
-
-        for i in 0..=56 {
-            output[i] = key[PC1[i] as usize];
+    // The key schedule selects 56 of the key's 64 bits with permuted choice 1,
+    // and splits them into two 28 bit halves C and D. Each round rotates both
+    // halves left by one or two bits, and permuted choice 2 selects 48 of the
+    // resulting 56 bits as the round key.
+    fn key_expansion(key: u64) -> [u64; 16] {
+        let permuted_key = permute(key, 64, &PC1);
+        let mut c = (permuted_key >> 28) as u32;
+        let mut d = (permuted_key & 0x0fff_ffff) as u32;
+
+        let mut round_keys = [0u64; 16];
+        for (round_key, &shift) in round_keys.iter_mut().zip(SHIFTS.iter()) {
+            c = rotate_left_28(c, shift as u32);
+            d = rotate_left_28(d, shift as u32);
+
+            let cd = ((c as u64) << 28) | d as u64;
+            *round_key = permute(cd, 56, &PC2);
         }
 
-        output
+        round_keys
     }
+}
 
-    fn permuted_choice_two(&self, key_left: u32, key_right: u32) -> [u8; 48] {
-        let mut output = [0u8; 48];
+// TripleDes implements BlockCipher for the Triple Data Encryption Algorithm
+// (TDEA) as defined in NIST SP 800-67, in encrypt-decrypt-encrypt order.
+// C = encrypt_block(decrypt_block(encrypt_block(P, K_1), K_2), K_3)
 
-        // 24 vs 28?
+// 24 byte keys select keying option 1 (EDE3), with three independent keys.
+// 16 byte keys select keying option 2 (EDE2), where K_3 = K_1. Setting all
+// three keys equal degenerates to single DES, which is why EDE was chosen.
 
-        for i in 0..=48 {
-            // output[i] = PC2[i] as usize;
-        }
+// Meet-in-the-middle attacks reduce EDE3's effective strength to 112 bits,
+// and EDE2's to roughly 80 bits given enough known plaintexts.
+pub struct TripleDes {
+    k1: Des,
+    k2: Des,
+    k3: Des,
+}
 
-        output
+impl BlockCipher<U8> for TripleDes {
+    fn new(key: Vec<u8>) -> Result<Self, KeyError> {
+        let (k1, k2, k3) = match key.len() {
+            16 => (&key[0..8], &key[8..16], &key[0..8]),
+            24 => (&key[0..8], &key[8..16], &key[16..24]),
+            actual => {
+                return Err(KeyError::InvalidKeyLength {
+                    expected: &[16, 24],
+                    actual,
+                })
+            }
+        };
+
+        Ok(TripleDes {
+            k1: Des::new(k1.to_vec())?,
+            k2: Des::new(k2.to_vec())?,
+            k3: Des::new(k3.to_vec())?,
+        })
     }
 
-    fn initial_permutation(&self, block: Block<U8>) -> Block<U8> {
-        let mut output = [0u8; 64];
-
-        for (i, _) in block.into_iter().enumerate() {
-            output[i] = block[IP[i] as usize];
-        }
-
-        GenericArray::clone_from_slice(&output)
+    fn encrypt_block(&self, block: Block<U8>) -> Block<U8> {
+        let block = self.k1.encrypt_block(block);
+        let block = self.k2.decrypt_block(block);
+        self.k3.encrypt_block(block)
     }
 
-    fn final_permutation(&self, block: Block<U8>) -> Block<U8> {
-        let mut output = [0u8; 64];
-
-        for i in 0..block.len() {
-            output[i] = block[FP[i] as usize];
-        }
-
-        GenericArray::clone_from_slice(&output)
+    fn decrypt_block(&self, block: Block<U8>) -> Block<U8> {
+        let block = self.k3.decrypt_block(block);
+        let block = self.k2.encrypt_block(block);
+        self.k1.decrypt_block(block)
     }
+}
 
-    fn f(&self, right: Block<U4>, round_key: Block<U6>) -> Block<U4> {
-        // 1. expansion (32->48)
-        let mut expanded_right = [0u8; 48];
-        for i in 0..right.len() {
-            expanded_right[i] = E[right[i] as usize];
-        }
-
-        // 2. add round key
-        let xor_cipher = VernamCipher::new(expanded_right.into_iter(), round_key.into_iter());
-        let result = xor_cipher
-            .collect::<Result<Vec<u8>, VernamCipherError>>()
-            .unwrap(); // TODO: safety, or encode lengths to avoid returning Result
+// f is the DES round function, which mixes the right half of the block
+// with a 48 bit round key.
+fn f(right: u32, round_key: u64) -> u32 {
+    // 1. expansion (32->48): duplicates the edge bits of each 4 bit group
+    let expanded_right = permute(right as u64, 32, &E);
+
+    // 2. add round key
+    let keyed = expanded_right ^ round_key;
+
+    // 3. substitution (48->32): each 6 bit group indexes an S-box, where the
+    // outer two bits select the row, and the inner four bits select the column.
+    // ***This operation provides the non-linearity in the cipher.***
+    let mut substituted = 0u32;
+    for (i, sbox) in SBOXES.iter().enumerate() {
+        let group = ((keyed >> (42 - 6 * i)) & 0x3f) as usize;
+        let row = ((group & 0x20) >> 4) | (group & 0x01);
+        let col = (group >> 1) & 0x0f;
+
+        substituted = (substituted << 4) | sbox[row * 16 + col] as u32;
+    }
 
-        // 3. substitution
+    // 4. permutation: spreads each S-box's output across four S-boxes of the next round
+    permute(substituted as u64, 32, &P) as u32
+}
 
-        // 4. permutation
-        let mut permuted_block = [0u8; 32];
-        for i in 0..48 {
-            permuted_block[i] = P[substituded_block[i] as usize]
-        }
+// permute selects bits of an input_bits wide input according to table.
+// Tables are 1-indexed from the most significant bit, as in FIPS 46-3.
+fn permute(input: u64, input_bits: u32, table: &[u8]) -> u64 {
+    table.iter().fold(0, |output, &position| {
+        (output << 1) | ((input >> (input_bits - position as u32)) & 1)
+    })
+}
 
-        GenericArray::clone_from_slice(&permuted_block)
-    }
+fn rotate_left_28(half: u32, shift: u32) -> u32 {
+    ((half << shift) | (half >> (28 - shift))) & 0x0fff_ffff
 }
 
 const SHIFTS: [u8; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
@@ -181,7 +251,6 @@ const IP: [u8; 64] = [
 ];
 
 #[rustfmt::skip]
-
 const FP: [u8; 64] = [
     40,	8,	48,	16,	56,	24,	64,	32,
     39,	7,	47,	15,	55,	23,	63,	31,
@@ -212,3 +281,377 @@ const P: [u8; 32] = [
     2,	8,	24,	14,	32,	27,	3,	9,
     19,	13,	30,	6,	22,	11,	4,	25,
 ];
+
+#[rustfmt::skip]
+const SBOXES: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7,
+        0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8,
+        4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0,
+        15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10,
+        3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5,
+        0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15,
+        13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8,
+        13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1,
+        13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7,
+        1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15,
+        13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9,
+        10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4,
+        3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9,
+        14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6,
+        4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14,
+        11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11,
+        10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8,
+        9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6,
+        4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1,
+        13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6,
+        1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2,
+        6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7,
+        1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2,
+        7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8,
+        2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+#[rustfmt::skip]
+const WEAK_KEYS: [[u8; 8]; 4] = [
+    [0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01],
+    [0xfe, 0xfe, 0xfe, 0xfe, 0xfe, 0xfe, 0xfe, 0xfe],
+    [0xe0, 0xe0, 0xe0, 0xe0, 0xf1, 0xf1, 0xf1, 0xf1],
+    [0x1f, 0x1f, 0x1f, 0x1f, 0x0e, 0x0e, 0x0e, 0x0e],
+];
+
+#[rustfmt::skip]
+const SEMI_WEAK_KEYS: [[u8; 8]; 12] = [
+    [0x01, 0xfe, 0x01, 0xfe, 0x01, 0xfe, 0x01, 0xfe],
+    [0xfe, 0x01, 0xfe, 0x01, 0xfe, 0x01, 0xfe, 0x01],
+    [0x1f, 0xe0, 0x1f, 0xe0, 0x0e, 0xf1, 0x0e, 0xf1],
+    [0xe0, 0x1f, 0xe0, 0x1f, 0xf1, 0x0e, 0xf1, 0x0e],
+    [0x01, 0xe0, 0x01, 0xe0, 0x01, 0xf1, 0x01, 0xf1],
+    [0xe0, 0x01, 0xe0, 0x01, 0xf1, 0x01, 0xf1, 0x01],
+    [0x1f, 0xfe, 0x1f, 0xfe, 0x0e, 0xfe, 0x0e, 0xfe],
+    [0xfe, 0x1f, 0xfe, 0x1f, 0xfe, 0x0e, 0xfe, 0x0e],
+    [0x01, 0x1f, 0x01, 0x1f, 0x01, 0x0e, 0x01, 0x0e],
+    [0x1f, 0x01, 0x1f, 0x01, 0x0e, 0x01, 0x0e, 0x01],
+    [0xe0, 0xfe, 0xe0, 0xfe, 0xf1, 0xfe, 0xf1, 0xfe],
+    [0xfe, 0xe0, 0xfe, 0xe0, 0xfe, 0xf1, 0xfe, 0xf1],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permute() {
+        // the final permutation undoes the initial permutation
+        let block = 0x0123456789abcdef;
+        assert_eq!(permute(permute(block, 64, &IP), 64, &FP), block);
+    }
+
+    #[test]
+    fn test_key_expansion() {
+        // worked example from FIPS 46-3's companion literature (J. Orlin Grabbe)
+        let round_keys = Des::key_expansion(0x133457799bbcdff1);
+
+        assert_eq!(
+            round_keys[0],
+            0b000110_110000_001011_101111_111111_000111_000001_110010
+        );
+        assert_eq!(
+            round_keys[15],
+            0b110010_110011_110110_001011_000011_100001_011111_110101
+        );
+    }
+
+    #[test]
+    fn test_encrypt_block() {
+        let des = Des::new(vec![0x13, 0x34, 0x57, 0x79, 0x9b, 0xbc, 0xdf, 0xf1]).unwrap();
+
+        let plaintext = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+        let encrypted_block = des.encrypt_block(GenericArray::clone_from_slice(&plaintext));
+        let expected_output = [0x85, 0xe8, 0x13, 0x54, 0x0f, 0x0a, 0xb4, 0x05];
+        assert_eq!(encrypted_block.as_slice(), expected_output);
+
+        let decrypted_block = des.decrypt_block(encrypted_block);
+        assert_eq!(decrypted_block.as_slice(), plaintext);
+    }
+
+    // NIST SP 800-20 table 1: variable plaintext known answer test
+    #[test]
+    fn test_variable_plaintext_kat() {
+        #[rustfmt::skip]
+        let plaintexts_and_ciphertexts = [
+            ([0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x95, 0xf8, 0xa5, 0xe5, 0xdd, 0x31, 0xd9, 0x00]),
+            ([0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xdd, 0x7f, 0x12, 0x1c, 0xa5, 0x01, 0x56, 0x19]),
+            ([0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x2e, 0x86, 0x53, 0x10, 0x4f, 0x38, 0x34, 0xea]),
+            ([0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x4b, 0xd3, 0x88, 0xff, 0x6c, 0xd8, 0x1d, 0x4f]),
+            ([0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x20, 0xb9, 0xe7, 0x67, 0xb2, 0xfb, 0x14, 0x56]),
+            ([0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x55, 0x57, 0x93, 0x80, 0xd7, 0x71, 0x38, 0xef]),
+            ([0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x6c, 0xc5, 0xde, 0xfa, 0xaf, 0x04, 0x51, 0x2f]),
+            ([0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x0d, 0x9f, 0x27, 0x9b, 0xa5, 0xd8, 0x72, 0x60]),
+            ([0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xd9, 0x03, 0x1b, 0x02, 0x71, 0xbd, 0x5a, 0x0a]),
+            ([0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0x42, 0x42, 0x50, 0xb3, 0x7c, 0x3d, 0xd9, 0x51]),
+            ([0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xb8, 0x06, 0x1b, 0x7e, 0xcd, 0x9a, 0x21, 0xe5]),
+            ([0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xf1, 0x5d, 0x0f, 0x28, 0x6b, 0x65, 0xbd, 0x28]),
+            ([0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xad, 0xd0, 0xcc, 0x8d, 0x6e, 0x5d, 0xeb, 0xa1]),
+            ([0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xe6, 0xd5, 0xf8, 0x27, 0x52, 0xad, 0x63, 0xd1]),
+            ([0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xec, 0xbf, 0xe3, 0xbd, 0x3f, 0x59, 0x1a, 0x5e]),
+            ([0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], [0xf3, 0x56, 0x83, 0x43, 0x79, 0xd1, 0x65, 0xcd]),
+            ([0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00], [0x2b, 0x9f, 0x98, 0x2f, 0x20, 0x03, 0x7f, 0xa9]),
+            ([0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00], [0x88, 0x9d, 0xe0, 0x68, 0xa1, 0x6f, 0x0b, 0xe6]),
+            ([0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00], [0xe1, 0x9e, 0x27, 0x5d, 0x84, 0x6a, 0x12, 0x98]),
+            ([0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00], [0x32, 0x9a, 0x8e, 0xd5, 0x23, 0xd7, 0x1a, 0xec]),
+            ([0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00], [0xe7, 0xfc, 0xe2, 0x25, 0x57, 0xd2, 0x3c, 0x97]),
+            ([0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00], [0x12, 0xa9, 0xf5, 0x81, 0x7f, 0xf2, 0xd6, 0x5d]),
+            ([0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00], [0xa4, 0x84, 0xc3, 0xad, 0x38, 0xdc, 0x9c, 0x19]),
+            ([0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00], [0xfb, 0xe0, 0x0a, 0x8a, 0x1e, 0xf8, 0xad, 0x72]),
+            ([0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00], [0x75, 0x0d, 0x07, 0x94, 0x07, 0x52, 0x13, 0x63]),
+            ([0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00], [0x64, 0xfe, 0xed, 0x9c, 0x72, 0x4c, 0x2f, 0xaf]),
+            ([0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00], [0xf0, 0x2b, 0x26, 0x3b, 0x32, 0x8e, 0x2b, 0x60]),
+            ([0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00], [0x9d, 0x64, 0x55, 0x5a, 0x9a, 0x10, 0xb8, 0x52]),
+            ([0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00], [0xd1, 0x06, 0xff, 0x0b, 0xed, 0x52, 0x55, 0xd7]),
+            ([0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00], [0xe1, 0x65, 0x2c, 0x6b, 0x13, 0x8c, 0x64, 0xa5]),
+            ([0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00], [0xe4, 0x28, 0x58, 0x11, 0x86, 0xec, 0x8f, 0x46]),
+            ([0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00], [0xae, 0xb5, 0xf5, 0xed, 0xe2, 0x2d, 0x1a, 0x36]),
+            ([0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00], [0xe9, 0x43, 0xd7, 0x56, 0x8a, 0xec, 0x0c, 0x5c]),
+            ([0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00], [0xdf, 0x98, 0xc8, 0x27, 0x6f, 0x54, 0xb0, 0x4b]),
+            ([0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00], [0xb1, 0x60, 0xe4, 0x68, 0x0f, 0x6c, 0x69, 0x6f]),
+            ([0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00], [0xfa, 0x07, 0x52, 0xb0, 0x7d, 0x9c, 0x4a, 0xb8]),
+            ([0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00], [0xca, 0x3a, 0x2b, 0x03, 0x6d, 0xbc, 0x85, 0x02]),
+            ([0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00], [0x5e, 0x09, 0x05, 0x51, 0x7b, 0xb5, 0x9b, 0xcf]),
+            ([0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00], [0x81, 0x4e, 0xeb, 0x3b, 0x91, 0xd9, 0x07, 0x26]),
+            ([0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00], [0x4d, 0x49, 0xdb, 0x15, 0x32, 0x91, 0x9c, 0x9f]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00], [0x25, 0xeb, 0x5f, 0xc3, 0xf8, 0xcf, 0x06, 0x21]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00], [0xab, 0x6a, 0x20, 0xc0, 0x62, 0x0d, 0x1c, 0x6f]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00], [0x79, 0xe9, 0x0d, 0xbc, 0x98, 0xf9, 0x2c, 0xca]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00], [0x86, 0x6e, 0xce, 0xdd, 0x80, 0x72, 0xbb, 0x0e]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00], [0x8b, 0x54, 0x53, 0x6f, 0x2f, 0x3e, 0x64, 0xa8]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00], [0xea, 0x51, 0xd3, 0x97, 0x55, 0x95, 0xb8, 0x6b]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00], [0xca, 0xff, 0xc6, 0xac, 0x45, 0x42, 0xde, 0x31]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00], [0x8d, 0xd4, 0x5a, 0x2d, 0xdf, 0x90, 0x79, 0x6c]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00], [0x10, 0x29, 0xd5, 0x5e, 0x88, 0x0e, 0xc2, 0xd0]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00], [0x5d, 0x86, 0xcb, 0x23, 0x63, 0x9d, 0xbe, 0xa9]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00], [0x1d, 0x1c, 0xa8, 0x53, 0xae, 0x7c, 0x0c, 0x5f]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00], [0xce, 0x33, 0x23, 0x29, 0x24, 0x8f, 0x32, 0x28]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00], [0x84, 0x05, 0xd1, 0xab, 0xe2, 0x4f, 0xb9, 0x42]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00], [0xe6, 0x43, 0xd7, 0x80, 0x90, 0xca, 0x42, 0x07]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00], [0x48, 0x22, 0x1b, 0x99, 0x37, 0x74, 0x8a, 0x23]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00], [0xdd, 0x7c, 0x0b, 0xbd, 0x61, 0xfa, 0xfd, 0x54]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80], [0x2f, 0xbc, 0x29, 0x1a, 0x57, 0x0d, 0xb5, 0xc4]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40], [0xe0, 0x7c, 0x30, 0xd7, 0xe4, 0xe2, 0x6e, 0x12]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20], [0x09, 0x53, 0xe2, 0x25, 0x8e, 0x8e, 0x90, 0xa1]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10], [0x5b, 0x71, 0x1b, 0xc4, 0xce, 0xeb, 0xf2, 0xee]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08], [0xcc, 0x08, 0x3f, 0x1e, 0x6d, 0x9e, 0x85, 0xf6]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04], [0xd2, 0xfd, 0x88, 0x67, 0xd5, 0x0d, 0x2d, 0xfe]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02], [0x06, 0xe7, 0xea, 0x22, 0xce, 0x92, 0x70, 0x8f]),
+            ([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01], [0x16, 0x6b, 0x40, 0xb4, 0x4a, 0xba, 0x4b, 0xd6]),
+        ];
+
+        let des = Des::new_unchecked(vec![0x01; 8]).unwrap(); // a weak key
+        for (plaintext, ciphertext) in plaintexts_and_ciphertexts {
+            let encrypted_block = des.encrypt_block(GenericArray::clone_from_slice(&plaintext));
+            assert_eq!(encrypted_block.as_slice(), ciphertext);
+
+            let decrypted_block = des.decrypt_block(GenericArray::clone_from_slice(&ciphertext));
+            assert_eq!(decrypted_block.as_slice(), plaintext);
+        }
+    }
+
+    // NIST SP 800-20 table 2: variable key known answer test
+    #[test]
+    fn test_variable_key_kat() {
+        #[rustfmt::skip]
+        let keys_and_ciphertexts = [
+            ([0x80, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x95, 0xa8, 0xd7, 0x28, 0x13, 0xda, 0xa9, 0x4d]),
+            ([0x40, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x0e, 0xec, 0x14, 0x87, 0xdd, 0x8c, 0x26, 0xd5]),
+            ([0x20, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x7a, 0xd1, 0x6f, 0xfb, 0x79, 0xc4, 0x59, 0x26]),
+            ([0x10, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0xd3, 0x74, 0x62, 0x94, 0xca, 0x6a, 0x6c, 0xf3]),
+            ([0x08, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x80, 0x9f, 0x5f, 0x87, 0x3c, 0x1f, 0xd7, 0x61]),
+            ([0x04, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0xc0, 0x2f, 0xaf, 0xfe, 0xc9, 0x89, 0xd1, 0xfc]),
+            ([0x02, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x46, 0x15, 0xaa, 0x1d, 0x33, 0xe7, 0x2f, 0x10]),
+            ([0x01, 0x80, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x20, 0x55, 0x12, 0x33, 0x50, 0xc0, 0x08, 0x58]),
+            ([0x01, 0x40, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0xdf, 0x3b, 0x99, 0xd6, 0x57, 0x73, 0x97, 0xc8]),
+            ([0x01, 0x20, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x31, 0xfe, 0x17, 0x36, 0x9b, 0x52, 0x88, 0xc9]),
+            ([0x01, 0x10, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0xdf, 0xdd, 0x3c, 0xc6, 0x4d, 0xae, 0x16, 0x42]),
+            ([0x01, 0x08, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x17, 0x8c, 0x83, 0xce, 0x2b, 0x39, 0x9d, 0x94]),
+            ([0x01, 0x04, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0x50, 0xf6, 0x36, 0x32, 0x4a, 0x9b, 0x7f, 0x80]),
+            ([0x01, 0x02, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01], [0xa8, 0x46, 0x8e, 0xe3, 0xbc, 0x18, 0xf0, 0x6d]),
+            ([0x01, 0x01, 0x80, 0x01, 0x01, 0x01, 0x01, 0x01], [0xa2, 0xdc, 0x9e, 0x92, 0xfd, 0x3c, 0xde, 0x92]),
+            ([0x01, 0x01, 0x40, 0x01, 0x01, 0x01, 0x01, 0x01], [0xca, 0xc0, 0x9f, 0x79, 0x7d, 0x03, 0x12, 0x87]),
+            ([0x01, 0x01, 0x20, 0x01, 0x01, 0x01, 0x01, 0x01], [0x90, 0xba, 0x68, 0x0b, 0x22, 0xae, 0xb5, 0x25]),
+            ([0x01, 0x01, 0x10, 0x01, 0x01, 0x01, 0x01, 0x01], [0xce, 0x7a, 0x24, 0xf3, 0x50, 0xe2, 0x80, 0xb6]),
+            ([0x01, 0x01, 0x08, 0x01, 0x01, 0x01, 0x01, 0x01], [0x88, 0x2b, 0xff, 0x0a, 0xa0, 0x1a, 0x0b, 0x87]),
+            ([0x01, 0x01, 0x04, 0x01, 0x01, 0x01, 0x01, 0x01], [0x25, 0x61, 0x02, 0x88, 0x92, 0x45, 0x11, 0xc2]),
+            ([0x01, 0x01, 0x02, 0x01, 0x01, 0x01, 0x01, 0x01], [0xc7, 0x15, 0x16, 0xc2, 0x9c, 0x75, 0xd1, 0x70]),
+            ([0x01, 0x01, 0x01, 0x80, 0x01, 0x01, 0x01, 0x01], [0x51, 0x99, 0xc2, 0x9a, 0x52, 0xc9, 0xf0, 0x59]),
+            ([0x01, 0x01, 0x01, 0x40, 0x01, 0x01, 0x01, 0x01], [0xc2, 0x2f, 0x0a, 0x29, 0x4a, 0x71, 0xf2, 0x9f]),
+            ([0x01, 0x01, 0x01, 0x20, 0x01, 0x01, 0x01, 0x01], [0xee, 0x37, 0x14, 0x83, 0x71, 0x4c, 0x02, 0xea]),
+            ([0x01, 0x01, 0x01, 0x10, 0x01, 0x01, 0x01, 0x01], [0xa8, 0x1f, 0xbd, 0x44, 0x8f, 0x9e, 0x52, 0x2f]),
+            ([0x01, 0x01, 0x01, 0x08, 0x01, 0x01, 0x01, 0x01], [0x4f, 0x64, 0x4c, 0x92, 0xe1, 0x92, 0xdf, 0xed]),
+            ([0x01, 0x01, 0x01, 0x04, 0x01, 0x01, 0x01, 0x01], [0x1a, 0xfa, 0x9a, 0x66, 0xa6, 0xdf, 0x92, 0xae]),
+            ([0x01, 0x01, 0x01, 0x02, 0x01, 0x01, 0x01, 0x01], [0xb3, 0xc1, 0xcc, 0x71, 0x5c, 0xb8, 0x79, 0xd8]),
+            ([0x01, 0x01, 0x01, 0x01, 0x80, 0x01, 0x01, 0x01], [0x19, 0xd0, 0x32, 0xe6, 0x4a, 0xb0, 0xbd, 0x8b]),
+            ([0x01, 0x01, 0x01, 0x01, 0x40, 0x01, 0x01, 0x01], [0x3c, 0xfa, 0xa7, 0xa7, 0xdc, 0x87, 0x20, 0xdc]),
+            ([0x01, 0x01, 0x01, 0x01, 0x20, 0x01, 0x01, 0x01], [0xb7, 0x26, 0x5f, 0x7f, 0x44, 0x7a, 0xc6, 0xf3]),
+            ([0x01, 0x01, 0x01, 0x01, 0x10, 0x01, 0x01, 0x01], [0x9d, 0xb7, 0x3b, 0x3c, 0x0d, 0x16, 0x3f, 0x54]),
+            ([0x01, 0x01, 0x01, 0x01, 0x08, 0x01, 0x01, 0x01], [0x81, 0x81, 0xb6, 0x5b, 0xab, 0xf4, 0xa9, 0x75]),
+            ([0x01, 0x01, 0x01, 0x01, 0x04, 0x01, 0x01, 0x01], [0x93, 0xc9, 0xb6, 0x40, 0x42, 0xea, 0xa2, 0x40]),
+            ([0x01, 0x01, 0x01, 0x01, 0x02, 0x01, 0x01, 0x01], [0x55, 0x70, 0x53, 0x08, 0x29, 0x70, 0x55, 0x92]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x80, 0x01, 0x01], [0x86, 0x38, 0x80, 0x9e, 0x87, 0x87, 0x87, 0xa0]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x40, 0x01, 0x01], [0x41, 0xb9, 0xa7, 0x9a, 0xf7, 0x9a, 0xc2, 0x08]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x20, 0x01, 0x01], [0x7a, 0x9b, 0xe4, 0x2f, 0x20, 0x09, 0xa8, 0x92]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x10, 0x01, 0x01], [0x29, 0x03, 0x8d, 0x56, 0xba, 0x6d, 0x27, 0x45]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x08, 0x01, 0x01], [0x54, 0x95, 0xc6, 0xab, 0xf1, 0xe5, 0xdf, 0x51]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x04, 0x01, 0x01], [0xae, 0x13, 0xdb, 0xd5, 0x61, 0x48, 0x89, 0x33]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01, 0x01], [0x02, 0x4d, 0x1f, 0xfa, 0x89, 0x04, 0xe3, 0x89]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x80, 0x01], [0xd1, 0x39, 0x97, 0x12, 0xf9, 0x9b, 0xf0, 0x2e]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x40, 0x01], [0x14, 0xc1, 0xd7, 0xc1, 0xcf, 0xfe, 0xc7, 0x9e]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x20, 0x01], [0x1d, 0xe5, 0x27, 0x9d, 0xae, 0x3b, 0xed, 0x6f]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x10, 0x01], [0xe9, 0x41, 0xa3, 0x3f, 0x85, 0x50, 0x13, 0x03]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x08, 0x01], [0xda, 0x99, 0xdb, 0xbc, 0x9a, 0x03, 0xf3, 0x79]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x04, 0x01], [0xb7, 0xfc, 0x92, 0xf9, 0x1d, 0x8e, 0x92, 0xe9]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x02, 0x01], [0xae, 0x8e, 0x5c, 0xaa, 0x3c, 0xa0, 0x4e, 0x85]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x80], [0x9c, 0xc6, 0x2d, 0xf4, 0x3b, 0x6e, 0xed, 0x74]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x40], [0xd8, 0x63, 0xdb, 0xb5, 0xc5, 0x9a, 0x91, 0xa0]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x20], [0xa1, 0xab, 0x21, 0x90, 0x54, 0x5b, 0x91, 0xd7]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x10], [0x08, 0x75, 0x04, 0x1e, 0x64, 0xc5, 0x70, 0xf7]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x08], [0x5a, 0x59, 0x45, 0x28, 0xbe, 0xbe, 0xf1, 0xcc]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x04], [0xfc, 0xdb, 0x32, 0x91, 0xde, 0x21, 0xf0, 0xc0]),
+            ([0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x02], [0x86, 0x9e, 0xfd, 0x7f, 0x9f, 0x26, 0x5a, 0x09]),
+        ];
+
+        for (key, ciphertext) in keys_and_ciphertexts {
+            let des = Des::new(key.to_vec()).unwrap();
+            let encrypted_block = des.encrypt_block(GenericArray::default());
+            assert_eq!(encrypted_block.as_slice(), ciphertext);
+
+            let decrypted_block = des.decrypt_block(GenericArray::clone_from_slice(&ciphertext));
+            assert_eq!(decrypted_block.as_slice(), [0u8; 8]);
+        }
+    }
+
+    // NIST SP 800-67 appendix B: TDEA ECB mode example (keying option 1)
+    #[test]
+    fn test_triple_des_ede3() {
+        #[rustfmt::skip]
+        let key = vec![
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+            0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01,
+            0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23,
+        ];
+        let plaintext = b"The qufck brown fox jump";
+        #[rustfmt::skip]
+        let expected_output = [
+            0xa8, 0x26, 0xfd, 0x8c, 0xe5, 0x3b, 0x85, 0x5f,
+            0xcc, 0xe2, 0x1c, 0x81, 0x12, 0x25, 0x6f, 0xe6,
+            0x68, 0xd5, 0xc0, 0x5d, 0xd9, 0xb6, 0xb9, 0x00,
+        ];
+
+        let tdes = TripleDes::new(key).unwrap();
+        for (plaintext_block, ciphertext_block) in plaintext
+            .chunks_exact(8)
+            .zip(expected_output.chunks_exact(8))
+        {
+            let encrypted_block =
+                tdes.encrypt_block(GenericArray::clone_from_slice(plaintext_block));
+            assert_eq!(encrypted_block.as_slice(), ciphertext_block);
+
+            let decrypted_block = tdes.decrypt_block(encrypted_block);
+            assert_eq!(decrypted_block.as_slice(), plaintext_block);
+        }
+    }
+
+    #[test]
+    fn test_triple_des_ede2() {
+        #[rustfmt::skip]
+        let key = vec![
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+            0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01,
+        ];
+        let plaintext = b"The qufc";
+        let expected_output = [0xc4, 0x48, 0x62, 0xf7, 0x0c, 0xf2, 0xfb, 0xdc];
+
+        let tdes = TripleDes::new(key).unwrap();
+        let encrypted_block = tdes.encrypt_block(GenericArray::clone_from_slice(plaintext));
+        assert_eq!(encrypted_block.as_slice(), expected_output);
+
+        let decrypted_block = tdes.decrypt_block(encrypted_block);
+        assert_eq!(decrypted_block.as_slice(), plaintext);
+    }
+
+    #[test]
+    fn test_triple_des_degenerates_to_des() {
+        let key = vec![0x13, 0x34, 0x57, 0x79, 0x9b, 0xbc, 0xdf, 0xf1];
+        let des = Des::new(key.clone()).unwrap();
+        let tdes = TripleDes::new(key.repeat(3)).unwrap();
+
+        let plaintext =
+            GenericArray::clone_from_slice(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        assert_eq!(des.encrypt_block(plaintext), tdes.encrypt_block(plaintext));
+    }
+
+    #[test]
+    fn test_key_checks() {
+        assert_eq!(
+            Des::new(vec![0x13; 7]).err(),
+            Some(KeyError::InvalidKeyLength {
+                expected: &[8],
+                actual: 7
+            })
+        );
+        assert_eq!(
+            Des::new(vec![0x13, 0x34, 0x57, 0x79, 0x9b, 0xbc, 0xdf, 0xf0]).err(),
+            Some(KeyError::InvalidParity(7))
+        );
+
+        for key in WEAK_KEYS {
+            assert_eq!(Des::new(key.to_vec()).err(), Some(KeyError::WeakKey));
+        }
+        for key in SEMI_WEAK_KEYS {
+            assert_eq!(Des::new(key.to_vec()).err(), Some(KeyError::SemiWeakKey));
+        }
+
+        // a weak key can still be used deliberately, under which encryption is
+        // its own inverse
+        let des = Des::new_unchecked(WEAK_KEYS[2].to_vec()).unwrap();
+        let plaintext = GenericArray::clone_from_slice(b"weak key");
+        assert_eq!(des.encrypt_block(des.encrypt_block(plaintext)), plaintext);
+    }
+
+    #[test]
+    fn test_triple_des_key_checks() {
+        assert_eq!(
+            TripleDes::new(vec![0x13; 8]).err(),
+            Some(KeyError::InvalidKeyLength {
+                expected: &[16, 24],
+                actual: 8
+            })
+        );
+
+        // every component key is checked
+        let mut key = [0x13, 0x34, 0x57, 0x79, 0x9b, 0xbc, 0xdf, 0xf1].repeat(2);
+        key.extend(WEAK_KEYS[1]);
+        assert_eq!(TripleDes::new(key).err(), Some(KeyError::WeakKey));
+    }
+}
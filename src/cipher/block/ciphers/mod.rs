@@ -1,12 +1,16 @@
 use generic_array::ArrayLength;
 
 use super::Block;
+use crate::cipher::KeyError;
 
 pub mod aes;
+pub mod blowfish;
 pub mod des;
 
 pub trait BlockCipher<N: ArrayLength<u8>> {
-    fn new(key: Vec<u8>) -> Self;
+    fn new(key: Vec<u8>) -> Result<Self, KeyError>
+    where
+        Self: Sized;
     fn encrypt_block(&self, block: Block<N>) -> Block<N>;
     fn decrypt_block(&self, block: Block<N>) -> Block<N>;
 }
@@ -3,13 +3,21 @@ use std::marker;
 use generic_array::{ArrayLength, GenericArray};
 use rand::RngCore;
 
-use super::BlockMode;
+use super::{BlockMode, BlockModeError, IncrementalMode};
 use crate::cipher::{
-    block::{ciphers::BlockCipher, pads::Padder},
+    block::{
+        ciphers::BlockCipher,
+        pads::{Padder, PaddingError},
+        Block,
+    },
     stream::{VernamCipher, VernamCipherError},
 };
 
-struct Cbc<N, R, C, P>
+// Cbc implements cipher block chaining as defined in NIST SP 800-38A.
+// Ciphertexts are self-describing: the IV is emitted as the first block of the
+// output, and is parsed back out of the first block on decryption.
+// ... | IV | C_1 | C_2 | ... | C_n |
+pub struct Cbc<N, R, C, P>
 where
     N: ArrayLength<u8>,
     R: RngCore,
@@ -19,6 +27,8 @@ where
     rng: R,
     cipher: C,
     padder: P,
+    iv: Option<Block<N>>, // when set, used in place of a random IV (known-answer tests only)
+    prev: Block<N>,       // the previous ciphertext block, or the IV before the first block
     _marker: marker::PhantomData<N>, // required since N ties C and P together without being used directly
 }
 
@@ -34,6 +44,22 @@ where
             rng,
             cipher,
             padder,
+            iv: None,
+            prev: GenericArray::default(),
+            _marker: marker::PhantomData,
+        }
+    }
+
+    // with_iv fixes the IV used by every encryption instead of drawing one from rng.
+    // Reusing an IV under the same key leaks whether two messages share a prefix,
+    // so this is only meant for known-answer tests such as NIST SP 800-38A's.
+    pub fn with_iv(rng: R, cipher: C, padder: P, iv: Block<N>) -> Self {
+        Cbc {
+            rng,
+            cipher,
+            padder,
+            iv: Some(iv),
+            prev: GenericArray::default(),
             _marker: marker::PhantomData,
         }
     }
@@ -47,60 +73,111 @@ where
     P: Padder<N>,
 {
     fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
-        let mut iv = vec![0u8; N::to_usize()];
-        self.rng.fill_bytes(iv.as_mut_slice());
-        let mut prev_ciphertext = GenericArray::clone_from_slice(iv.as_slice());
-
+        let iv = self.start_encryption();
         let ciphertext_blocks = self
             .padder
             .pad(plaintext)
             .into_iter()
-            .map(|plaintext_block| {
-                let a = prev_ciphertext.clone().into_iter();
-                let b = plaintext_block.into_iter();
-                let xor = VernamCipher::new(b, a)
-                    .collect::<Result<Vec<u8>, VernamCipherError>>()
-                    .unwrap(); // TODO: possibly change error type or provide SAFETY annotation
-
-                let encrypted_block = self
-                    .cipher
-                    .encrypt_block(GenericArray::clone_from_slice(&xor));
-                prev_ciphertext = encrypted_block.clone();
-
-                encrypted_block
-            })
-            .flat_map(|b| b.as_slice().to_vec())
+            .map(|plaintext_block| self.encrypt_next(plaintext_block))
+            .collect::<Vec<Block<N>>>();
+
+        iv.into_iter()
+            .chain(ciphertext_blocks)
+            .flat_map(|b| b.into_iter())
+            .collect()
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Result<Vec<u8>, BlockModeError> {
+        let block_size = N::to_usize();
+        if ciphertext.len() < 2 * block_size {
+            return Err(BlockModeError::TruncatedCiphertext {
+                len: ciphertext.len(),
+                min: 2 * block_size,
+            });
+        }
+        if ciphertext.len() % block_size != 0 {
+            return Err(BlockModeError::UnalignedCiphertext {
+                len: ciphertext.len(),
+                block_size,
+            });
+        }
+
+        let (iv, ciphertext) = ciphertext.split_at(block_size);
+        self.start_decryption(vec![GenericArray::clone_from_slice(iv)]);
+
+        let plaintext_blocks = ciphertext
+            .chunks_exact(block_size)
+            .map(|chunk| self.decrypt_next(GenericArray::clone_from_slice(chunk)))
             .collect();
 
-        ciphertext_blocks
+        Ok(self.padder.unpad(plaintext_blocks)?)
     }
+}
 
-    fn decrypt(&mut self, _ciphertext: Vec<u8>) -> Vec<u8> {
-        unimplemented!()
+impl<N, R, C, P> IncrementalMode<N> for Cbc<N, R, C, P>
+where
+    N: ArrayLength<u8>,
+    R: RngCore,
+    C: BlockCipher<N>,
+    P: Padder<N>,
+{
+    fn header_blocks(&self) -> usize {
+        1
+    }
+
+    fn start_encryption(&mut self) -> Vec<Block<N>> {
+        let iv = match &self.iv {
+            Some(iv) => iv.clone(),
+            None => {
+                let mut iv = GenericArray::default();
+                self.rng.fill_bytes(iv.as_mut_slice());
+                iv
+            }
+        };
+        self.prev = iv.clone();
+
+        vec![iv]
+    }
+
+    fn encrypt_next(&mut self, block: Block<N>) -> Block<N> {
+        let a = self.prev.clone().into_iter();
+        let b = block.into_iter();
+        let xor = VernamCipher::new(b, a)
+            .collect::<Result<Vec<u8>, VernamCipherError>>()
+            .unwrap(); // SAFETY: both inputs are typed with N
+
+        let encrypted_block = self
+            .cipher
+            .encrypt_block(GenericArray::clone_from_slice(&xor));
+        self.prev = encrypted_block.clone();
 
-        // let mut prev_ciphertext = GenericArray::default();
-        // prev_ciphertext.copy_from_slice(&self.iv);
+        encrypted_block
+    }
+
+    fn start_decryption(&mut self, header: Vec<Block<N>>) {
+        self.prev = header[0].clone();
+    }
 
-        // let plaintext_blocks = ciphertext
-        //     .chunks_exact(N::to_usize())
-        //     .map(|chunk| GenericArray::clone_from_slice(chunk))
-        //     .map(|b| {
-        //         let diffused_plaintext_block = self.cipher.decrypt_block(b);
-        //         let undiffused_plaintext_block = VernamCipher::new(
-        //             diffused_plaintext_block.clone().into_iter(),
-        //             prev_ciphertext.clone().into_iter(),
-        //         )
-        //         .collect::<Result<Vec<u8>, VernamCipherError>>()
-        //         .unwrap();
+    fn decrypt_next(&mut self, block: Block<N>) -> Block<N> {
+        let diffused_plaintext_block = self.cipher.decrypt_block(block.clone());
+        let undiffused_plaintext_block = VernamCipher::new(
+            diffused_plaintext_block.into_iter(),
+            self.prev.clone().into_iter(),
+        )
+        .collect::<Result<Vec<u8>, VernamCipherError>>()
+        .unwrap(); // SAFETY: both inputs are typed with N
 
-        //         prev_ciphertext = b;
+        self.prev = block;
 
-        //         undiffused_plaintext_block
-        //     })
-        //     .flat_map(|plaintext_block| plaintext_block)
-        //     .collect();
+        GenericArray::clone_from_slice(&undiffused_plaintext_block)
+    }
+
+    fn pad_final(&self, remainder: Vec<u8>) -> Vec<Block<N>> {
+        self.padder.pad(remainder)
+    }
 
-        // plaintext_blocks
+    fn unpad_final(&self, block: Block<N>) -> Result<Vec<u8>, PaddingError> {
+        self.padder.unpad(vec![block])
     }
 }
 
@@ -110,17 +187,21 @@ mod tests {
 
     use super::*;
     use crate::{
-        cipher::block::{ciphers, pads},
+        cipher::block::{
+            ciphers,
+            modes::tests::{sp800_38a_aes, SP800_38A_IV, SP800_38A_PLAINTEXT},
+            pads,
+        },
         rng::MT,
     };
 
     #[test]
-    fn test_encrypt_one_block() {
+    fn test_encrypt_prefixes_iv() {
         let seed = 1131464071u32;
         let rng = MT::from_seed(seed.to_be_bytes());
 
         let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
         let padder = pads::pkcs7::Pkcs7::new();
 
         let mut cbc = Cbc::new(rng, cipher, padder);
@@ -128,20 +209,125 @@ mod tests {
         let plaintext = b"ABCDEFGHIJKLMNOP";
         let encrypted = cbc.encrypt(plaintext.to_vec());
 
-        println!("{:?}", encrypted);
-        // #[rustfmt::skip]
-        // let expected_output = [
-        //     0xf5, 0x45, 0xc0, 0x06,
-        //     0x06, 0x91, 0x26, 0xd9,
-        //     0xc0, 0xf9, 0x3f, 0xa7,
-        //     0xdd, 0x89, 0xab, 0x98,
-        //     // encrypted padding (entire block)
-        //     0x60, 0xfa, 0x36, 0x70,
-        //     0x7e, 0x45, 0xf4, 0x99,
-        //     0xdb, 0xa0, 0xf2, 0x5b,
-        //     0x92, 0x23, 0x01, 0xa5,
-        // ];
-
-        // assert_eq!(encrypted, expected_output);
+        // iv, one block of plaintext, and one block of padding
+        assert_eq!(encrypted.len(), 48);
+
+        let decrypted = cbc.decrypt(encrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_random_ivs_differ() {
+        let seed = 1131464071u32;
+        let rng = MT::from_seed(seed.to_be_bytes());
+
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+
+        let mut cbc = Cbc::new(rng, cipher, padder);
+
+        let plaintext = b"ABCDEFGHIJKLMNOP";
+        let first = cbc.encrypt(plaintext.to_vec());
+        let second = cbc.encrypt(plaintext.to_vec());
+        assert_ne!(first, second);
+
+        assert_eq!(cbc.decrypt(first).unwrap(), plaintext);
+        assert_eq!(cbc.decrypt(second).unwrap(), plaintext);
+    }
+
+    // NIST SP 800-38A F.2.1 CBC-AES128.Encrypt and F.2.2 CBC-AES128.Decrypt
+    #[test]
+    fn test_sp800_38a_cbc_aes128() {
+        let seed = 1131464071u32;
+        let rng = MT::from_seed(seed.to_be_bytes());
+
+        #[rustfmt::skip]
+        let expected_ciphertext = [
+            0x76, 0x49, 0xab, 0xac, 0x81, 0x19, 0xb2, 0x46, 0xce, 0xe9, 0x8e, 0x9b, 0x12, 0xe9, 0x19, 0x7d,
+            0x50, 0x86, 0xcb, 0x9b, 0x50, 0x72, 0x19, 0xee, 0x95, 0xdb, 0x11, 0x3a, 0x91, 0x76, 0x78, 0xb2,
+            0x73, 0xbe, 0xd6, 0xb8, 0xe3, 0xc1, 0x74, 0x3b, 0x71, 0x16, 0xe6, 0x9e, 0x22, 0x22, 0x95, 0x16,
+            0x3f, 0xf1, 0xca, 0xa1, 0x68, 0x1f, 0xac, 0x09, 0x12, 0x0e, 0xca, 0x30, 0x75, 0x86, 0xe1, 0xa7,
+            // encrypted padding (entire block)
+            0x8c, 0xb8, 0x28, 0x07, 0x23, 0x0e, 0x13, 0x21, 0xd3, 0xfa, 0xe0, 0x0d, 0x18, 0xcc, 0x20, 0x12,
+        ];
+
+        let padder = pads::pkcs7::Pkcs7::new();
+        let iv = GenericArray::clone_from_slice(&SP800_38A_IV);
+        let mut cbc = Cbc::with_iv(rng, sp800_38a_aes(), padder, iv);
+
+        let encrypted = cbc.encrypt(SP800_38A_PLAINTEXT.to_vec());
+        assert_eq!(encrypted[..16], SP800_38A_IV);
+        assert_eq!(encrypted[16..], expected_ciphertext);
+
+        let decrypted = cbc.decrypt(encrypted).unwrap();
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_decrypt_unaligned() {
+        let seed = 1131464071u32;
+        let rng = MT::from_seed(seed.to_be_bytes());
+
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+        let mut cbc = Cbc::new(rng, cipher, padder);
+
+        let actual_output = cbc.decrypt(vec![0u8; 33]);
+        assert_eq!(
+            actual_output,
+            Err(BlockModeError::UnalignedCiphertext {
+                len: 33,
+                block_size: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_decrypt_truncated() {
+        let seed = 1131464071u32;
+        let rng = MT::from_seed(seed.to_be_bytes());
+
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+        let mut cbc = Cbc::new(rng, cipher, padder);
+
+        // an iv on its own carries no padded block
+        let actual_output = cbc.decrypt(vec![0u8; 16]);
+        assert_eq!(
+            actual_output,
+            Err(BlockModeError::TruncatedCiphertext { len: 16, min: 32 })
+        );
+
+        let actual_output = cbc.decrypt(vec![0u8; 8]);
+        assert_eq!(
+            actual_output,
+            Err(BlockModeError::TruncatedCiphertext { len: 8, min: 32 })
+        );
+    }
+
+    #[test]
+    fn test_decrypt_invalid_padding() {
+        let seed = 1131464071u32;
+        let rng = MT::from_seed(seed.to_be_bytes());
+
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+        let mut cbc = Cbc::new(rng, cipher, padder);
+
+        // flipping a bit of the second to last ciphertext block flips the same
+        // bit of the last plaintext block, turning the 0x10 padding byte into 0x00
+        let mut encrypted = cbc.encrypt(b"ABCDEFGHIJKLMNOP".to_vec());
+        encrypted[31] ^= 0x10;
+
+        assert_eq!(
+            cbc.decrypt(encrypted),
+            Err(BlockModeError::InvalidPadding(
+                pads::PaddingError::InvalidPadding
+            ))
+        );
     }
 }
@@ -0,0 +1,283 @@
+use std::marker;
+
+use generic_array::{ArrayLength, GenericArray};
+
+use super::BlockModeError;
+use crate::cipher::block::{ciphers::BlockCipher, Block};
+
+/// Where the partial final block is placed by CBC with ciphertext stealing,
+/// as defined in the addendum to NIST SP 800-38A.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StealingVariant {
+    /// The partial block precedes the final full block.
+    Cs1,
+    /// The final two blocks are swapped only when the message is not a
+    /// multiple of the block size, so aligned messages match plain CBC.
+    Cs2,
+    /// The final two blocks are always swapped, as used by Kerberos (RFC 3962).
+    Cs3,
+}
+
+// CbcCs implements cipher block chaining with ciphertext stealing as defined
+// in the addendum to NIST SP 800-38A, for fixed size formats where ciphertext
+// has to be exactly as long as the plaintext.
+
+// The final partial plaintext block P*_n is padded with zeros, and the whole
+// message is encrypted with CBC. Since the zeros XORed into C_{n-1} can be
+// recovered on decryption, only the leading d bytes of C_{n-1} are kept,
+// where d is the length of P*_n.
+// CS1: ... | C_{n-2} | C*_{n-1} | C_n |
+// CS3: ... | C_{n-2} | C_n | C*_{n-1} |
+
+// Messages must be at least one block long. Unlike Cbc, the IV is not part of
+// the ciphertext, and is passed to every call instead (e.g. derived from a
+// record number). It must still be unpredictable for each message.
+pub struct CbcCs<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    cipher: C,
+    variant: StealingVariant,
+    _marker: marker::PhantomData<N>,
+}
+
+impl<N, C> CbcCs<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    pub fn new(cipher: C, variant: StealingVariant) -> Self {
+        CbcCs {
+            cipher,
+            variant,
+            _marker: marker::PhantomData,
+        }
+    }
+
+    pub fn encrypt(&self, iv: &Block<N>, plaintext: Vec<u8>) -> Result<Vec<u8>, BlockModeError> {
+        let block_size = N::to_usize();
+        let (blocks, partial_len) = self.split(plaintext.len())?;
+
+        let mut padded = plaintext;
+        padded.resize(blocks * block_size, 0x00);
+
+        let mut prev = iv.clone();
+        let mut ciphertext_blocks = Vec::with_capacity(blocks);
+        for chunk in padded.chunks_exact(block_size) {
+            prev = self.cipher.encrypt_block(xor(&prev, chunk));
+            ciphertext_blocks.push(prev.clone());
+        }
+
+        if blocks == 1 {
+            return Ok(ciphertext_blocks[0].to_vec());
+        }
+
+        // SAFETY: there are at least two blocks
+        let last = ciphertext_blocks.pop().unwrap();
+        let stolen = ciphertext_blocks.pop().unwrap();
+
+        let mut output: Vec<u8> = ciphertext_blocks.into_iter().flatten().collect();
+        if self.swapped(partial_len) {
+            output.extend(last);
+            output.extend_from_slice(&stolen[..partial_len]);
+        } else {
+            output.extend_from_slice(&stolen[..partial_len]);
+            output.extend(last);
+        }
+
+        Ok(output)
+    }
+
+    pub fn decrypt(&self, iv: &Block<N>, ciphertext: Vec<u8>) -> Result<Vec<u8>, BlockModeError> {
+        let block_size = N::to_usize();
+        let (blocks, partial_len) = self.split(ciphertext.len())?;
+
+        if blocks == 1 {
+            let block = GenericArray::clone_from_slice(&ciphertext);
+            return Ok(xor(iv, &self.cipher.decrypt_block(block)).to_vec());
+        }
+
+        let (head, tail) = ciphertext.split_at((blocks - 2) * block_size);
+        let (partial, last) = if self.swapped(partial_len) {
+            let (last, partial) = tail.split_at(block_size);
+            (partial, last)
+        } else {
+            tail.split_at(partial_len)
+        };
+
+        // 1. decrypting C_n gives P_n XOR C_{n-1}, and since P_n was padded
+        //    with zeros its tail is the stolen tail of C_{n-1}
+        let last = self
+            .cipher
+            .decrypt_block(GenericArray::clone_from_slice(last));
+        let mut full = partial.to_vec();
+        full.extend_from_slice(&last[partial_len..]);
+
+        // 2. with C_{n-1} restored, the rest is ordinary CBC decryption
+        let mut prev = iv.clone();
+        let mut output = Vec::with_capacity(ciphertext.len());
+        for chunk in head.chunks_exact(block_size).chain([full.as_slice()]) {
+            let block = GenericArray::clone_from_slice(chunk);
+            output.extend(xor(&prev, &self.cipher.decrypt_block(block.clone())));
+            prev = block;
+        }
+
+        output.extend(last.iter().zip(partial).map(|(x, y)| x ^ y));
+
+        Ok(output)
+    }
+
+    // split returns the number of blocks in a message of len bytes, and the
+    // length of the final, possibly full, block.
+    fn split(&self, len: usize) -> Result<(usize, usize), BlockModeError> {
+        let block_size = N::to_usize();
+        if len < block_size {
+            return Err(BlockModeError::InvalidDataUnitLength(len));
+        }
+
+        let blocks = (len + block_size - 1) / block_size;
+        Ok((blocks, len - (blocks - 1) * block_size))
+    }
+
+    fn swapped(&self, partial_len: usize) -> bool {
+        match self.variant {
+            StealingVariant::Cs1 => false,
+            StealingVariant::Cs2 => partial_len != N::to_usize(),
+            StealingVariant::Cs3 => true,
+        }
+    }
+}
+
+fn xor<N: ArrayLength<u8>>(a: &Block<N>, b: &[u8]) -> Block<N> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum::U16;
+
+    use super::*;
+    use crate::{
+        cipher::block::{
+            ciphers::aes::Aes,
+            modes::tests::{sp800_38a_aes, SP800_38A_IV, SP800_38A_PLAINTEXT},
+        },
+        encode::hex::decode,
+    };
+
+    fn cbc_cs(variant: StealingVariant) -> CbcCs<U16, Aes> {
+        CbcCs::new(Aes::new(b"chicken teriyaki".to_vec()).unwrap(), variant)
+    }
+
+    const PLAINTEXT: &[u8] = b"I would like the General Gau's Chicken, please, and wonton soup.";
+
+    // RFC 3962 appendix B test vectors, which use CS3 with a zero IV
+    #[rustfmt::skip]
+    const CS3_VECTORS: [(usize, &str); 6] = [
+        (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+        (31, "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5"),
+        (32, "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584"),
+        (47, "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e39312523a78662d5be7fcbcc98ebf5"),
+        (48, "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd839312523a78662d5be7fcbcc98ebf5a8"),
+        (64, "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a84807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8"),
+    ];
+
+    // reorder moves the final block of a CS3 ciphertext back in front of the
+    // partial block, giving the CS1 ciphertext
+    fn reorder(cs3: &[u8]) -> Vec<u8> {
+        let partial_len = cs3.len() - (cs3.len() - 1) / 16 * 16;
+        let (head, tail) = cs3.split_at(cs3.len() - 16 - partial_len);
+        let (last, partial) = tail.split_at(16);
+
+        [head, partial, last].concat()
+    }
+
+    #[test]
+    fn test_rfc3962_vectors() {
+        let iv = GenericArray::default();
+        for (len, ciphertext) in CS3_VECTORS {
+            let plaintext = PLAINTEXT[..len].to_vec();
+            let cs3 = decode(ciphertext);
+            let cs1 = reorder(&cs3);
+            // CS2 only swaps when the final block is partial
+            let cs2 = if len % 16 == 0 {
+                cs1.clone()
+            } else {
+                cs3.clone()
+            };
+
+            for (variant, expected) in [
+                (StealingVariant::Cs1, cs1),
+                (StealingVariant::Cs2, cs2),
+                (StealingVariant::Cs3, cs3),
+            ] {
+                let mode = cbc_cs(variant);
+
+                let encrypted = mode.encrypt(&iv, plaintext.clone()).unwrap();
+                assert_eq!(encrypted, expected, "{:?} length {}", variant, len);
+
+                let decrypted = mode.decrypt(&iv, encrypted).unwrap();
+                assert_eq!(decrypted, plaintext, "{:?} length {}", variant, len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_length() {
+        let iv = GenericArray::clone_from_slice(&[0x5a; 16]);
+        for len in 16..=PLAINTEXT.len() {
+            let plaintext = PLAINTEXT[..len].to_vec();
+
+            let cs1 = cbc_cs(StealingVariant::Cs1);
+            let cs1_encrypted = cs1.encrypt(&iv, plaintext.clone()).unwrap();
+            assert_eq!(cs1_encrypted.len(), len);
+            assert_eq!(cs1.decrypt(&iv, cs1_encrypted.clone()).unwrap(), plaintext);
+
+            // a single block has nothing to steal from, so every variant is plain CBC
+            let cs3 = cbc_cs(StealingVariant::Cs3);
+            let cs3_encrypted = cs3.encrypt(&iv, plaintext.clone()).unwrap();
+            if len == 16 {
+                assert_eq!(cs3_encrypted, cs1_encrypted);
+            } else {
+                assert_eq!(reorder(&cs3_encrypted), cs1_encrypted);
+            }
+            assert_eq!(cs3.decrypt(&iv, cs3_encrypted).unwrap(), plaintext);
+
+            let cs2 = cbc_cs(StealingVariant::Cs2);
+            let cs2_encrypted = cs2.encrypt(&iv, plaintext.clone()).unwrap();
+            assert_eq!(cs2_encrypted.len(), len);
+            assert_eq!(cs2.decrypt(&iv, cs2_encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_aligned_cs1_matches_cbc() {
+        // NIST SP 800-38A F.2.1 CBC-AES128.Encrypt, which needs no stealing
+        let mode = CbcCs::<U16, Aes>::new(sp800_38a_aes(), StealingVariant::Cs1);
+        let iv = GenericArray::clone_from_slice(&SP800_38A_IV);
+
+        assert_eq!(
+            mode.encrypt(&iv, SP800_38A_PLAINTEXT.to_vec()).unwrap(),
+            decode(concat!(
+                "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2",
+                "73bed6b8e3c1743b7116e69e222295163ff1caa1681fac09120eca307586e1a7",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_short_message() {
+        let mode = cbc_cs(StealingVariant::Cs3);
+        let iv = GenericArray::default();
+
+        assert_eq!(
+            mode.encrypt(&iv, vec![0u8; 15]),
+            Err(BlockModeError::InvalidDataUnitLength(15))
+        );
+        assert_eq!(
+            mode.decrypt(&iv, Vec::new()),
+            Err(BlockModeError::InvalidDataUnitLength(0))
+        );
+    }
+}
@@ -1,121 +1,294 @@
 use std::marker;
 
 use generic_array::{ArrayLength, GenericArray};
-use rand::RngCore;
 
-use super::BlockMode;
 use crate::cipher::{
-    block::{ciphers::BlockCipher, pads::Padder},
-    stream::{VernamCipher, VernamCipherError},
+    block::{ciphers::BlockCipher, Block},
+    StreamCipher,
 };
 
-struct Cfb<N, R, C, P>
+/// Number of bits of plaintext processed per block cipher call by CFB.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CfbSegment {
+    /// CFB-1: one cipher call per bit.
+    Bit,
+    /// CFB-8: one cipher call per byte.
+    Byte,
+    /// Full block feedback (e.g. CFB-128 for AES): one cipher call per block.
+    #[default]
+    Block,
+}
+
+// Cfb implements cipher feedback mode as defined in NIST SP 800-38A.
+
+// CFB: C_i = P_i XOR S_i, S_i = encrypt_block(C_{i-1}, K), C_0 = IV
+
+// With a segment size s smaller than the block, only the leading s bits of
+// each keystream block are used, and the input block is a shift register: each
+// s bit ciphertext segment is shifted in at the right, and the leading s bits
+// fall off the left. The IV is the initial contents of the register.
+// I_1 = IV, I_j = LSB_{b-s}(I_{j-1}) || C#_{j-1}
+// C#_j = P#_j XOR MSB_s(encrypt_block(I_j, K))
+
+// Smaller segments resynchronize after fewer bits of lost or corrupted
+// ciphertext, at the cost of one cipher call per segment. Messages are still
+// passed in whole bytes, with CFB-1 processing each byte from its most
+// significant bit.
+
+// CFB needs no padding: a partial final block is XORed with the leading bytes
+// of its keystream block. Unlike OFB and CTR the keystream depends on the
+// ciphertext, so encryption and decryption differ, and a flipped ciphertext
+// bit garbles the following block as well as flipping the same plaintext bit.
+
+// The IV is not part of the ciphertext, and must be unpredictable for every
+// message under a key.
+pub struct Cfb<N, C>
 where
     N: ArrayLength<u8>,
-    R: RngCore,
     C: BlockCipher<N>,
-    P: Padder<N>,
 {
-    rng: R,
     cipher: C,
-    padder: P,
-    _marker: marker::PhantomData<N>, // required since N ties C and P together without being used directly
+    segment: CfbSegment,
+    register: Block<N>, // C_{i-1} while producing keystream block i, overwritten with C_i as it is produced
+    keystream: Block<N>, // encrypt_block(C_{i-1}, K)
+    offset: usize,      // offset of the next byte within the current block
+    _marker: marker::PhantomData<N>,
 }
 
-impl<N, R, C, P> Cfb<N, R, C, P>
+impl<N, C> Cfb<N, C>
 where
     N: ArrayLength<u8>,
-    R: RngCore,
     C: BlockCipher<N>,
-    P: Padder<N>,
 {
-    pub fn new(rng: R, cipher: C, padder: P) -> Self {
+    pub fn new(cipher: C, iv: Block<N>) -> Self {
+        Cfb::with_segment(cipher, iv, CfbSegment::default())
+    }
+
+    pub fn with_segment(cipher: C, iv: Block<N>, segment: CfbSegment) -> Self {
         Cfb {
-            rng,
             cipher,
-            padder,
+            segment,
+            register: iv,
+            keystream: GenericArray::default(),
+            offset: N::to_usize(), // the first keystream block is drawn from the IV
             _marker: marker::PhantomData,
         }
     }
+
+    // process XORs input with the keystream, feeding back either the input
+    // (decryption) or the output (encryption) as ciphertext.
+    fn process(&mut self, input: Vec<u8>, direction: Direction) -> Vec<u8> {
+        match self.segment {
+            CfbSegment::Bit => self.process_segments(input, 1, direction),
+            CfbSegment::Byte => self.process_segments(input, 8, direction),
+            CfbSegment::Block => self.process_blocks(input, direction),
+        }
+    }
+
+    // process_segments processes each byte of input as 8 / bits segments, with
+    // a cipher call per segment.
+    fn process_segments(&mut self, input: Vec<u8>, bits: u32, direction: Direction) -> Vec<u8> {
+        let mask = ((1u16 << bits) - 1) as u8;
+
+        input
+            .into_iter()
+            .map(|byte| {
+                let mut output = 0u8;
+                for shift in (0..8).step_by(bits as usize).rev() {
+                    let keystream = self.cipher.encrypt_block(self.register.clone())[0];
+
+                    let segment = (byte >> shift) & mask;
+                    let processed = segment ^ (keystream >> (8 - bits));
+                    let ciphertext = match direction {
+                        Direction::Encrypt => processed,
+                        Direction::Decrypt => segment,
+                    };
+                    shift_in(&mut self.register, ciphertext, bits);
+
+                    output |= processed << shift;
+                }
+
+                output
+            })
+            .collect()
+    }
+
+    fn process_blocks(&mut self, input: Vec<u8>, direction: Direction) -> Vec<u8> {
+        let block_size = N::to_usize();
+
+        input
+            .into_iter()
+            .map(|byte| {
+                if self.offset == block_size {
+                    self.keystream = self.cipher.encrypt_block(self.register.clone());
+                    self.offset = 0;
+                }
+
+                let output = byte ^ self.keystream[self.offset];
+                self.register[self.offset] = match direction {
+                    Direction::Encrypt => output,
+                    Direction::Decrypt => byte,
+                };
+                self.offset += 1;
+
+                output
+            })
+            .collect()
+    }
 }
 
-impl<N, R, C, P> BlockMode<N, C, P> for Cfb<N, R, C, P>
+impl<N, C> StreamCipher for Cfb<N, C>
 where
     N: ArrayLength<u8>,
-    R: RngCore,
     C: BlockCipher<N>,
-    P: Padder<N>,
 {
     fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
-        let mut iv = vec![0u8; N::to_usize()];
-        self.rng.fill_bytes(iv.as_mut_slice());
-        let mut prev_output = GenericArray::clone_from_slice(iv.as_slice());
-
-        let ciphertext_blocks = self
-            .padder
-            .pad(plaintext) // padding the plaintext even though CFB is a stream cipher
-            .into_iter()
-            .flat_map(|plaintext_chunk| {
-                let streamkey_chunk = self.cipher.encrypt_block(prev_output.clone());
-                let ciphertext_block = VernamCipher::new(
-                    plaintext_chunk.iter().copied(),
-                    streamkey_chunk.iter().copied(),
-                )
-                .collect::<Result<Vec<u8>, VernamCipherError>>()
-                .unwrap();
-
-                prev_output = GenericArray::clone_from_slice(ciphertext_block.as_slice());
-
-                ciphertext_block
-            })
-            .collect();
+        self.process(plaintext, Direction::Encrypt)
+    }
 
-        ciphertext_blocks
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.process(ciphertext, Direction::Decrypt)
     }
+}
 
-    fn decrypt(&mut self, _ciphertext: Vec<u8>) -> Vec<u8> {
-        unimplemented!()
+#[derive(Copy, Clone)]
+enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+// shift_in shifts the register left by bits (at most 8), and fills the
+// vacated low bits with segment.
+fn shift_in<N: ArrayLength<u8>>(register: &mut Block<N>, segment: u8, bits: u32) {
+    let mut carry = segment;
+    for byte in register.iter_mut().rev() {
+        let shifted = (u16::from(*byte) << bits) | u16::from(carry);
+        *byte = shifted as u8;
+        carry = (shifted >> 8) as u8;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use rand::SeedableRng;
-
     use super::*;
-    use crate::{
-        cipher::block::{ciphers, pads},
-        rng::MT,
+    use crate::cipher::block::modes::tests::{
+        sp800_38a_aes, sp800_38a_cfb, SP800_38A_IV, SP800_38A_PLAINTEXT,
     };
 
+    // NIST SP 800-38A F.3.13 CFB128-AES128.Encrypt and F.3.14 CFB128-AES128.Decrypt
+    #[rustfmt::skip]
+    const SP800_38A_CIPHERTEXT: [u8; 64] = [
+        0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a,
+        0xc8, 0xa6, 0x45, 0x37, 0xa0, 0xb3, 0xa9, 0x3f, 0xcd, 0xe3, 0xcd, 0xad, 0x9f, 0x1c, 0xe5, 0x8b,
+        0x26, 0x75, 0x1f, 0x67, 0xa3, 0xcb, 0xb1, 0x40, 0xb1, 0x80, 0x8c, 0xf1, 0x87, 0xa4, 0xf4, 0xdf,
+        0xc0, 0x4b, 0x05, 0x35, 0x7c, 0x5d, 0x1c, 0x0e, 0xea, 0xc4, 0xc6, 0x6f, 0x9f, 0xf7, 0xf2, 0xe6,
+    ];
+
+    #[test]
+    fn test_sp800_38a_cfb128_aes128() {
+        let mut cfb = sp800_38a_cfb();
+        let encrypted = cfb.encrypt(SP800_38A_PLAINTEXT.to_vec());
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+
+        let mut cfb = sp800_38a_cfb();
+        let decrypted = cfb.decrypt(SP800_38A_CIPHERTEXT.to_vec());
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_decrypt_in_pieces() {
+        // consecutive calls continue the stream, even when splitting blocks
+        let mut cfb = sp800_38a_cfb();
+        let mut decrypted = cfb.decrypt(SP800_38A_CIPHERTEXT[..5].to_vec());
+        decrypted.extend(cfb.decrypt(SP800_38A_CIPHERTEXT[5..16].to_vec()));
+        decrypted.extend(cfb.decrypt(SP800_38A_CIPHERTEXT[16..37].to_vec()));
+        decrypted.extend(cfb.decrypt(SP800_38A_CIPHERTEXT[37..].to_vec()));
+
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_partial_block() {
+        // no padding: a partial final block encrypts to the same length
+        let mut cfb = sp800_38a_cfb();
+        let encrypted = cfb.encrypt(SP800_38A_PLAINTEXT[..21].to_vec());
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT[..21]);
+
+        let mut cfb = sp800_38a_cfb();
+        let decrypted = cfb.decrypt(encrypted);
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT[..21]);
+    }
+
+    #[test]
+    fn test_error_propagation() {
+        // flipping a ciphertext bit flips the same plaintext bit, and garbles
+        // the whole of the next block
+        let mut ciphertext = SP800_38A_CIPHERTEXT.to_vec();
+        ciphertext[3] ^= 0x01;
+
+        let mut cfb = sp800_38a_cfb();
+        let decrypted = cfb.decrypt(ciphertext);
+        assert_eq!(decrypted[3], SP800_38A_PLAINTEXT[3] ^ 0x01);
+        assert_ne!(decrypted[16..32], SP800_38A_PLAINTEXT[16..32]);
+        assert_eq!(decrypted[32..], SP800_38A_PLAINTEXT[32..]);
+    }
+
+    // NIST SP 800-38A F.3.1 CFB1-AES128.Encrypt and F.3.2 CFB1-AES128.Decrypt,
+    // whose 16 bits of plaintext are the leading bits of the CFB128 plaintext
+    #[test]
+    fn test_sp800_38a_cfb1_aes128() {
+        let cfb1 = || {
+            let cipher = sp800_38a_aes();
+            let iv = GenericArray::clone_from_slice(&SP800_38A_IV);
+            Cfb::with_segment(cipher, iv, CfbSegment::Bit)
+        };
+
+        let encrypted = cfb1().encrypt(SP800_38A_PLAINTEXT[..2].to_vec());
+        assert_eq!(encrypted, [0x68, 0xb3]);
+
+        let decrypted = cfb1().decrypt(encrypted);
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT[..2]);
+    }
+
+    // NIST SP 800-38A F.3.7 CFB8-AES128.Encrypt and F.3.8 CFB8-AES128.Decrypt
     #[test]
-    fn test_encrypt_one_block() {
-        let seed = 1131464071u32;
-        let rng = MT::from_seed(seed.to_be_bytes());
-
-        let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
-        let padder = pads::pkcs7::Pkcs7::new();
-
-        let mut cbc = Cfb::new(rng, cipher, padder);
-
-        let plaintext = b"ABCDEFGHIJKLMNOP";
-        let encrypted = cbc.encrypt(plaintext.to_vec());
-
-        println!("{:?}", encrypted);
-        // #[rustfmt::skip]
-        // let expected_output = [
-        //     0xf5, 0x45, 0xc0, 0x06,
-        //     0x06, 0x91, 0x26, 0xd9,
-        //     0xc0, 0xf9, 0x3f, 0xa7,
-        //     0xdd, 0x89, 0xab, 0x98,
-        //     // encrypted padding (entire block)
-        //     0x60, 0xfa, 0x36, 0x70,
-        //     0x7e, 0x45, 0xf4, 0x99,
-        //     0xdb, 0xa0, 0xf2, 0x5b,
-        //     0x92, 0x23, 0x01, 0xa5,
-        // ];
-
-        // assert_eq!(encrypted, expected_output);
+    fn test_sp800_38a_cfb8_aes128() {
+        let cfb8 = || {
+            let cipher = sp800_38a_aes();
+            let iv = GenericArray::clone_from_slice(&SP800_38A_IV);
+            Cfb::with_segment(cipher, iv, CfbSegment::Byte)
+        };
+
+        #[rustfmt::skip]
+        let expected_ciphertext = [
+            0x3b, 0x79, 0x42, 0x4c, 0x9c, 0x0d, 0xd4, 0x36, 0xba,
+            0xce, 0x9e, 0x0e, 0xd4, 0x58, 0x6a, 0x4f, 0x32, 0xb9,
+        ];
+
+        let encrypted = cfb8().encrypt(SP800_38A_PLAINTEXT[..18].to_vec());
+        assert_eq!(encrypted, expected_ciphertext);
+
+        let mut cfb = cfb8();
+        let mut decrypted = cfb.decrypt(expected_ciphertext[..7].to_vec());
+        decrypted.extend(cfb.decrypt(expected_ciphertext[7..].to_vec()));
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT[..18]);
+    }
+
+    #[test]
+    fn test_segment_resynchronizes() {
+        // a corrupted CFB-8 byte garbles only the next block's worth of bytes,
+        // after which it has been shifted out of the register
+        let cfb8 = || {
+            let cipher = sp800_38a_aes();
+            let iv = GenericArray::clone_from_slice(&SP800_38A_IV);
+            Cfb::with_segment(cipher, iv, CfbSegment::Byte)
+        };
+
+        let mut ciphertext = cfb8().encrypt(SP800_38A_PLAINTEXT.to_vec());
+        ciphertext[3] ^= 0x01;
+
+        let decrypted = cfb8().decrypt(ciphertext);
+        assert_eq!(decrypted[3], SP800_38A_PLAINTEXT[3] ^ 0x01);
+        assert_eq!(decrypted[20..], SP800_38A_PLAINTEXT[20..]);
     }
 }
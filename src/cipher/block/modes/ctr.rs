@@ -0,0 +1,339 @@
+use std::marker;
+
+use generic_array::{ArrayLength, GenericArray};
+
+use super::BlockModeError;
+use crate::cipher::{
+    block::{ciphers::BlockCipher, Block},
+    stream::{VernamCipher, VernamCipherError},
+    StreamCipher,
+};
+
+/// Byte order of the counter half of a CTR counter block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// `CounterLayout` describes how a CTR counter block is split between the
+/// nonce and the counter. The nonce always occupies the leading bytes of the
+/// block, and the counter the trailing `counter_len` bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CounterLayout {
+    pub counter_len: usize,
+    pub endianness: Endianness,
+}
+
+impl CounterLayout {
+    /// 64 bit nonce followed by a 64 bit little-endian counter, as used by cryptopals.
+    pub const LE64: CounterLayout = CounterLayout {
+        counter_len: 8,
+        endianness: Endianness::Little,
+    };
+
+    /// 64 bit nonce followed by a 64 bit big-endian counter, as used by NIST SP 800-38A.
+    pub const BE64: CounterLayout = CounterLayout {
+        counter_len: 8,
+        endianness: Endianness::Big,
+    };
+
+    /// 96 bit nonce followed by a 32 bit big-endian counter, as used by GCM.
+    pub const BE32: CounterLayout = CounterLayout {
+        counter_len: 4,
+        endianness: Endianness::Big,
+    };
+
+    fn counter_bytes(&self, counter: u64) -> Vec<u8> {
+        match self.endianness {
+            Endianness::Little => counter.to_le_bytes()[..self.counter_len].to_vec(),
+            Endianness::Big => counter.to_be_bytes()[8 - self.counter_len..].to_vec(),
+        }
+    }
+}
+
+// Ctr implements counter mode as defined in NIST SP 800-38A.
+// CTR turns a block cipher into a stream cipher by encrypting successive
+// values of a counter, and XORing the resulting keystream with the plaintext.
+
+// CTR: C_i = P_i XOR encrypt_block(nonce || counter_i, K)
+
+// Since the keystream does not depend on the plaintext, CTR needs no padding,
+// produces ciphertext of the same length as the plaintext, and permits random
+// access: block i of the keystream is computed directly from counter i.
+// Encryption and decryption are the same operation.
+
+// Reusing a (key, nonce) pair reuses the keystream, which reduces CTR to a
+// many-time pad (see cryptopals challenges 19 and 20).
+pub struct Ctr<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    cipher: C,
+    nonce: Vec<u8>,
+    layout: CounterLayout,
+    block: u64,    // counter of the block the next byte is drawn from
+    offset: usize, // offset of the next byte within that block
+    _marker: marker::PhantomData<N>,
+}
+
+impl<N, C> Ctr<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    pub fn new(cipher: C, nonce: Vec<u8>, layout: CounterLayout) -> Result<Self, BlockModeError> {
+        let block_size = N::to_usize();
+        if layout.counter_len == 0 || layout.counter_len > 8 || layout.counter_len > block_size {
+            return Err(BlockModeError::InvalidCounterLength(layout.counter_len));
+        }
+
+        let expected = block_size - layout.counter_len;
+        if nonce.len() != expected {
+            return Err(BlockModeError::InvalidNonceLength {
+                expected,
+                actual: nonce.len(),
+            });
+        }
+
+        Ok(Ctr {
+            cipher,
+            nonce,
+            layout,
+            block: 0,
+            offset: 0,
+            _marker: marker::PhantomData,
+        })
+    }
+
+    // seek moves the stream to the start of the given block. The block is used
+    // as the counter directly, so seeking also sets the initial counter value
+    // (e.g. GCM encrypts its payload starting from counter 2).
+    pub fn seek(&mut self, block: u64) {
+        self.block = block;
+        self.offset = 0;
+    }
+
+    // keystream returns the keystream starting from the current position,
+    // without advancing it.
+    pub fn keystream(&self) -> Keystream<'_, N, C> {
+        let mut keystream = Keystream::new(&self.cipher, &self.nonce, self.layout, self.block);
+        keystream.offset = self.offset;
+
+        keystream
+    }
+
+    fn apply_keystream(&mut self, input: Vec<u8>) -> Vec<u8> {
+        let len = input.len();
+        let output = VernamCipher::new(input.into_iter(), self.keystream().take(len))
+            .collect::<Result<Vec<u8>, VernamCipherError>>()
+            .unwrap(); // SAFETY: the keystream is infinite, so take(len) always yields len bytes
+
+        let position = self.offset + len;
+        let block_size = N::to_usize();
+        self.block = self.block.wrapping_add((position / block_size) as u64);
+        self.offset = position % block_size;
+
+        output
+    }
+}
+
+impl<N, C> StreamCipher for Ctr<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(ciphertext)
+    }
+}
+
+/// An infinite iterator over a CTR keystream.
+///
+/// `Keystream` composes with `VernamCipher` so the keystream can be XORed
+/// with arbitrary byte iterators.
+pub struct Keystream<'a, N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    cipher: &'a C,
+    nonce: &'a [u8],
+    layout: CounterLayout,
+    block: u64,
+    offset: usize,
+    buffer: Option<Block<N>>,
+}
+
+impl<'a, N, C> Keystream<'a, N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    // new borrows a cipher to generate a keystream from the given counter, for
+    // constructions such as GCM which derive their counter blocks per message.
+    // The caller is responsible for nonce.len() + layout.counter_len == N.
+    pub(crate) fn new(cipher: &'a C, nonce: &'a [u8], layout: CounterLayout, block: u64) -> Self {
+        Keystream {
+            cipher,
+            nonce,
+            layout,
+            block,
+            offset: 0,
+            buffer: None,
+        }
+    }
+}
+
+impl<'a, N, C> Iterator for Keystream<'a, N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buffer = match self.buffer.take() {
+            Some(buffer) => buffer,
+            None => {
+                // counter values wrap modulo 2^(8 * counter_len)
+                let counter_block = self
+                    .nonce
+                    .iter()
+                    .copied()
+                    .chain(self.layout.counter_bytes(self.block))
+                    .collect::<Vec<u8>>();
+
+                self.cipher
+                    .encrypt_block(GenericArray::clone_from_slice(&counter_block))
+            }
+        };
+
+        let byte = buffer[self.offset];
+        self.offset += 1;
+        if self.offset == N::to_usize() {
+            self.block = self.block.wrapping_add(1);
+            self.offset = 0;
+        } else {
+            self.buffer = Some(buffer);
+        }
+
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cipher::block::{
+            ciphers,
+            modes::tests::{sp800_38a_aes, sp800_38a_ctr, SP800_38A_COUNTER, SP800_38A_PLAINTEXT},
+        },
+        encode::base64,
+    };
+
+    // NIST SP 800-38A F.5.1 CTR-AES128.Encrypt and F.5.2 CTR-AES128.Decrypt
+    #[rustfmt::skip]
+    const SP800_38A_CIPHERTEXT: [u8; 64] = [
+        0x87, 0x4d, 0x61, 0x91, 0xb6, 0x20, 0xe3, 0x26, 0x1b, 0xef, 0x68, 0x64, 0x99, 0x0d, 0xb6, 0xce,
+        0x98, 0x06, 0xf6, 0x6b, 0x79, 0x70, 0xfd, 0xff, 0x86, 0x17, 0x18, 0x7b, 0xb9, 0xff, 0xfd, 0xff,
+        0x5a, 0xe4, 0xdf, 0x3e, 0xdb, 0xd5, 0xd3, 0x5e, 0x5b, 0x4f, 0x09, 0x02, 0x0d, 0xb0, 0x3e, 0xab,
+        0x1e, 0x03, 0x1d, 0xda, 0x2f, 0xbe, 0x03, 0xd1, 0x79, 0x21, 0x70, 0xa0, 0xf3, 0x00, 0x9c, 0xee,
+    ];
+
+    #[test]
+    fn test_sp800_38a_ctr_aes128() {
+        let mut ctr = sp800_38a_ctr();
+        let encrypted = ctr.encrypt(SP800_38A_PLAINTEXT.to_vec());
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+
+        let mut ctr = sp800_38a_ctr();
+        let decrypted = ctr.decrypt(SP800_38A_CIPHERTEXT.to_vec());
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_encrypt_in_pieces() {
+        // consecutive calls continue the keystream, even when splitting blocks
+        let mut ctr = sp800_38a_ctr();
+        let mut encrypted = ctr.encrypt(SP800_38A_PLAINTEXT[..5].to_vec());
+        encrypted.extend(ctr.encrypt(SP800_38A_PLAINTEXT[5..16].to_vec()));
+        encrypted.extend(ctr.encrypt(SP800_38A_PLAINTEXT[16..37].to_vec()));
+        encrypted.extend(ctr.encrypt(SP800_38A_PLAINTEXT[37..].to_vec()));
+
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut ctr = sp800_38a_ctr();
+        ctr.seek(SP800_38A_COUNTER + 2);
+
+        let decrypted = ctr.decrypt(SP800_38A_CIPHERTEXT[32..48].to_vec());
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT[32..48]);
+    }
+
+    #[test]
+    fn test_keystream_composes_with_vernam() {
+        let ctr = sp800_38a_ctr();
+        let keystream = ctr.keystream().take(SP800_38A_PLAINTEXT.len());
+
+        let encrypted = VernamCipher::new(SP800_38A_PLAINTEXT.into_iter(), keystream)
+            .collect::<Result<Vec<u8>, VernamCipherError>>()
+            .unwrap();
+
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+    }
+
+    // cryptopals challenge 18: nonce 0, 64 bit little-endian block counter
+    #[test]
+    fn test_cryptopals_little_endian_counter() {
+        let ciphertext = base64::Base64ToByteDecoder::new(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==".chars(),
+        )
+        .collect::<Result<Vec<u8>, base64::Base64Error>>()
+        .unwrap();
+
+        let cipher = ciphers::aes::Aes::new(b"YELLOW SUBMARINE".to_vec()).unwrap();
+        let mut ctr = Ctr::new(cipher, vec![0u8; 8], CounterLayout::LE64).unwrap();
+
+        let decrypted = ctr.decrypt(ciphertext);
+        assert_eq!(
+            decrypted,
+            b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
+        );
+    }
+
+    #[test]
+    fn test_counter_wraps() {
+        // a 32 bit counter wraps independently of the nonce
+        let cipher = sp800_38a_aes();
+        let mut wrapped = Ctr::new(cipher, vec![0u8; 12], CounterLayout::BE32).unwrap();
+        wrapped.seek(u32::MAX as u64 + 1);
+
+        let cipher = sp800_38a_aes();
+        let mut zero = Ctr::new(cipher, vec![0u8; 12], CounterLayout::BE32).unwrap();
+
+        assert_eq!(wrapped.encrypt(vec![0u8; 32]), zero.encrypt(vec![0u8; 32]));
+    }
+
+    #[test]
+    fn test_invalid_nonce_length() {
+        let cipher = sp800_38a_aes();
+        let ctr = Ctr::new(cipher, vec![0u8; 8], CounterLayout::BE32);
+
+        assert!(matches!(
+            ctr,
+            Err(BlockModeError::InvalidNonceLength {
+                expected: 12,
+                actual: 8
+            })
+        ));
+    }
+}
@@ -3,8 +3,9 @@ use std::marker;
 use generic_array::{ArrayLength, GenericArray};
 
 use super::super::ciphers::BlockCipher;
-use super::super::pads::Padder;
-use super::BlockMode;
+use super::super::pads::{Padder, PaddingError};
+use super::super::Block;
+use super::{BlockMode, BlockModeError, IncrementalMode};
 
 pub struct Ecb<N, C, P>
 where
@@ -43,21 +44,68 @@ where
             .padder
             .pad(plaintext)
             .into_iter()
-            .map(|plaintext_block| self.cipher.encrypt_block(plaintext_block))
+            .map(|plaintext_block| self.encrypt_next(plaintext_block))
             .flat_map(|b| b.as_slice().to_vec())
             .collect();
 
         ciphertext_blocks
     }
 
-    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Result<Vec<u8>, BlockModeError> {
+        let block_size = N::to_usize();
+        if ciphertext.len() < block_size {
+            return Err(BlockModeError::TruncatedCiphertext {
+                len: ciphertext.len(),
+                min: block_size,
+            });
+        }
+        if ciphertext.len() % block_size != 0 {
+            return Err(BlockModeError::UnalignedCiphertext {
+                len: ciphertext.len(),
+                block_size,
+            });
+        }
+
         let plaintext_blocks = ciphertext
-            .chunks_exact(N::to_usize()) // TODO: what do if input has remainder
+            .chunks_exact(block_size)
             .map(|chunk| GenericArray::clone_from_slice(chunk))
-            .map(|ciphertext_block| self.cipher.decrypt_block(ciphertext_block))
+            .map(|ciphertext_block| self.decrypt_next(ciphertext_block))
             .collect();
 
-        self.padder.unpad(plaintext_blocks)
+        Ok(self.padder.unpad(plaintext_blocks)?)
+    }
+}
+
+impl<N, C, P> IncrementalMode<N> for Ecb<N, C, P>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+    P: Padder<N>,
+{
+    fn header_blocks(&self) -> usize {
+        0
+    }
+
+    fn start_encryption(&mut self) -> Vec<Block<N>> {
+        Vec::new()
+    }
+
+    fn encrypt_next(&mut self, block: Block<N>) -> Block<N> {
+        self.cipher.encrypt_block(block)
+    }
+
+    fn start_decryption(&mut self, _header: Vec<Block<N>>) {}
+
+    fn decrypt_next(&mut self, block: Block<N>) -> Block<N> {
+        self.cipher.decrypt_block(block)
+    }
+
+    fn pad_final(&self, remainder: Vec<u8>) -> Vec<Block<N>> {
+        self.padder.pad(remainder)
+    }
+
+    fn unpad_final(&self, block: Block<N>) -> Result<Vec<u8>, PaddingError> {
+        self.padder.unpad(vec![block])
     }
 }
 
@@ -69,7 +117,7 @@ mod tests {
     #[test]
     fn test_encrypt_half_block() {
         let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
         let padder = pads::pkcs7::Pkcs7::new();
         let mut ecb = Ecb::new(cipher, padder);
 
@@ -89,7 +137,7 @@ mod tests {
     #[test]
     fn test_encrypt_one_block() {
         let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
         let padder = pads::pkcs7::Pkcs7::new();
         let mut ecb = Ecb::new(cipher, padder);
 
@@ -114,7 +162,7 @@ mod tests {
     #[test]
     fn test_encrypt_two_blocks() {
         let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
         let padder = pads::pkcs7::Pkcs7::new();
         let mut ecb = Ecb::new(cipher, padder);
 
@@ -141,4 +189,41 @@ mod tests {
 
         assert_eq!(encrypted, expected_output);
     }
+
+    #[test]
+    fn test_decrypt_two_blocks() {
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+        let mut ecb = Ecb::new(cipher, padder);
+
+        let plaintext = b"ABCDEFGHIJKLMNOPABCDEFGH";
+        let encrypted = ecb.encrypt(plaintext.to_vec());
+        let decrypted = ecb.decrypt(encrypted).unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_unaligned() {
+        let key = b"YELLOW SUBMARINE";
+        let cipher = ciphers::aes::Aes::new(key.to_vec()).unwrap();
+        let padder = pads::pkcs7::Pkcs7::new();
+        let mut ecb = Ecb::new(cipher, padder);
+
+        let actual_output = ecb.decrypt(vec![0u8; 17]);
+        assert_eq!(
+            actual_output,
+            Err(BlockModeError::UnalignedCiphertext {
+                len: 17,
+                block_size: 16
+            })
+        );
+
+        let actual_output = ecb.decrypt(Vec::new());
+        assert_eq!(
+            actual_output,
+            Err(BlockModeError::TruncatedCiphertext { len: 0, min: 16 })
+        );
+    }
 }
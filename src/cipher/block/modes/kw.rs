@@ -0,0 +1,348 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::BlockModeError;
+use crate::{cipher::block::ciphers::BlockCipher, utils::constant_time_eq};
+
+// KeyWrap implements the AES key wrap algorithm (KW) defined in RFC 3394, and
+// key wrap with padding (KWP) defined in RFC 5649, both also specified in
+// NIST SP 800-38F. Key wrap encrypts key material under a key-encryption key
+// (KEK), deterministically and with integrity protection.
+
+// KW splits the key data into 64 bit blocks R_1..R_n (n >= 2) and runs six
+// passes of a Feistel-like network over them, chaining through a 64 bit
+// register A which starts out as the integrity check value (ICV):
+// for j = 0..5, i = 1..n:
+//     B   = encrypt_block(A || R_i, K)
+//     A   = MSB_64(B) XOR (n * j + i)
+//     R_i = LSB_64(B)
+// C = A || R_1 || ... || R_n
+
+// Unwrapping runs the passes backwards, and the recovered A must equal the
+// ICV. Any change to the ciphertext changes A with overwhelming probability.
+
+// KWP zero pads the key data to a multiple of 64 bits, and replaces the ICV
+// with A65959A6 || [len(P)]_32 so the padding can be removed. Key data of at
+// most 8 bytes is wrapped with a single block cipher call.
+pub struct KeyWrap<C>
+where
+    C: BlockCipher<U16>,
+{
+    cipher: C,
+}
+
+const ICV: [u8; 8] = [0xa6; 8];
+const ALTERNATIVE_ICV: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+impl<C> KeyWrap<C>
+where
+    C: BlockCipher<U16>,
+{
+    pub fn new(cipher: C) -> Self {
+        KeyWrap { cipher }
+    }
+
+    // wrap requires key data of at least 16 bytes, in a multiple of 8 bytes.
+    pub fn wrap(&self, key_data: &[u8]) -> Result<Vec<u8>, BlockModeError> {
+        if key_data.len() < 16 || key_data.len() % 8 != 0 {
+            return Err(BlockModeError::InvalidKeyDataLength(key_data.len()));
+        }
+
+        Ok(self.w(ICV, key_data))
+    }
+
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, BlockModeError> {
+        check_wrapped_len(wrapped, 24)?;
+
+        let (a, key_data) = self.w_inverse(wrapped);
+        if !constant_time_eq(&a, &ICV) {
+            return Err(BlockModeError::IntegrityCheckFailed);
+        }
+
+        Ok(key_data)
+    }
+
+    // wrap_with_padding accepts key data of any length from 1 to 2^32 - 1 bytes.
+    pub fn wrap_with_padding(&self, key_data: &[u8]) -> Result<Vec<u8>, BlockModeError> {
+        let len = u32::try_from(key_data.len())
+            .ok()
+            .filter(|&len| len > 0)
+            .ok_or(BlockModeError::InvalidKeyDataLength(key_data.len()))?;
+
+        let mut aiv = [0u8; 8];
+        aiv[..4].copy_from_slice(&ALTERNATIVE_ICV);
+        aiv[4..].copy_from_slice(&len.to_be_bytes());
+
+        let mut padded = key_data.to_vec();
+        padded.resize((key_data.len() + 7) / 8 * 8, 0x00);
+
+        if padded.len() == 8 {
+            let block = [aiv.as_slice(), &padded].concat();
+            return Ok(self
+                .cipher
+                .encrypt_block(GenericArray::clone_from_slice(&block))
+                .to_vec());
+        }
+
+        Ok(self.w(aiv, &padded))
+    }
+
+    pub fn unwrap_with_padding(&self, wrapped: &[u8]) -> Result<Vec<u8>, BlockModeError> {
+        check_wrapped_len(wrapped, 16)?;
+
+        let (a, padded) = if wrapped.len() == 16 {
+            let block = self
+                .cipher
+                .decrypt_block(GenericArray::clone_from_slice(wrapped));
+            let (a, padded) = block.split_at(8);
+
+            // SAFETY: the block splits into two 8 byte halves
+            (a.try_into().unwrap(), padded.to_vec())
+        } else {
+            self.w_inverse(wrapped)
+        };
+
+        // the ICV prefix, the length and the zero padding all have to check
+        // out, without revealing which of them failed
+        let len = u32::from_be_bytes([a[4], a[5], a[6], a[7]]) as usize;
+        let len_valid = len <= padded.len() && len + 8 > padded.len();
+        let padding_valid = padded
+            .iter()
+            .skip(len.min(padded.len()))
+            .fold(0u8, |acc, byte| acc | byte)
+            == 0;
+        if !constant_time_eq(&a[..4], &ALTERNATIVE_ICV) || !len_valid || !padding_valid {
+            return Err(BlockModeError::IntegrityCheckFailed);
+        }
+
+        let mut key_data = padded;
+        key_data.truncate(len);
+
+        Ok(key_data)
+    }
+
+    // w is the wrapping function, run over n >= 2 64 bit blocks.
+    fn w(&self, iv: [u8; 8], input: &[u8]) -> Vec<u8> {
+        let mut a = iv;
+        let mut r = input
+            .chunks_exact(8)
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>();
+        let n = r.len();
+
+        for j in 0..6 {
+            for (i, r_i) in r.iter_mut().enumerate() {
+                let block = [a.as_slice(), r_i].concat();
+                let b = self
+                    .cipher
+                    .encrypt_block(GenericArray::clone_from_slice(&block));
+
+                let t = ((n * j + i + 1) as u64).to_be_bytes();
+                for ((a, b), t) in a.iter_mut().zip(&b[..8]).zip(t) {
+                    *a = b ^ t;
+                }
+                r_i.copy_from_slice(&b[8..]);
+            }
+        }
+
+        a.into_iter().chain(r.into_iter().flatten()).collect()
+    }
+
+    // w_inverse is the unwrapping function, returning the recovered A and the
+    // 64 bit blocks R_1..R_n.
+    fn w_inverse(&self, input: &[u8]) -> ([u8; 8], Vec<u8>) {
+        let (a, rest) = input.split_at(8);
+        let mut a: [u8; 8] = a.try_into().unwrap(); // SAFETY: split at 8
+        let mut r = rest
+            .chunks_exact(8)
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>();
+        let n = r.len();
+
+        for j in (0..6).rev() {
+            for (i, r_i) in r.iter_mut().enumerate().rev() {
+                let t = ((n * j + i + 1) as u64).to_be_bytes();
+                let block = a
+                    .iter()
+                    .zip(t)
+                    .map(|(a, t)| a ^ t)
+                    .chain(r_i.iter().copied())
+                    .collect::<Vec<u8>>();
+                let b = self
+                    .cipher
+                    .decrypt_block(GenericArray::clone_from_slice(&block));
+
+                a.copy_from_slice(&b[..8]);
+                r_i.copy_from_slice(&b[8..]);
+            }
+        }
+
+        (a, r.into_iter().flatten().collect())
+    }
+}
+
+fn check_wrapped_len(wrapped: &[u8], min: usize) -> Result<(), BlockModeError> {
+    if wrapped.len() < min {
+        return Err(BlockModeError::TruncatedCiphertext {
+            len: wrapped.len(),
+            min,
+        });
+    }
+    if wrapped.len() % 8 != 0 {
+        return Err(BlockModeError::UnalignedCiphertext {
+            len: wrapped.len(),
+            block_size: 8,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cipher::block::ciphers::aes::Aes, encode::hex::decode};
+
+    fn key_wrap(kek: &str) -> KeyWrap<Aes> {
+        KeyWrap::new(Aes::new(decode(kek)).unwrap())
+    }
+
+    const KEK_128: &str = "000102030405060708090a0b0c0d0e0f";
+    const KEK_192: &str = "000102030405060708090a0b0c0d0e0f1011121314151617";
+    const KEK_256: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    // RFC 3394 section 4 test vectors
+    #[rustfmt::skip]
+    const RFC3394_VECTORS: [(&str, &str, &str); 6] = [
+        // 4.1 wrap 128 bits of key data with a 128 bit KEK
+        (KEK_128, "00112233445566778899aabbccddeeff",
+            "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5"),
+        // 4.2 wrap 128 bits of key data with a 192 bit KEK
+        (KEK_192, "00112233445566778899aabbccddeeff",
+            "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d"),
+        // 4.3 wrap 128 bits of key data with a 256 bit KEK
+        (KEK_256, "00112233445566778899aabbccddeeff",
+            "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7"),
+        // 4.4 wrap 192 bits of key data with a 192 bit KEK
+        (KEK_192, "00112233445566778899aabbccddeeff0001020304050607",
+            "031d33264e15d33268f24ec260743edce1c6c7ddee725a936ba814915c6762d2"),
+        // 4.5 wrap 192 bits of key data with a 256 bit KEK
+        (KEK_256, "00112233445566778899aabbccddeeff0001020304050607",
+            "a8f9bc1612c68b3ff6e6f4fbe30e71e4769c8b80a32cb8958cd5d17d6b254da1"),
+        // 4.6 wrap 256 bits of key data with a 256 bit KEK
+        (KEK_256, "00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f",
+            "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21"),
+    ];
+
+    #[test]
+    fn test_rfc3394_vectors() {
+        for (kek, key_data, wrapped) in RFC3394_VECTORS {
+            let kw = key_wrap(kek);
+
+            assert_eq!(kw.wrap(&decode(key_data)).unwrap(), decode(wrapped));
+            assert_eq!(kw.unwrap(&decode(wrapped)).unwrap(), decode(key_data));
+        }
+    }
+
+    // RFC 5649 section 6 test vectors
+    #[test]
+    fn test_rfc5649_vectors() {
+        let kw = key_wrap("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8");
+
+        for (key_data, wrapped) in [
+            (
+                "c37b7e6492584340bed12207808941155068f738",
+                "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a",
+            ),
+            // a single block
+            ("466f7250617369", "afbeb0f07dfbf5419200f2ccb50bb24f"),
+        ] {
+            assert_eq!(
+                kw.wrap_with_padding(&decode(key_data)).unwrap(),
+                decode(wrapped)
+            );
+            assert_eq!(
+                kw.unwrap_with_padding(&decode(wrapped)).unwrap(),
+                decode(key_data)
+            );
+        }
+    }
+
+    #[test]
+    fn test_padded_round_trip() {
+        let kw = key_wrap(KEK_256);
+        for len in 1..=40 {
+            let key_data = (0..len as u8).collect::<Vec<u8>>();
+            let wrapped = kw.wrap_with_padding(&key_data).unwrap();
+
+            assert_eq!(wrapped.len(), (len + 7) / 8 * 8 + 8);
+            assert_eq!(kw.unwrap_with_padding(&wrapped).unwrap(), key_data);
+        }
+    }
+
+    #[test]
+    fn test_tampering_fails() {
+        let kw = key_wrap(KEK_128);
+        let (_, key_data, wrapped) = RFC3394_VECTORS[0];
+
+        for i in 0..24 {
+            let mut tampered = decode(wrapped);
+            tampered[i] ^= 0x01;
+            assert_eq!(
+                kw.unwrap(&tampered),
+                Err(BlockModeError::IntegrityCheckFailed)
+            );
+        }
+
+        // a KW ciphertext doesn't unwrap as KWP, and vice versa
+        assert_eq!(
+            kw.unwrap_with_padding(&decode(wrapped)),
+            Err(BlockModeError::IntegrityCheckFailed)
+        );
+        let padded = kw.wrap_with_padding(&decode(key_data)).unwrap();
+        assert_eq!(
+            kw.unwrap(&padded),
+            Err(BlockModeError::IntegrityCheckFailed)
+        );
+
+        // a KWP ciphertext whose length field claims more bytes than the padding allows
+        let single = kw.wrap_with_padding(&[0x42; 8]).unwrap();
+        let mut block = kw
+            .cipher
+            .decrypt_block(GenericArray::clone_from_slice(&single));
+        block[7] = 9;
+        let forged = kw.cipher.encrypt_block(block);
+        assert_eq!(
+            kw.unwrap_with_padding(&forged),
+            Err(BlockModeError::IntegrityCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        let kw = key_wrap(KEK_128);
+
+        assert_eq!(
+            kw.wrap(&[0u8; 8]),
+            Err(BlockModeError::InvalidKeyDataLength(8))
+        );
+        assert_eq!(
+            kw.wrap(&[0u8; 20]),
+            Err(BlockModeError::InvalidKeyDataLength(20))
+        );
+        assert_eq!(
+            kw.wrap_with_padding(&[]),
+            Err(BlockModeError::InvalidKeyDataLength(0))
+        );
+        assert_eq!(
+            kw.unwrap(&[0u8; 16]),
+            Err(BlockModeError::TruncatedCiphertext { len: 16, min: 24 })
+        );
+        assert_eq!(
+            kw.unwrap_with_padding(&[0u8; 25]),
+            Err(BlockModeError::UnalignedCiphertext {
+                len: 25,
+                block_size: 8
+            })
+        );
+    }
+}
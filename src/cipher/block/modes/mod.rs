@@ -1,19 +1,31 @@
+use thiserror::Error;
+
 use super::ciphers::BlockCipher;
-use super::pads::Padder;
+use super::pads::{Padder, PaddingError};
+use crate::cipher::KeyError;
 
 mod cbc;
+mod cbc_cs;
 mod cfb;
 mod ctr;
 mod ecb;
+mod kw;
 mod ofb;
+mod streaming;
+mod xts;
 
 pub use cbc::*;
+pub use cbc_cs::*;
 pub use cfb::*;
 pub use ctr::*;
 pub use ecb::*;
+pub use kw::*;
 pub use ofb::*;
+pub use streaming::*;
+pub use xts::*;
 
-/// `BlockMode` is a trait representing a mode of operation for block ciphers.
+/// `BlockMode` is a trait representing a padded mode of operation for block
+/// ciphers.
 ///
 /// This trait is generic over three parameters:
 /// * `N` is a type implementing `ArrayLength<u8>`, specifying the block size.
@@ -25,14 +37,33 @@ pub use ofb::*;
 /// operation describes how to *repeatedly* apply a cipher's single-block
 /// operation to transform larger amounts of data than a single block.
 ///
-/// Block modes such as ECB, CBC, OFB, CFB, CTR and XTS provide *confidentiality*,
+/// Block modes such as ECB, CBC, CBC-CS, OFB, CFB, CTR and XTS provide *confidentiality*,
 /// but do not provide protection against accidental or malicious tampering
 /// of payloads, also known as *integrity*.
 ///
+/// Modes fall into four groups:
+/// * padded modes pass every block through the cipher, so plaintext is padded
+///   to a multiple of the block size, and implement `BlockMode`.
+/// * keystream modes encrypt an IV or counter to produce a keystream that is
+///   XORed with the plaintext, so they need no padding, and implement
+///   `StreamCipher` instead.
+/// * length-preserving modes (CBC-CS, XTS) pass every block through the cipher,
+///   but steal ciphertext to avoid padding, and have their own APIs.
+/// * key wrap (KW, KWP) deterministically encrypts and integrity protects
+///   key material under a key-encryption key.
+///
 /// ECB: C_i = encrypt_block(P_i, K)
 /// CBC: C_i = encrypt_block(P_i XOR C_{i-1}, K), C_0 = IV
+/// CBC-CS: CBC with the final partial block zero padded, and the padding's
+///         share of C_{n-1} dropped (stolen) from the ciphertext
 /// OFB: C_i = P_i XOR S_i, S_i = encrypt_block(S_{i-1}, K), S_0 = IV
-/// CFB: Y_i = P_i XOR S_i, S_i = encrypt_block(C_{i-1}, K), C_0 = IV
+/// CFB: C_i = P_i XOR S_i, S_i = encrypt_block(C_{i-1}, K), C_0 = IV
+/// CTR: C_i = P_i XOR encrypt_block(nonce || i, K)
+/// XTS: C_j = encrypt_block(P_j XOR T_j, K_1) XOR T_j, T_j = encrypt_block(i, K_2) * alpha^j
+///
+/// Padded modes also implement `IncrementalMode`, so messages too large to
+/// hold in memory can be processed in pieces with `Encryptor` and `Decryptor`,
+/// or through `std::io` with `EncryptWriter` and `DecryptReader`.
 ///
 /// Callers with systems that require authentication on top of secrecy
 /// should used AEAD (authenticated encryption with additional data) schemes such
@@ -44,5 +75,114 @@ where
     P: Padder<N>,
 {
     fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8>;
-    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8>;
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Result<Vec<u8>, BlockModeError>;
+}
+
+/// Errors that can occur when decrypting with a `BlockMode`.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockModeError {
+    /// Occurs when the ciphertext length is not a multiple of the block size.
+    #[error("ciphertext length {len} is not a multiple of the block size {block_size}")]
+    UnalignedCiphertext { len: usize, block_size: usize },
+
+    /// Occurs when the ciphertext is too short to hold its IV (if any) and a padded block.
+    #[error("ciphertext length {len} is shorter than the minimum {min}")]
+    TruncatedCiphertext { len: usize, min: usize },
+
+    /// Occurs when a nonce does not fill the nonce half of a counter block.
+    #[error("nonce length {actual} does not match the expected {expected}")]
+    InvalidNonceLength { expected: usize, actual: usize },
+
+    /// Occurs when the mode or its block cipher rejects a (sub)key.
+    #[error(transparent)]
+    InvalidKey(#[from] KeyError),
+
+    /// Occurs when the decrypted final block does not end in valid padding.
+    #[error(transparent)]
+    InvalidPadding(#[from] PaddingError),
+
+    /// Occurs when a data unit (an XTS sector or CBC-CS message) is shorter than one block.
+    #[error("data unit length {0} is shorter than one block")]
+    InvalidDataUnitLength(usize),
+
+    /// Occurs when a counter is empty, wider than 64 bits, or wider than the block.
+    #[error("counter length {0} is not supported")]
+    InvalidCounterLength(usize),
+
+    /// Occurs when key data is too short or, for unpadded key wrap, not a
+    /// multiple of 8 bytes.
+    #[error("key data length {0} is not supported")]
+    InvalidKeyDataLength(usize),
+
+    /// Occurs when an unwrapped key's integrity check value does not verify.
+    #[error("integrity check failed")]
+    IntegrityCheckFailed,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use generic_array::{typenum::U16, GenericArray};
+
+    use super::*;
+    use crate::cipher::block::ciphers::aes::Aes;
+
+    // NIST SP 800-38A appendix F encrypts the same plaintext under the same
+    // AES-128 key in every mode. ECB, CBC, CFB and OFB share the IV, and CTR
+    // starts its counter blocks at nonce || counter. Each mode's tests keep
+    // their own ciphertexts.
+    #[rustfmt::skip]
+    pub(crate) const SP800_38A_KEY: [u8; 16] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6,
+        0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    #[rustfmt::skip]
+    pub(crate) const SP800_38A_IV: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+        0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    ];
+
+    #[rustfmt::skip]
+    pub(crate) const SP800_38A_PLAINTEXT: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    #[rustfmt::skip]
+    pub(crate) const SP800_38A_NONCE: [u8; 8] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7];
+
+    pub(crate) const SP800_38A_COUNTER: u64 = 0xf8f9fafbfcfdfeff;
+
+    pub(crate) fn sp800_38a_aes() -> Aes {
+        Aes::new(SP800_38A_KEY.to_vec()).unwrap()
+    }
+
+    pub(crate) fn sp800_38a_cfb() -> Cfb<U16, Aes> {
+        Cfb::new(
+            sp800_38a_aes(),
+            GenericArray::clone_from_slice(&SP800_38A_IV),
+        )
+    }
+
+    pub(crate) fn sp800_38a_ctr() -> Ctr<U16, Aes> {
+        let mut ctr = Ctr::new(
+            sp800_38a_aes(),
+            SP800_38A_NONCE.to_vec(),
+            CounterLayout::BE64,
+        )
+        .unwrap();
+        ctr.seek(SP800_38A_COUNTER);
+
+        ctr
+    }
+
+    pub(crate) fn sp800_38a_ofb() -> Ofb<U16, Aes> {
+        Ofb::new(
+            sp800_38a_aes(),
+            GenericArray::clone_from_slice(&SP800_38A_IV),
+        )
+    }
 }
@@ -1,119 +1,130 @@
 use std::marker;
 
-use generic_array::{ArrayLength, GenericArray};
-use rand::RngCore;
+use generic_array::ArrayLength;
 
-use super::BlockMode;
 use crate::cipher::{
-    block::{ciphers::BlockCipher, pads::Padder},
-    stream::{VernamCipher, VernamCipherError},
+    block::{ciphers::BlockCipher, Block},
+    StreamCipher,
 };
 
-struct Ofb<N, R, C, P>
+// Ofb implements output feedback mode as defined in NIST SP 800-38A.
+// OFB turns a block cipher into a stream cipher by repeatedly encrypting the
+// IV, and XORing the resulting keystream with the plaintext.
+
+// OFB: C_i = P_i XOR S_i, S_i = encrypt_block(S_{i-1}, K), S_0 = IV
+
+// Like CTR, the keystream does not depend on the plaintext, so OFB needs no
+// padding and encryption and decryption are the same operation. Unlike CTR,
+// block i of the keystream can only be reached by computing every block
+// before it.
+
+// The IV is not part of the ciphertext. It must be unique for every message
+// under a key, since reusing it reuses the keystream.
+pub struct Ofb<N, C>
 where
     N: ArrayLength<u8>,
-    R: RngCore,
     C: BlockCipher<N>,
-    P: Padder<N>,
 {
-    rng: R,
     cipher: C,
-    padder: P,
-    _marker: marker::PhantomData<N>, // required since N ties C and P together without being used directly
+    register: Block<N>, // the current keystream block, or the IV before the first block
+    offset: usize,      // offset of the next keystream byte within register
+    _marker: marker::PhantomData<N>,
 }
 
-impl<N, R, C, P> Ofb<N, R, C, P>
+impl<N, C> Ofb<N, C>
 where
     N: ArrayLength<u8>,
-    R: RngCore,
     C: BlockCipher<N>,
-    P: Padder<N>,
 {
-    pub fn new(rng: R, cipher: C, padder: P) -> Self {
+    pub fn new(cipher: C, iv: Block<N>) -> Self {
         Ofb {
-            rng,
             cipher,
-            padder,
+            register: iv,
+            offset: N::to_usize(), // the IV itself is never used as keystream
             _marker: marker::PhantomData,
         }
     }
-}
 
-impl<N, R, C, P> BlockMode<N, C, P> for Ofb<N, R, C, P>
-where
-    N: ArrayLength<u8>,
-    R: RngCore,
-    C: BlockCipher<N>,
-    P: Padder<N>,
-{
-    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
-        let mut iv = vec![0u8; N::to_usize()];
-        self.rng.fill_bytes(iv.as_mut_slice());
-        let mut prev_output = GenericArray::clone_from_slice(iv.as_slice());
+    fn apply_keystream(&mut self, input: Vec<u8>) -> Vec<u8> {
+        let block_size = N::to_usize();
 
-        let ciphertext_blocks = self
-            .padder
-            .pad(plaintext) // padding the plaintext even though OFB is a stream cipher
+        input
             .into_iter()
-            .flat_map(|plaintext_chunk| {
-                let streamkey_chunk = self.cipher.encrypt_block(prev_output.clone());
-                prev_output = streamkey_chunk;
+            .map(|byte| {
+                if self.offset == block_size {
+                    self.register = self.cipher.encrypt_block(self.register.clone());
+                    self.offset = 0;
+                }
 
-                let ciphertext_block =
-                    VernamCipher::new(plaintext_chunk.iter().copied(), prev_output.iter().copied())
-                        .collect::<Result<Vec<u8>, VernamCipherError>>()
-                        .unwrap();
+                let output = byte ^ self.register[self.offset];
+                self.offset += 1;
 
-                ciphertext_block
+                output
             })
-            .collect();
+            .collect()
+    }
+}
 
-        ciphertext_blocks
+impl<N, C> StreamCipher for Ofb<N, C>
+where
+    N: ArrayLength<u8>,
+    C: BlockCipher<N>,
+{
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(plaintext)
     }
 
-    fn decrypt(&mut self, _ciphertext: Vec<u8>) -> Vec<u8> {
-        unimplemented!()
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(ciphertext)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use rand::SeedableRng;
-
     use super::*;
-    use crate::{
-        cipher::block::{ciphers, pads},
-        rng::MT,
-    };
+    use crate::cipher::block::modes::tests::{sp800_38a_ofb, SP800_38A_PLAINTEXT};
+
+    // NIST SP 800-38A F.4.1 OFB-AES128.Encrypt and F.4.2 OFB-AES128.Decrypt
+    #[rustfmt::skip]
+    const SP800_38A_CIPHERTEXT: [u8; 64] = [
+        0x3b, 0x3f, 0xd9, 0x2e, 0xb7, 0x2d, 0xad, 0x20, 0x33, 0x34, 0x49, 0xf8, 0xe8, 0x3c, 0xfb, 0x4a,
+        0x77, 0x89, 0x50, 0x8d, 0x16, 0x91, 0x8f, 0x03, 0xf5, 0x3c, 0x52, 0xda, 0xc5, 0x4e, 0xd8, 0x25,
+        0x97, 0x40, 0x05, 0x1e, 0x9c, 0x5f, 0xec, 0xf6, 0x43, 0x44, 0xf7, 0xa8, 0x22, 0x60, 0xed, 0xcc,
+        0x30, 0x4c, 0x65, 0x28, 0xf6, 0x59, 0xc7, 0x78, 0x66, 0xa5, 0x10, 0xd9, 0xc1, 0xd6, 0xae, 0x5e,
+    ];
+
+    #[test]
+    fn test_sp800_38a_ofb_aes128() {
+        let mut ofb = sp800_38a_ofb();
+        let encrypted = ofb.encrypt(SP800_38A_PLAINTEXT.to_vec());
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+
+        let mut ofb = sp800_38a_ofb();
+        let decrypted = ofb.decrypt(SP800_38A_CIPHERTEXT.to_vec());
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_encrypt_in_pieces() {
+        // consecutive calls continue the keystream, even when splitting blocks
+        let mut ofb = sp800_38a_ofb();
+        let mut encrypted = ofb.encrypt(SP800_38A_PLAINTEXT[..5].to_vec());
+        encrypted.extend(ofb.encrypt(SP800_38A_PLAINTEXT[5..16].to_vec()));
+        encrypted.extend(ofb.encrypt(SP800_38A_PLAINTEXT[16..37].to_vec()));
+        encrypted.extend(ofb.encrypt(SP800_38A_PLAINTEXT[37..].to_vec()));
+
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT);
+    }
 
     #[test]
-    fn test_encrypt_one_block() {
-        let seed = 1131464071u32;
-        let rng = MT::from_seed(seed.to_be_bytes());
-
-        let key = b"YELLOW SUBMARINE";
-        let cipher = ciphers::aes::Aes::new(key.to_vec());
-        let padder = pads::pkcs7::Pkcs7::new();
-
-        let mut cbc = Ofb::new(rng, cipher, padder);
-
-        let plaintext = b"ABCDEFGHIJKLMNOP";
-        let encrypted = cbc.encrypt(plaintext.to_vec());
-
-        println!("{:?}", encrypted);
-        // #[rustfmt::skip]
-        // let expected_output = [
-        //     0xf5, 0x45, 0xc0, 0x06,
-        //     0x06, 0x91, 0x26, 0xd9,
-        //     0xc0, 0xf9, 0x3f, 0xa7,
-        //     0xdd, 0x89, 0xab, 0x98,
-        //     // encrypted padding (entire block)
-        //     0x60, 0xfa, 0x36, 0x70,
-        //     0x7e, 0x45, 0xf4, 0x99,
-        //     0xdb, 0xa0, 0xf2, 0x5b,
-        //     0x92, 0x23, 0x01, 0xa5,
-        // ];
-
-        // assert_eq!(encrypted, expected_output);
+    fn test_partial_block() {
+        // no padding: a partial final block encrypts to the same length
+        let mut ofb = sp800_38a_ofb();
+        let encrypted = ofb.encrypt(SP800_38A_PLAINTEXT[..21].to_vec());
+        assert_eq!(encrypted, SP800_38A_CIPHERTEXT[..21]);
+
+        let mut ofb = sp800_38a_ofb();
+        let decrypted = ofb.decrypt(encrypted);
+        assert_eq!(decrypted, SP800_38A_PLAINTEXT[..21]);
     }
 }
@@ -0,0 +1,511 @@
+use std::{
+    io::{self, Read, Write},
+    marker, mem,
+};
+
+use generic_array::{ArrayLength, GenericArray};
+
+use super::BlockModeError;
+use crate::cipher::block::{pads::PaddingError, Block};
+
+// BlockMode::encrypt and decrypt take the whole message at once, which does
+// not scale to multi-gigabyte files. Encryptor and Decryptor instead accept the
+// message in arbitrarily sized pieces, holding back at most one block between
+// calls, and EncryptWriter and DecryptReader wrap them as std::io adapters.
+
+/// `IncrementalMode` exposes the block-at-a-time state machine of a padded
+/// block mode, which `Encryptor` and `Decryptor` drive.
+pub trait IncrementalMode<N: ArrayLength<u8>> {
+    /// Number of blocks written ahead of the ciphertext, e.g. 1 for CBC's IV.
+    fn header_blocks(&self) -> usize;
+
+    /// Starts encrypting a new message, returning its header blocks.
+    fn start_encryption(&mut self) -> Vec<Block<N>>;
+    fn encrypt_next(&mut self, block: Block<N>) -> Block<N>;
+
+    /// Starts decrypting a new message from the header blocks parsed off the
+    /// front of its ciphertext.
+    fn start_decryption(&mut self, header: Vec<Block<N>>);
+    fn decrypt_next(&mut self, block: Block<N>) -> Block<N>;
+
+    /// Pads the final 1 to block size bytes of plaintext, or the empty message.
+    fn pad_final(&self, remainder: Vec<u8>) -> Vec<Block<N>>;
+
+    /// Removes padding from the final block of plaintext.
+    fn unpad_final(&self, block: Block<N>) -> Result<Vec<u8>, PaddingError>;
+}
+
+/// `Encryptor` encrypts a message passed in pieces to `update`, followed by a
+/// call to `finalize`. The output is identical to `BlockMode::encrypt` over the
+/// whole message.
+pub struct Encryptor<N, M>
+where
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    mode: M,
+    buffer: Vec<u8>, // plaintext held back from the last update
+    started: bool,
+    _marker: marker::PhantomData<N>,
+}
+
+impl<N, M> Encryptor<N, M>
+where
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    pub fn new(mode: M) -> Self {
+        Encryptor {
+            mode,
+            buffer: Vec::with_capacity(N::to_usize()),
+            started: false,
+            _marker: marker::PhantomData,
+        }
+    }
+
+    // update returns the ciphertext of every block completed by input, except
+    // the last one.
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        let block_size = N::to_usize();
+        let mut output = self.start();
+
+        // keep between 1 and block_size bytes buffered for finalize, so that a
+        // block aligned message ends in a full final block, as padding schemes
+        // that don't add a block to aligned input (e.g. Zero) expect
+        self.buffer.extend_from_slice(input);
+        let complete = self.buffer.len().saturating_sub(1) / block_size * block_size;
+        for chunk in self.buffer[..complete].chunks_exact(block_size) {
+            let block = self
+                .mode
+                .encrypt_next(GenericArray::clone_from_slice(chunk));
+            output.extend_from_slice(&block);
+        }
+        self.buffer.drain(..complete);
+
+        output
+    }
+
+    // finalize pads and encrypts the remaining plaintext, after which the
+    // encryptor is ready for the next message.
+    pub fn finalize(&mut self) -> Vec<u8> {
+        let mut output = self.start();
+
+        let remainder = mem::take(&mut self.buffer);
+        for block in self.mode.pad_final(remainder) {
+            output.extend_from_slice(&self.mode.encrypt_next(block));
+        }
+        self.started = false;
+
+        output
+    }
+
+    pub fn into_inner(self) -> M {
+        self.mode
+    }
+
+    fn start(&mut self) -> Vec<u8> {
+        if self.started {
+            return Vec::new();
+        }
+
+        self.started = true;
+        self.mode
+            .start_encryption()
+            .into_iter()
+            .flat_map(|block| block.into_iter())
+            .collect()
+    }
+}
+
+/// `Decryptor` decrypts a message passed in pieces to `update`, followed by a
+/// call to `finalize`. The final block is held back until `finalize`, since
+/// only then is it known to contain the padding.
+pub struct Decryptor<N, M>
+where
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    mode: M,
+    buffer: Vec<u8>, // the header, or ciphertext held back from the last update
+    started: bool,
+    len: usize, // bytes of ciphertext passed to update so far
+    _marker: marker::PhantomData<N>,
+}
+
+impl<N, M> Decryptor<N, M>
+where
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    pub fn new(mode: M) -> Self {
+        Decryptor {
+            mode,
+            buffer: Vec::new(),
+            started: false,
+            len: 0,
+            _marker: marker::PhantomData,
+        }
+    }
+
+    // update returns the plaintext of every block completed by input, except
+    // the last one.
+    pub fn update(&mut self, input: &[u8]) -> Vec<u8> {
+        let block_size = N::to_usize();
+        self.len += input.len();
+        self.buffer.extend_from_slice(input);
+
+        if !self.started {
+            let header_len = self.mode.header_blocks() * block_size;
+            if self.buffer.len() < header_len {
+                return Vec::new();
+            }
+
+            let header = self
+                .buffer
+                .drain(..header_len)
+                .collect::<Vec<u8>>()
+                .chunks_exact(block_size)
+                .map(GenericArray::clone_from_slice)
+                .collect();
+            self.mode.start_decryption(header);
+            self.started = true;
+        }
+
+        // keep between 1 and block_size bytes buffered for finalize
+        let complete = self.buffer.len().saturating_sub(1) / block_size * block_size;
+        let mut output = Vec::with_capacity(complete);
+        for chunk in self.buffer[..complete].chunks_exact(block_size) {
+            let block = self
+                .mode
+                .decrypt_next(GenericArray::clone_from_slice(chunk));
+            output.extend_from_slice(&block);
+        }
+        self.buffer.drain(..complete);
+
+        output
+    }
+
+    // finalize decrypts the held back block and strips its padding, after which
+    // the decryptor is ready for the next message.
+    pub fn finalize(&mut self) -> Result<Vec<u8>, BlockModeError> {
+        let block_size = N::to_usize();
+        let min = (self.mode.header_blocks() + 1) * block_size;
+        let len = mem::take(&mut self.len);
+        let last = mem::take(&mut self.buffer);
+        self.started = false;
+
+        if len < min {
+            return Err(BlockModeError::TruncatedCiphertext { len, min });
+        }
+        if last.len() != block_size {
+            return Err(BlockModeError::UnalignedCiphertext { len, block_size });
+        }
+
+        let block = self
+            .mode
+            .decrypt_next(GenericArray::clone_from_slice(&last));
+        Ok(self.mode.unpad_final(block)?)
+    }
+
+    pub fn into_inner(self) -> M {
+        self.mode
+    }
+}
+
+/// `EncryptWriter` encrypts everything written to it into the inner writer.
+/// `finish` must be called once the plaintext is complete to write the final
+/// padded block; dropping the writer without finishing truncates the output.
+pub struct EncryptWriter<W, N, M>
+where
+    W: Write,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    inner: W,
+    encryptor: Encryptor<N, M>,
+}
+
+impl<W, N, M> EncryptWriter<W, N, M>
+where
+    W: Write,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    pub fn new(inner: W, mode: M) -> Self {
+        EncryptWriter {
+            inner,
+            encryptor: Encryptor::new(mode),
+        }
+    }
+
+    // finish writes the final padded block and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let output = self.encryptor.finalize();
+        self.inner.write_all(&output)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+impl<W, N, M> Write for EncryptWriter<W, N, M>
+where
+    W: Write,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let output = self.encryptor.update(buf);
+        self.inner.write_all(&output)?;
+
+        Ok(buf.len())
+    }
+
+    // flush can only flush complete blocks, the partial block stays buffered
+    // until more plaintext arrives or the writer is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Ciphertext is read from the inner reader in chunks of this many bytes.
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// `DecryptReader` decrypts the ciphertext read from the inner reader.
+/// Ciphertext that is truncated, unaligned or incorrectly padded surfaces as an
+/// `io::ErrorKind::InvalidData` error once the inner reader is exhausted.
+pub struct DecryptReader<R, N, M>
+where
+    R: Read,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    inner: R,
+    decryptor: Decryptor<N, M>,
+    output: Vec<u8>, // plaintext decrypted but not read yet
+    position: usize,
+    finished: bool,
+}
+
+impl<R, N, M> DecryptReader<R, N, M>
+where
+    R: Read,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    pub fn new(inner: R, mode: M) -> Self {
+        DecryptReader {
+            inner,
+            decryptor: Decryptor::new(mode),
+            output: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fill_output(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; READ_CHUNK_LEN];
+        let n = match self.inner.read(&mut chunk) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        self.output = if n == 0 {
+            self.finished = true;
+            self.decryptor
+                .finalize()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            self.decryptor.update(&chunk[..n])
+        };
+        self.position = 0;
+
+        Ok(())
+    }
+}
+
+impl<R, N, M> Read for DecryptReader<R, N, M>
+where
+    R: Read,
+    N: ArrayLength<u8>,
+    M: IncrementalMode<N>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.output.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.fill_output()?;
+        }
+
+        let n = buf.len().min(self.output.len() - self.position);
+        buf[..n].copy_from_slice(&self.output[self.position..self.position + n]);
+        self.position += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum::U16;
+    use rand::{Rng, SeedableRng};
+
+    use super::super::{BlockMode, Cbc, Ecb};
+    use super::*;
+    use crate::{
+        cipher::block::{
+            ciphers::aes::Aes, ciphers::BlockCipher, pads::pkcs7::Pkcs7, pads::zero::Zero,
+        },
+        rng::MT,
+    };
+
+    fn cbc(seed: u32) -> Cbc<U16, MT, Aes, Pkcs7> {
+        let cipher = Aes::new(b"YELLOW SUBMARINE".to_vec()).unwrap();
+        Cbc::new(MT::from_seed(seed.to_be_bytes()), cipher, Pkcs7::new())
+    }
+
+    fn ecb() -> Ecb<U16, Aes, Pkcs7> {
+        let cipher = Aes::new(b"YELLOW SUBMARINE".to_vec()).unwrap();
+        Ecb::new(cipher, Pkcs7::new())
+    }
+
+    fn ecb_zero() -> Ecb<U16, Aes, Zero> {
+        let cipher = Aes::new(b"YELLOW SUBMARINE".to_vec()).unwrap();
+        Ecb::new(cipher, Zero::new())
+    }
+
+    // split_randomly cuts input into pieces of random length, including
+    // empty pieces and pieces longer than a block.
+    fn split_randomly(rng: &mut MT, input: &[u8]) -> Vec<Vec<u8>> {
+        let mut pieces = Vec::new();
+        let mut rest = input;
+        while !rest.is_empty() {
+            let (piece, tail) = rest.split_at(rng.gen_range(0..=40).min(rest.len()));
+            pieces.push(piece.to_vec());
+            rest = tail;
+        }
+
+        pieces
+    }
+
+    #[test]
+    fn test_matches_one_shot() {
+        let seed: u32 = 7;
+        let mut rng = MT::from_seed(seed.to_be_bytes());
+
+        for len in [0, 1, 15, 16, 17, 31, 32, 100, 257] {
+            let plaintext = (0..len).map(|i| i as u8).collect::<Vec<u8>>();
+            let pieces = split_randomly(&mut rng, &plaintext);
+
+            // both CBCs draw the same IV from identically seeded rngs
+            let expected_ciphertext = cbc(1).encrypt(plaintext.clone());
+            let mut encryptor = Encryptor::new(cbc(1));
+            let mut ciphertext = Vec::new();
+            for piece in &pieces {
+                ciphertext.extend(encryptor.update(piece));
+            }
+            ciphertext.extend(encryptor.finalize());
+            assert_eq!(ciphertext, expected_ciphertext);
+
+            let mut decryptor = Decryptor::new(cbc(2));
+            let mut decrypted = Vec::new();
+            for piece in split_randomly(&mut rng, &ciphertext) {
+                decrypted.extend(decryptor.update(&piece));
+            }
+            decrypted.extend(decryptor.finalize().unwrap());
+            assert_eq!(decrypted, plaintext);
+
+            let expected_ciphertext = ecb().encrypt(plaintext.clone());
+            let mut encryptor = Encryptor::new(ecb());
+            let mut ciphertext = Vec::new();
+            for piece in &pieces {
+                ciphertext.extend(encryptor.update(piece));
+            }
+            ciphertext.extend(encryptor.finalize());
+            assert_eq!(ciphertext, expected_ciphertext);
+        }
+    }
+
+    #[test]
+    fn test_zero_padding_matches_one_shot() {
+        // Zero only adds a block to empty input, so a block aligned message
+        // must not gain an extra block when streamed
+        for len in [0, 1, 16, 32, 33] {
+            let plaintext = vec![b'A'; len];
+
+            let mut encryptor = Encryptor::new(ecb_zero());
+            let mut ciphertext = encryptor.update(&plaintext[..len / 2]);
+            ciphertext.extend(encryptor.update(&plaintext[len / 2..]));
+            ciphertext.extend(encryptor.finalize());
+            assert_eq!(ciphertext, ecb_zero().encrypt(plaintext), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_reuse_after_finalize() {
+        let mut encryptor = Encryptor::new(cbc(1));
+        let mut first = encryptor.update(b"first message");
+        first.extend(encryptor.finalize());
+        let mut second = encryptor.update(b"first message");
+        second.extend(encryptor.finalize());
+
+        // each message gets a fresh IV
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+
+        let mut decryptor = Decryptor::new(cbc(2));
+        for ciphertext in [first, second] {
+            let mut decrypted = decryptor.update(&ciphertext);
+            decrypted.extend(decryptor.finalize().unwrap());
+            assert_eq!(decrypted, b"first message");
+        }
+    }
+
+    #[test]
+    fn test_decryptor_errors() {
+        let mut decryptor = Decryptor::new(cbc(1));
+        decryptor.update(&[0u8; 20]);
+        assert_eq!(
+            decryptor.finalize(),
+            Err(BlockModeError::TruncatedCiphertext { len: 20, min: 32 })
+        );
+
+        decryptor.update(&[0u8; 40]);
+        assert_eq!(
+            decryptor.finalize(),
+            Err(BlockModeError::UnalignedCiphertext {
+                len: 40,
+                block_size: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_io_round_trip() {
+        let plaintext = (0..100_000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+
+        let mut writer = EncryptWriter::new(Vec::new(), cbc(1));
+        io::copy(&mut plaintext.as_slice(), &mut writer).unwrap();
+        let ciphertext = writer.finish().unwrap();
+        assert_eq!(ciphertext, cbc(1).encrypt(plaintext.clone()));
+
+        let mut reader = DecryptReader::new(ciphertext.as_slice(), cbc(2));
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_reader_invalid_data() {
+        let mut reader = DecryptReader::new([0u8; 33].as_slice(), ecb());
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,7 +1,7 @@
 use generic_array::{ArrayLength, GenericArray};
 
 use super::super::Block;
-use super::Padder;
+use super::{strip, Padder, PaddingError};
 
 // A Padder that implements X9.23 padding defined by ANSI X9.23 (withdrawn CBC enhancement)
 // see: https://www.ibm.com/docs/en/linux-on-systems?topic=processes-ansi-x923-cipher-block-chaining
@@ -11,8 +11,18 @@ use super::Padder;
 // differs insofar as the remaining padding bytes are random (usually 0x00).
 // e.g. byte stream with block size 8 bytes (64 bits)
 // ... | DD DD DD DD DD DD DD DD | DD DD DD DD 00 00 00 04 |
+
+// pad always fills with zeros, and unpad requires them, so the only valid
+// padding for a given input is the one pad produces.
+#[derive(Default)]
 pub struct AnsiX923 {}
 
+impl AnsiX923 {
+    pub fn new() -> Self {
+        AnsiX923 {}
+    }
+}
+
 impl<N: ArrayLength<u8>> Padder<N> for AnsiX923 {
     fn pad(&self, plaintext: Vec<u8>) -> Vec<Block<N>> {
         let mut byte_stream = plaintext;
@@ -37,16 +47,77 @@ impl<N: ArrayLength<u8>> Padder<N> for AnsiX923 {
             .collect()
     }
 
-    fn unpad(&self, ciphertext: Vec<Block<N>>) -> Vec<u8> {
-        let last_block = ciphertext[ciphertext.len() - 1];
-        let last_byte = last_block[last_block.len() - 1];
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError> {
+        let block_size = N::to_usize();
+        let last_block = input.last().ok_or(PaddingError::InvalidPadding)?;
+
+        let padding_len = last_block[block_size - 1] as usize;
+        if padding_len == 0
+            || padding_len > block_size
+            || last_block[block_size - padding_len..block_size - 1]
+                .iter()
+                .any(|&byte| byte != 0x00)
+        {
+            return Err(PaddingError::InvalidPadding);
+        }
+
+        Ok(strip(input, padding_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum;
+
+    use super::*;
+
+    #[test]
+    fn test_ansix923_pad_partial() {
+        let padder = AnsiX923::new();
+        let padded: Vec<GenericArray<u8, typenum::U8>> = padder.pad(vec![0xdd; 12]);
+
+        assert_eq!(padded.len(), 2);
+        assert_eq!(padded[1].as_slice(), &[0xdd, 0xdd, 0xdd, 0xdd, 0, 0, 0, 4]);
+    }
 
-        let byte_stream = ciphertext
-            .into_iter()
-            .flat_map(|b| b.as_slice())
-            .take(ciphertext.len() - last_byte as usize)
-            .cloned(); // TODO: is there a way to get [T] instead of [&T] from GenericArray<u8, N>
+    #[test]
+    fn test_ansix923_round_trip() {
+        let padder = AnsiX923::new();
+        for len in 0..=32 {
+            let plaintext = (0..len as u8).collect::<Vec<u8>>();
+            let padded: Vec<GenericArray<u8, typenum::U16>> = padder.pad(plaintext.clone());
+
+            assert_eq!(padded.len(), len / 16 + 1);
+            assert_eq!(padder.unpad(padded), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_ansix923_unpad_invalid() {
+        let padder = AnsiX923::new();
+        let unpad = |block: &[u8]| {
+            Padder::<typenum::U8>::unpad(&padder, vec![GenericArray::clone_from_slice(block)])
+        };
 
-        byte_stream.collect()
+        // zero length
+        assert_eq!(
+            unpad(&[0xdd, 0, 0, 0, 0, 0, 0, 0]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // padding longer than the block
+        assert_eq!(
+            unpad(&[0, 0, 0, 0, 0, 0, 0, 9]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // non-zero fill
+        assert_eq!(
+            unpad(&[0xdd, 0xdd, 0xdd, 0xdd, 0, 1, 0, 4]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // no blocks at all
+        assert_eq!(
+            Padder::<typenum::U8>::unpad(&padder, Vec::new()),
+            Err(PaddingError::InvalidPadding)
+        );
     }
 }
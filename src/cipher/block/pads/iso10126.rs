@@ -0,0 +1,126 @@
+use std::cell::RefCell;
+
+use generic_array::{ArrayLength, GenericArray};
+use rand::RngCore;
+
+use super::super::Block;
+use super::{strip, Padder, PaddingError};
+
+// A Padder that implements the padding defined in ISO 10126 (withdrawn in 2007)
+// see: https://www.w3.org/TR/xmlenc-core1/#sec-Padding
+
+// Like ANSI X9.23, the last byte encodes the length of the padding, but the
+// remaining padding bytes are random rather than zero.
+// e.g. byte stream with block size 8 bytes (64 bits)
+// ... | DD DD DD DD DD DD DD DD | DD DD DD DD 81 A6 23 04 |
+
+// The random bytes can't be checked, so unpad only validates the length byte.
+// Padder takes &self, so the rng is kept in a RefCell to draw the fill.
+pub struct Iso10126<R: RngCore> {
+    rng: RefCell<R>,
+}
+
+impl<R: RngCore> Iso10126<R> {
+    pub fn new(rng: R) -> Self {
+        Iso10126 {
+            rng: RefCell::new(rng),
+        }
+    }
+}
+
+impl<N: ArrayLength<u8>, R: RngCore> Padder<N> for Iso10126<R> {
+    fn pad(&self, plaintext: Vec<u8>) -> Vec<Block<N>> {
+        let mut byte_stream = plaintext;
+        let block_size = N::to_usize();
+        let remainder = byte_stream.len() % block_size;
+
+        let padding = if remainder == 0 {
+            block_size
+        } else {
+            block_size - remainder
+        };
+
+        let mut fill = vec![0u8; padding - 1];
+        self.rng.borrow_mut().fill_bytes(&mut fill);
+        byte_stream.extend(fill);
+        byte_stream.push(padding as u8); // TODO: constrain max block size is 256
+        byte_stream
+            .chunks_exact(block_size)
+            .map(|chunk| GenericArray::clone_from_slice(chunk))
+            .collect()
+    }
+
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError> {
+        let block_size = N::to_usize();
+        let last_block = input.last().ok_or(PaddingError::InvalidPadding)?;
+
+        let padding_len = last_block[block_size - 1] as usize;
+        if padding_len == 0 || padding_len > block_size {
+            return Err(PaddingError::InvalidPadding);
+        }
+
+        Ok(strip(input, padding_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::rng::MT;
+
+    fn padder() -> Iso10126<MT> {
+        let seed: u32 = 42;
+        Iso10126::new(MT::from_seed(seed.to_be_bytes()))
+    }
+
+    #[test]
+    fn test_iso10126_pad_random() {
+        let padder = padder();
+        let first: Vec<GenericArray<u8, typenum::U16>> = padder.pad(vec![0u8; 4]);
+        let second: Vec<GenericArray<u8, typenum::U16>> = padder.pad(vec![0u8; 4]);
+
+        assert_eq!(first[0][15], 12);
+        assert_eq!(second[0][15], 12);
+        // the fill is drawn afresh for every call
+        assert_ne!(first[0][4..15], second[0][4..15]);
+    }
+
+    #[test]
+    fn test_iso10126_round_trip() {
+        let padder = padder();
+        for len in 0..=32 {
+            let plaintext = (0..len as u8).collect::<Vec<u8>>();
+            let padded: Vec<GenericArray<u8, typenum::U16>> = padder.pad(plaintext.clone());
+
+            assert_eq!(padded.len(), len / 16 + 1);
+            assert_eq!(padder.unpad(padded), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_iso10126_unpad_invalid() {
+        let padder = padder();
+        let unpad = |block: &[u8]| {
+            Padder::<typenum::U8>::unpad(&padder, vec![GenericArray::clone_from_slice(block)])
+        };
+
+        // zero length
+        assert_eq!(
+            unpad(&[0xdd, 1, 2, 3, 4, 5, 6, 0]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // padding longer than the block
+        assert_eq!(
+            unpad(&[0xdd, 1, 2, 3, 4, 5, 6, 9]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // no blocks at all
+        assert_eq!(
+            Padder::<typenum::U8>::unpad(&padder, Vec::new()),
+            Err(PaddingError::InvalidPadding)
+        );
+    }
+}
@@ -1,9 +1,26 @@
 use generic_array::{ArrayLength, GenericArray};
 
 use super::super::Block;
-use super::Padder;
+use super::{strip, Padder, PaddingError};
 
-struct Iso7816 {}
+// A Padder that implements the padding defined in ISO/IEC 7816-4 (smart cards),
+// which is the same as ISO/IEC 9797-1 padding method 2.
+
+// The input is always padded with a single mandatory 0x80 byte, followed by as
+// many 0x00 bytes as are needed to reach a multiple of the block size.
+// e.g. byte stream with block size 8 bytes (64 bits)
+// ... | DD DD DD DD DD DD DD DD | DD DD DD DD 80 00 00 00 |
+
+// Since the delimiter is not a zero byte, unpad finds it by skipping the
+// trailing zeros of the last block.
+#[derive(Default)]
+pub struct Iso7816 {}
+
+impl Iso7816 {
+    pub fn new() -> Self {
+        Iso7816 {}
+    }
+}
 
 impl<N: ArrayLength<u8>> Padder<N> for Iso7816 {
     fn pad(&self, plaintext: Vec<u8>) -> Vec<Block<N>> {
@@ -18,7 +35,7 @@ impl<N: ArrayLength<u8>> Padder<N> for Iso7816 {
         };
 
         byte_stream.push(0x80);
-        byte_stream.resize(byte_stream.len() + padding - 1, 0x00 as u8);
+        byte_stream.resize(byte_stream.len() + padding - 1, 0x00);
         byte_stream
             .chunks_exact(block_size)
             .map(|c| {
@@ -29,13 +46,72 @@ impl<N: ArrayLength<u8>> Padder<N> for Iso7816 {
             .collect()
     }
 
-    fn unpad(&self, ciphertext: Vec<Block<N>>) -> Vec<u8> {
-        let byte_stream = ciphertext
-            .into_iter()
-            .flat_map(|b| b.as_slice())
-            .take_while(|&byte| *byte == 0x00)
-            .take(1); // the 0x80 delimeter padding byte
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError> {
+        let block_size = N::to_usize();
+        let last_block = input.last().ok_or(PaddingError::InvalidPadding)?;
+
+        // the delimiter is the last non-zero byte, and it has to be in the last block
+        let delimiter = last_block
+            .iter()
+            .rposition(|&byte| byte != 0x00)
+            .ok_or(PaddingError::InvalidPadding)?;
+        if last_block[delimiter] != 0x80 {
+            return Err(PaddingError::InvalidPadding);
+        }
+
+        Ok(strip(input, block_size - delimiter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum;
+
+    use super::*;
+
+    #[test]
+    fn test_iso7816_pad_partial() {
+        let padder = Iso7816::new();
+        let padded: Vec<GenericArray<u8, typenum::U8>> = padder.pad(vec![0xdd; 12]);
+
+        assert_eq!(padded.len(), 2);
+        assert_eq!(
+            padded[1].as_slice(),
+            &[0xdd, 0xdd, 0xdd, 0xdd, 0x80, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_iso7816_round_trip() {
+        let padder = Iso7816::new();
+        for len in 0..=32 {
+            // trailing zeros and 0x80 bytes in the data must survive
+            let plaintext = (0..len).map(|i| [0x00, 0x80][i % 2]).collect::<Vec<u8>>();
+            let padded: Vec<GenericArray<u8, typenum::U16>> = padder.pad(plaintext.clone());
+
+            assert_eq!(padded.len(), len / 16 + 1);
+            assert_eq!(padder.unpad(padded), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_iso7816_unpad_invalid() {
+        let padder = Iso7816::new();
+        let unpad = |block: &[u8]| {
+            Padder::<typenum::U8>::unpad(&padder, vec![GenericArray::clone_from_slice(block)])
+        };
 
-        todo!()
+        // no delimiter
+        assert_eq!(unpad(&[0u8; 8]), Err(PaddingError::InvalidPadding));
+        // wrong delimiter
+        assert_eq!(
+            unpad(&[0xdd, 0xdd, 0xdd, 0xdd, 0x01, 0, 0, 0]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // no blocks at all
+        assert_eq!(
+            Padder::<typenum::U8>::unpad(&padder, Vec::new()),
+            Err(PaddingError::InvalidPadding)
+        );
     }
 }
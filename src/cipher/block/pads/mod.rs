@@ -1,11 +1,40 @@
 use super::Block;
 use generic_array::ArrayLength;
+use thiserror::Error;
 
-// pub mod ansix923;
-// pub mod iso7816;
+pub mod ansix923;
+pub mod iso10126;
+pub mod iso7816;
 pub mod pkcs7;
+pub mod zero;
 
 pub trait Padder<N: ArrayLength<u8>> {
     fn pad(&self, input: Vec<u8>) -> Vec<Block<N>>;
-    fn unpad(&self, input: Vec<Block<N>>) -> Vec<u8>;
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError>;
+}
+
+/// Errors that can occur when removing padding.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PaddingError {
+    /// Occurs when the final block does not end in padding of the expected form,
+    /// or there is no final block at all.
+    ///
+    /// Revealing this error for CBC ciphertexts that an attacker chose (e.g. as
+    /// a distinct network response) is a padding oracle, which is enough to
+    /// decrypt any ciphertext (see cryptopals challenge 17).
+    #[error("invalid padding")]
+    InvalidPadding,
+}
+
+// strip flattens the blocks and drops the final padding_len bytes, which the
+// caller has already validated.
+fn strip<N: ArrayLength<u8>>(input: Vec<Block<N>>, padding_len: usize) -> Vec<u8> {
+    let len = input.len() * N::to_usize() - padding_len;
+
+    input
+        .into_iter()
+        .flat_map(|block| block.into_iter())
+        .take(len)
+        .collect()
 }
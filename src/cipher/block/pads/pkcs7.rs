@@ -1,7 +1,7 @@
 use generic_array::{ArrayLength, GenericArray};
 
 use super::super::Block;
-use super::Padder;
+use super::{strip, Padder, PaddingError};
 
 // A Padder that implements pre-IETF CMS PKCS7 padding defined by RSA (the company)
 // see: https://datatracker.ietf.org/doc/html/rfc2315#section-10.3
@@ -50,18 +50,24 @@ impl<N: ArrayLength<u8>> Padder<N> for Pkcs7 {
             .collect()
     }
 
-    fn unpad(&self, ciphertext: Vec<Block<N>>) -> Vec<u8> {
-        let ciphertext_len = ciphertext.len();
-        let last_block = &ciphertext[ciphertext_len - 1];
-        let last_byte = last_block[last_block.len() - 1];
-        // println!("moose: {ciphertext_len}, {last_byte}");
-
-        let byte_stream = ciphertext
-            .into_iter()
-            .flat_map(|block| block.as_slice().to_vec())
-            .take(ciphertext_len * N::to_usize() - last_byte as usize);
-
-        byte_stream.collect()
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError> {
+        let block_size = N::to_usize();
+        let last_block = input.last().ok_or(PaddingError::InvalidPadding)?;
+        let padding = last_block[block_size - 1];
+
+        // a padding byte of 0 or larger than the block size can't have been
+        // produced by pad, and neither can padding bytes that differ
+        let padding_len = padding as usize;
+        if padding_len == 0
+            || padding_len > block_size
+            || last_block[block_size - padding_len..]
+                .iter()
+                .any(|&byte| byte != padding)
+        {
+            return Err(PaddingError::InvalidPadding);
+        }
+
+        Ok(strip(input, padding_len))
     }
 }
 
@@ -105,7 +111,7 @@ mod tests {
             GenericArray::clone_from_slice(&[16u8; 16]),
         ];
         let padder = Pkcs7 {};
-        let unpadded = padder.unpad(ciphertext);
+        let unpadded = padder.unpad(ciphertext).unwrap();
 
         // should remove the entire last block of padding, leaving just the original data
         assert_eq!(unpadded, vec![0u8; 16]);
@@ -117,9 +123,44 @@ mod tests {
             GenericArray::clone_from_slice(&[0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 6, 6, 6, 6, 6]);
         let ciphertext = vec![block];
         let padder = Pkcs7 {};
-        let unpadded = padder.unpad(ciphertext);
+        let unpadded = padder.unpad(ciphertext).unwrap();
 
         // should remove the last 6 bytes of padding, leaving just the original data.
         assert_eq!(unpadded, vec![0u8; 10]);
     }
+
+    #[test]
+    fn test_pkcs7_round_trip() {
+        let padder = Pkcs7::new();
+        for len in 0..=32 {
+            let plaintext = (0..len as u8).collect::<Vec<u8>>();
+            let padded: Vec<GenericArray<u8, typenum::U16>> = padder.pad(plaintext.clone());
+
+            assert_eq!(padded.len(), len / 16 + 1);
+            assert_eq!(padder.unpad(padded), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_pkcs7_unpad_invalid() {
+        let padder = Pkcs7::new();
+        let unpad = |block: &[u8]| {
+            Padder::<typenum::U16>::unpad(&padder, vec![GenericArray::clone_from_slice(block)])
+        };
+
+        // zero padding byte
+        assert_eq!(unpad(&[0u8; 16]), Err(PaddingError::InvalidPadding));
+        // padding longer than the block
+        assert_eq!(unpad(&[17u8; 16]), Err(PaddingError::InvalidPadding));
+        // inconsistent padding bytes
+        assert_eq!(
+            unpad(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 2, 3]),
+            Err(PaddingError::InvalidPadding)
+        );
+        // no blocks at all
+        assert_eq!(
+            Padder::<typenum::U16>::unpad(&padder, Vec::new()),
+            Err(PaddingError::InvalidPadding)
+        );
+    }
 }
@@ -0,0 +1,97 @@
+use generic_array::{ArrayLength, GenericArray};
+
+use super::super::Block;
+use super::{strip, Padder, PaddingError};
+
+// A Padder that implements zero padding, ISO/IEC 9797-1 padding method 1.
+
+// The input is padded with as few 0x00 bytes as are needed to reach a multiple
+// of the block size. Input that is already a multiple of the block size is left
+// alone, except for empty input which becomes a single block of zeros.
+// e.g. byte stream with block size 8 bytes (64 bits)
+// ... | DD DD DD DD DD DD DD DD | DD DD DD DD 00 00 00 00 |
+
+// The padding is ambiguous: unpad strips every trailing zero of the last block,
+// so it only round-trips data that doesn't end in 0x00 (e.g. text). It exists
+// for interoperability with systems that use it, and for MACs where only pad
+// is needed.
+#[derive(Default)]
+pub struct Zero {}
+
+impl Zero {
+    pub fn new() -> Self {
+        Zero {}
+    }
+}
+
+impl<N: ArrayLength<u8>> Padder<N> for Zero {
+    fn pad(&self, plaintext: Vec<u8>) -> Vec<Block<N>> {
+        let mut byte_stream = plaintext;
+        let block_size = N::to_usize();
+
+        let blocks = ((byte_stream.len() + block_size - 1) / block_size).max(1);
+        byte_stream.resize(blocks * block_size, 0x00);
+        byte_stream
+            .chunks_exact(block_size)
+            .map(|chunk| GenericArray::clone_from_slice(chunk))
+            .collect()
+    }
+
+    fn unpad(&self, input: Vec<Block<N>>) -> Result<Vec<u8>, PaddingError> {
+        let last_block = input.last().ok_or(PaddingError::InvalidPadding)?;
+
+        let padding_len = last_block
+            .iter()
+            .rev()
+            .take_while(|&&byte| byte == 0x00)
+            .count();
+
+        Ok(strip(input, padding_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use generic_array::typenum;
+
+    use super::*;
+
+    #[test]
+    fn test_zero_pad() {
+        let padder = Zero::new();
+
+        let padded: Vec<GenericArray<u8, typenum::U8>> = padder.pad(vec![0xdd; 12]);
+        assert_eq!(padded.len(), 2);
+        assert_eq!(padded[1].as_slice(), &[0xdd, 0xdd, 0xdd, 0xdd, 0, 0, 0, 0]);
+
+        // aligned input gets no padding
+        let padded: Vec<GenericArray<u8, typenum::U8>> = padder.pad(vec![0xdd; 16]);
+        assert_eq!(padded.len(), 2);
+
+        // empty input still produces a block
+        let padded: Vec<GenericArray<u8, typenum::U8>> = padder.pad(Vec::new());
+        assert_eq!(padded, vec![GenericArray::default()]);
+    }
+
+    #[test]
+    fn test_zero_round_trip() {
+        let padder = Zero::new();
+        for len in 0..=32 {
+            let plaintext = (1..=len as u8).collect::<Vec<u8>>();
+            let padded: Vec<GenericArray<u8, typenum::U16>> = padder.pad(plaintext.clone());
+
+            assert_eq!(padded.len(), ((len + 15) / 16).max(1));
+            assert_eq!(padder.unpad(padded), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_zero_unpad_invalid() {
+        let padder = Zero::new();
+
+        assert_eq!(
+            Padder::<typenum::U8>::unpad(&padder, Vec::new()),
+            Err(PaddingError::InvalidPadding)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use super::{FpeError, MAX_RADIX};
+
+// Alphabet maps the characters of an alphabet to numerals by position, so
+// "0123456789abcdefghijklmnopqrstuvwxyz" maps 'a' to 10 in radix 36. An
+// alphabet's radix is its length.
+#[derive(Clone, Debug)]
+pub struct Alphabet {
+    characters: Vec<char>,
+    numerals: HashMap<char, u16>,
+}
+
+impl Alphabet {
+    pub fn new(characters: &str) -> Result<Self, FpeError> {
+        let characters = characters.chars().collect::<Vec<char>>();
+        if characters.len() < 2 || characters.len() > MAX_RADIX as usize {
+            return Err(FpeError::InvalidRadix(characters.len() as u32));
+        }
+
+        let mut numerals = HashMap::with_capacity(characters.len());
+        for (numeral, &character) in characters.iter().enumerate() {
+            if numerals.insert(character, numeral as u16).is_some() {
+                return Err(FpeError::DuplicateCharacter(character));
+            }
+        }
+
+        Ok(Alphabet {
+            characters,
+            numerals,
+        })
+    }
+
+    pub fn radix(&self) -> u32 {
+        self.characters.len() as u32
+    }
+
+    // to_numerals converts a string to its numeral string.
+    pub fn to_numerals(&self, input: &str) -> Result<Vec<u16>, FpeError> {
+        input
+            .chars()
+            .map(|character| {
+                self.numerals
+                    .get(&character)
+                    .copied()
+                    .ok_or(FpeError::InvalidCharacter(character))
+            })
+            .collect()
+    }
+
+    // to_string converts a numeral string back to characters. Numerals must
+    // be less than the radix.
+    pub fn to_string(&self, numerals: &[u16]) -> String {
+        numerals
+            .iter()
+            .map(|&numeral| self.characters[numeral as usize])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let alphabet = Alphabet::new("0123456789abcdefghijklmnopqrstuvwxyz").unwrap();
+        assert_eq!(alphabet.radix(), 36);
+
+        let numerals = alphabet.to_numerals("09az").unwrap();
+        assert_eq!(numerals, [0, 9, 10, 35]);
+        assert_eq!(alphabet.to_string(&numerals), "09az");
+    }
+
+    #[test]
+    fn test_invalid_alphabets() {
+        assert!(matches!(Alphabet::new("0"), Err(FpeError::InvalidRadix(1))));
+        assert!(matches!(
+            Alphabet::new("0120"),
+            Err(FpeError::DuplicateCharacter('0'))
+        ));
+
+        let alphabet = Alphabet::new("01").unwrap();
+        assert_eq!(
+            alphabet.to_numerals("012"),
+            Err(FpeError::InvalidCharacter('2'))
+        );
+    }
+}
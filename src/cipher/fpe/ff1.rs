@@ -0,0 +1,231 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::{add_mod, byte_len, num, str_radix, sub_mod, validate_input, validate_radix, FpeError};
+use crate::cipher::block::ciphers::{aes::Aes, BlockCipher};
+
+// Ff1 implements the FF1 format-preserving encryption scheme from NIST
+// SP 800-38G, a ten round Feistel network over numeral strings.
+
+// The input X of n numerals is split into A (the first u = floor(n / 2)) and
+// B (the remaining v = n - u). Each round computes a pseudorandom y from B,
+// the round number and the tweak with an AES CBC-MAC, extended with AES in
+// counter mode if the round needs more than 16 bytes, and then
+// C = (NUM_radix(A) + y) mod radix^m
+// A, B = B, STR^m_radix(C)
+// where m alternates between u and v.
+
+// Tweaks may be any length, including empty.
+pub struct Ff1 {
+    cipher: Aes,
+    radix: u32,
+    min_len: usize,
+}
+
+const ROUNDS: u8 = 10;
+const MAX_LEN: usize = u32::MAX as usize;
+
+impl Ff1 {
+    pub fn new(key: Vec<u8>, radix: u32) -> Result<Self, FpeError> {
+        let min_len = validate_radix(radix)?;
+
+        Ok(Ff1 {
+            cipher: Aes::new(key)?,
+            radix,
+            min_len,
+        })
+    }
+
+    pub fn encrypt(&self, tweak: &[u8], plaintext: &[u16]) -> Result<Vec<u16>, FpeError> {
+        validate_input(plaintext, self.radix, self.min_len, MAX_LEN)?;
+        let n = plaintext.len();
+        let u = n / 2;
+
+        let mut a = plaintext[..u].to_vec();
+        let mut b = plaintext[u..].to_vec();
+        let p = self.p(n, u, tweak.len());
+        for i in 0..ROUNDS {
+            let m = if i % 2 == 0 { u } else { n - u };
+            let y = self.round(&p, tweak, i, &b, n - u, m);
+
+            let c = add_mod(&a, &y, self.radix);
+            a = b;
+            b = c;
+        }
+
+        a.extend(b);
+        Ok(a)
+    }
+
+    pub fn decrypt(&self, tweak: &[u8], ciphertext: &[u16]) -> Result<Vec<u16>, FpeError> {
+        validate_input(ciphertext, self.radix, self.min_len, MAX_LEN)?;
+        let n = ciphertext.len();
+        let u = n / 2;
+
+        let mut a = ciphertext[..u].to_vec();
+        let mut b = ciphertext[u..].to_vec();
+        let p = self.p(n, u, tweak.len());
+        for i in (0..ROUNDS).rev() {
+            let m = if i % 2 == 0 { u } else { n - u };
+            let y = self.round(&p, tweak, i, &a, n - u, m);
+
+            let c = sub_mod(&b, &y, self.radix);
+            b = a;
+            a = c;
+        }
+
+        a.extend(b);
+        Ok(a)
+    }
+
+    // p returns the block that starts the CBC-MAC in every round.
+    // P = [1]^1 || [2]^1 || [1]^1 || [radix]^3 || [10]^1 || [u mod 256]^1 || [n]^4 || [t]^4
+    fn p(&self, n: usize, u: usize, t: usize) -> [u8; 16] {
+        let mut p = [0u8; 16];
+        p[..3].copy_from_slice(&[1, 2, 1]);
+        p[3..6].copy_from_slice(&self.radix.to_be_bytes()[1..]);
+        p[6] = ROUNDS;
+        p[7] = u as u8;
+        p[8..12].copy_from_slice(&(n as u32).to_be_bytes());
+        p[12..].copy_from_slice(&(t as u32).to_be_bytes());
+
+        p
+    }
+
+    // round returns y mod radix^m, as m numerals, for round i with the Feistel
+    // half x, where v is the length of the longer half.
+    fn round(&self, p: &[u8; 16], tweak: &[u8], i: u8, x: &[u16], v: usize, m: usize) -> Vec<u16> {
+        let b = byte_len(self.radix, v);
+        let d = 4 * ((b + 3) / 4) + 4;
+
+        // Q = T || [0]^((-t-b-1) mod 16) || [i]^1 || [NUM_radix(x)]^b
+        let mut q = tweak.to_vec();
+        q.resize(q.len() + (16 - (tweak.len() + b + 1) % 16) % 16, 0);
+        q.push(i);
+        q.extend(num(x, self.radix, b));
+
+        // R = PRF(P || Q), the last block of the CBC-MAC with a zero IV
+        let r = q.chunks_exact(16).fold(self.encipher(*p), |y, block| {
+            let mut input = [0u8; 16];
+            for (j, byte) in input.iter_mut().enumerate() {
+                *byte = y[j] ^ block[j];
+            }
+            self.encipher(input)
+        });
+
+        // S = R || CIPH(R XOR [1]^16) || CIPH(R XOR [2]^16) ..., truncated to d bytes
+        let mut s = r.to_vec();
+        for j in 1..(d as u128 + 15) / 16 {
+            let block = (u128::from_be_bytes(r) ^ j).to_be_bytes();
+            s.extend(self.encipher(block));
+        }
+        s.truncate(d);
+
+        str_radix(&s, self.radix, m)
+    }
+
+    fn encipher(&self, block: [u8; 16]) -> [u8; 16] {
+        let block: GenericArray<u8, U16> = block.into();
+        self.cipher.encrypt_block(block).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cipher::fpe::Alphabet, encode::hex::decode};
+
+    const DIGITS: &str = "0123456789";
+    const ALPHANUMERIC: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+    const KEY_128: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const KEY_192: &str = "2b7e151628aed2a6abf7158809cf4f3cef4359d8d580aa4f";
+    const KEY_256: &str = "2b7e151628aed2a6abf7158809cf4f3cef4359d8d580aa4f7f036d6f04fc6a94";
+
+    // NIST SP 800-38G FF1 samples
+    struct TestCase {
+        key: &'static str,
+        alphabet: &'static str,
+        tweak: &'static str,
+        plaintext: &'static str,
+        ciphertext: &'static str,
+    }
+
+    #[rustfmt::skip]
+    const TEST_CASES: [TestCase; 9] = [
+        TestCase { key: KEY_128, alphabet: DIGITS, tweak: "", plaintext: "0123456789", ciphertext: "2433477484" },
+        TestCase { key: KEY_128, alphabet: DIGITS, tweak: "39383736353433323130", plaintext: "0123456789", ciphertext: "6124200773" },
+        TestCase { key: KEY_128, alphabet: ALPHANUMERIC, tweak: "3737373770717273373737", plaintext: "0123456789abcdefghi", ciphertext: "a9tv40mll9kdu509eum" },
+        TestCase { key: KEY_192, alphabet: DIGITS, tweak: "", plaintext: "0123456789", ciphertext: "2830668132" },
+        TestCase { key: KEY_192, alphabet: DIGITS, tweak: "39383736353433323130", plaintext: "0123456789", ciphertext: "2496655549" },
+        TestCase { key: KEY_192, alphabet: ALPHANUMERIC, tweak: "3737373770717273373737", plaintext: "0123456789abcdefghi", ciphertext: "xbj3kv35jrawxv32ysr" },
+        TestCase { key: KEY_256, alphabet: DIGITS, tweak: "", plaintext: "0123456789", ciphertext: "6657667009" },
+        TestCase { key: KEY_256, alphabet: DIGITS, tweak: "39383736353433323130", plaintext: "0123456789", ciphertext: "1001623463" },
+        TestCase { key: KEY_256, alphabet: ALPHANUMERIC, tweak: "3737373770717273373737", plaintext: "0123456789abcdefghi", ciphertext: "xs8a0azh2avyalyzuwd" },
+    ];
+
+    #[test]
+    fn test_nist_samples() {
+        for test_case in TEST_CASES.iter() {
+            let alphabet = Alphabet::new(test_case.alphabet).unwrap();
+            let ff1 = Ff1::new(decode(test_case.key), alphabet.radix()).unwrap();
+            let tweak = decode(test_case.tweak);
+
+            let plaintext = alphabet.to_numerals(test_case.plaintext).unwrap();
+            let encrypted = ff1.encrypt(&tweak, &plaintext).unwrap();
+            assert_eq!(alphabet.to_string(&encrypted), test_case.ciphertext);
+
+            let decrypted = ff1.decrypt(&tweak, &encrypted).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // long inputs and large radixes need more than one block of S per round
+        for (radix, len) in [(2, 20), (2, 255), (10, 101), (1 << 16, 2), (1 << 16, 40)] {
+            let ff1 = Ff1::new(decode(KEY_128), radix).unwrap();
+            let plaintext = (0..len)
+                .map(|i| ((i * 7919) % radix as usize) as u16)
+                .collect::<Vec<u16>>();
+
+            let encrypted = ff1.encrypt(b"tweak", &plaintext).unwrap();
+            assert_eq!(encrypted.len(), len);
+            assert!(encrypted.iter().all(|&numeral| (numeral as u32) < radix));
+            assert_ne!(encrypted, plaintext);
+
+            assert_eq!(ff1.decrypt(b"tweak", &encrypted).unwrap(), plaintext);
+            assert_ne!(ff1.decrypt(b"other", &encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let ff1 = Ff1::new(decode(KEY_128), 10).unwrap();
+
+        // a million inputs need at least six decimal digits
+        assert_eq!(
+            ff1.encrypt(&[], &[0; 5]),
+            Err(FpeError::InvalidInputLength {
+                len: 5,
+                min: 6,
+                max: MAX_LEN
+            })
+        );
+        assert_eq!(
+            ff1.decrypt(&[], &[0, 1, 2, 3, 4, 10]),
+            Err(FpeError::InvalidNumeral {
+                numeral: 10,
+                radix: 10
+            })
+        );
+
+        assert!(matches!(
+            Ff1::new(decode(KEY_128), 1),
+            Err(FpeError::InvalidRadix(1))
+        ));
+        assert!(matches!(
+            Ff1::new(vec![0u8; 15], 10),
+            Err(FpeError::InvalidKey(_))
+        ));
+    }
+}
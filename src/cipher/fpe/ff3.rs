@@ -0,0 +1,257 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use super::{add_mod, num, str_radix, sub_mod, validate_input, validate_radix, FpeError};
+use crate::cipher::block::ciphers::{aes::Aes, BlockCipher};
+
+// Ff3 implements the FF3-1 format-preserving encryption scheme from NIST
+// SP 800-38G revision 1, an eight round Feistel network over numeral strings.
+
+// FF3-1 reverses numeral strings and bytes throughout (REV and REVB), which
+// this follows to match the specification's test vectors:
+// W   = T_R if i is even, T_L otherwise
+// P   = (W XOR [i]^4) || [NUM_radix(REV(B))]^12
+// y   = NUM(REVB(encrypt_block(REVB(P), REVB(K))))
+// C   = REV(STR^m_radix((NUM_radix(REV(A)) + y) mod radix^m))
+// A, B = B, C
+
+// The tweak is 56 bits. FF3's original 64 bit tweak allowed an attack by
+// Durak and Vaudenay, which FF3-1 fixes by zeroing 4 bits of each tweak half.
+pub struct Ff3 {
+    cipher: Aes,
+    radix: u32,
+    min_len: usize,
+    max_len: usize,
+}
+
+const ROUNDS: u8 = 8;
+pub const FF3_TWEAK_LEN: usize = 7;
+
+impl Ff3 {
+    pub fn new(key: Vec<u8>, radix: u32) -> Result<Self, FpeError> {
+        let min_len = validate_radix(radix)?;
+
+        // each half of the input must fit in the 96 bits of P: the maximum
+        // length is 2 * floor(log_radix(2^96))
+        let mut half_len = 0;
+        let mut domain = 1u128;
+        while domain * radix as u128 <= 1 << 96 {
+            domain *= radix as u128;
+            half_len += 1;
+        }
+
+        let mut key = key;
+        key.reverse();
+
+        Ok(Ff3 {
+            cipher: Aes::new(key)?,
+            radix,
+            min_len,
+            max_len: 2 * half_len,
+        })
+    }
+
+    pub fn encrypt(&self, tweak: &[u8], plaintext: &[u16]) -> Result<Vec<u16>, FpeError> {
+        let (tweak_l, tweak_r) = split_tweak(tweak)?;
+        self.process(tweak_l, tweak_r, plaintext, Direction::Encrypt)
+    }
+
+    pub fn decrypt(&self, tweak: &[u8], ciphertext: &[u16]) -> Result<Vec<u16>, FpeError> {
+        let (tweak_l, tweak_r) = split_tweak(tweak)?;
+        self.process(tweak_l, tweak_r, ciphertext, Direction::Decrypt)
+    }
+
+    fn process(
+        &self,
+        tweak_l: [u8; 4],
+        tweak_r: [u8; 4],
+        input: &[u16],
+        direction: Direction,
+    ) -> Result<Vec<u16>, FpeError> {
+        validate_input(input, self.radix, self.min_len, self.max_len)?;
+        let n = input.len();
+        let u = (n + 1) / 2;
+
+        // the halves are kept reversed, so numerals are least significant first
+        let mut a = input[..u].iter().rev().copied().collect::<Vec<u16>>();
+        let mut b = input[u..].iter().rev().copied().collect::<Vec<u16>>();
+        for round in 0..ROUNDS {
+            let i = match direction {
+                Direction::Encrypt => round,
+                Direction::Decrypt => ROUNDS - 1 - round,
+            };
+            let (m, w) = if i % 2 == 0 {
+                (u, tweak_r)
+            } else {
+                (n - u, tweak_l)
+            };
+
+            match direction {
+                Direction::Encrypt => {
+                    let y = self.round(w, i, &b, m);
+                    let c = add_mod(&a, &y, self.radix);
+                    a = b;
+                    b = c;
+                }
+                Direction::Decrypt => {
+                    let y = self.round(w, i, &a, m);
+                    let c = sub_mod(&b, &y, self.radix);
+                    b = a;
+                    a = c;
+                }
+            }
+        }
+
+        Ok(a.into_iter().rev().chain(b.into_iter().rev()).collect())
+    }
+
+    // round returns y mod radix^m, as m numerals, for round i with the
+    // reversed Feistel half x.
+    fn round(&self, w: [u8; 4], i: u8, x: &[u16], m: usize) -> Vec<u16> {
+        let mut p = [0u8; 16];
+        p[..4].copy_from_slice(&w);
+        p[3] ^= i;
+        p[4..].copy_from_slice(&num(x, self.radix, 12));
+
+        p.reverse();
+        let block: GenericArray<u8, U16> = p.into();
+        let mut s = self.cipher.encrypt_block(block);
+        s.reverse();
+
+        str_radix(&s, self.radix, m)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+// split_tweak splits the 56 bit tweak into T_L, the first 28 bits, and T_R,
+// the last 24 bits followed by bits 28 to 32, each padded with four zero bits.
+fn split_tweak(tweak: &[u8]) -> Result<([u8; 4], [u8; 4]), FpeError> {
+    if tweak.len() != FF3_TWEAK_LEN {
+        return Err(FpeError::InvalidTweakLength(tweak.len()));
+    }
+
+    let tweak_l = [tweak[0], tweak[1], tweak[2], tweak[3] & 0xf0];
+    let tweak_r = [tweak[4], tweak[5], tweak[6], tweak[3] << 4];
+
+    Ok((tweak_l, tweak_r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cipher::fpe::Alphabet, encode::hex::decode};
+
+    const DIGITS: &str = "0123456789";
+
+    // NIST FF3-1 samples
+    struct TestCase {
+        key: &'static str,
+        alphabet: &'static str,
+        tweak: &'static str,
+        plaintext: &'static str,
+        ciphertext: &'static str,
+    }
+
+    #[rustfmt::skip]
+    const TEST_CASES: [TestCase; 2] = [
+        TestCase {
+            key: "2de79d232df5585d68ce47882ae256d6",
+            alphabet: DIGITS,
+            tweak: "cbd09280979564",
+            plaintext: "3992520240",
+            ciphertext: "8901801106",
+        },
+        TestCase {
+            key: "01c63017111438f7fc8e24eb16c71ab5",
+            alphabet: DIGITS,
+            tweak: "c4e822dcd09f27",
+            plaintext: "60761757463116869318437658042297305934914824457484538562",
+            ciphertext: "35637144092473838892796702739628394376915177448290847293",
+        },
+    ];
+
+    #[test]
+    fn test_nist_samples() {
+        for test_case in TEST_CASES.iter() {
+            let alphabet = Alphabet::new(test_case.alphabet).unwrap();
+            let ff3 = Ff3::new(decode(test_case.key), alphabet.radix()).unwrap();
+            let tweak = decode(test_case.tweak);
+
+            let plaintext = alphabet.to_numerals(test_case.plaintext).unwrap();
+            let encrypted = ff3.encrypt(&tweak, &plaintext).unwrap();
+            assert_eq!(alphabet.to_string(&encrypted), test_case.ciphertext);
+
+            let decrypted = ff3.decrypt(&tweak, &encrypted).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_ff3_feistel_network() {
+        // FF3-1 only changes how the tweak halves are derived, so the Feistel
+        // network reproduces the original FF3 samples given their 64 bit tweaks
+        let alphabet = Alphabet::new(DIGITS).unwrap();
+        let ff3 = Ff3::new(decode("ef4359d8d580aa4f7f036d6f04fc6a94"), 10).unwrap();
+        let plaintext = alphabet.to_numerals("890121234567890000").unwrap();
+
+        for (tweak, ciphertext) in [
+            ("d8e7920afa330a73", "750918814058654607"),
+            ("9a768a92f60e12d8", "018989839189395384"),
+        ] {
+            let tweak = decode(tweak);
+            let tweak_l = tweak[..4].try_into().unwrap();
+            let tweak_r = tweak[4..].try_into().unwrap();
+
+            let encrypted = ff3
+                .process(tweak_l, tweak_r, &plaintext, Direction::Encrypt)
+                .unwrap();
+            assert_eq!(alphabet.to_string(&encrypted), ciphertext);
+
+            let decrypted = ff3
+                .process(tweak_l, tweak_r, &encrypted, Direction::Decrypt)
+                .unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tweak = [0x5a; FF3_TWEAK_LEN];
+        for (radix, len) in [(2, 20), (2, 192), (10, 56), (26, 40), (1 << 16, 12)] {
+            let ff3 = Ff3::new(vec![0x42; 16], radix).unwrap();
+            let plaintext = (0..len)
+                .map(|i| ((i * 7919) % radix as usize) as u16)
+                .collect::<Vec<u16>>();
+
+            let encrypted = ff3.encrypt(&tweak, &plaintext).unwrap();
+            assert_eq!(encrypted.len(), len);
+            assert!(encrypted.iter().all(|&numeral| (numeral as u32) < radix));
+            assert_ne!(encrypted, plaintext);
+
+            assert_eq!(ff3.decrypt(&tweak, &encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let ff3 = Ff3::new(vec![0u8; 16], 10).unwrap();
+
+        // 10^28 <= 2^96 < 10^29
+        assert_eq!(
+            ff3.encrypt(&[0; 7], &[0; 57]),
+            Err(FpeError::InvalidInputLength {
+                len: 57,
+                min: 6,
+                max: 56
+            })
+        );
+        assert_eq!(
+            ff3.encrypt(&[0; 8], &[0; 10]),
+            Err(FpeError::InvalidTweakLength(8))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use thiserror::Error;
+
+use crate::cipher::KeyError;
+
+mod alphabet;
+mod ff1;
+mod ff3;
+
+pub use alphabet::*;
+pub use ff1::*;
+pub use ff3::*;
+
+// Format-preserving encryption (FPE) schemes encrypt a string of numerals in
+// some radix to another string of the same length and radix, so that e.g. a
+// 16 digit card number encrypts to 16 digits. NIST SP 800-38G specifies FF1
+// and FF3-1, both Feistel networks whose round function is built from AES.
+
+// Inputs are numeral strings: slices of u16 numerals in [0, radix), most
+// significant first. Alphabet maps between numerals and characters.
+
+// FPE is deterministic: equal plaintexts under the same key and tweak encrypt
+// equally. Varying the tweak (e.g. with the first six and last four digits of
+// a card number left in the clear) shrinks the groups an attacker can compare.
+// Small domains are inherently weak, so both schemes require at least a
+// million possible inputs.
+
+/// Errors that can occur when encrypting or decrypting with an FPE scheme.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FpeError {
+    /// Occurs when the block cipher rejects the key.
+    #[error(transparent)]
+    InvalidKey(#[from] KeyError),
+
+    /// Occurs when the radix is outside [2, 2^16].
+    #[error("radix {0} is not supported")]
+    InvalidRadix(u32),
+
+    /// Occurs when the input length is outside the bounds for the radix.
+    #[error("input length {len} is outside [{min}, {max}]")]
+    InvalidInputLength { len: usize, min: usize, max: usize },
+
+    /// Occurs when a numeral is not less than the radix.
+    #[error("numeral {numeral} is invalid for radix {radix}")]
+    InvalidNumeral { numeral: u16, radix: u32 },
+
+    /// Occurs when the tweak length is not supported by the scheme.
+    #[error("tweak length {0} is not supported")]
+    InvalidTweakLength(usize),
+
+    /// Occurs when an alphabet repeats a character.
+    #[error("alphabet repeats the character {0:?}")]
+    DuplicateCharacter(char),
+
+    /// Occurs when a string contains a character outside the alphabet.
+    #[error("character {0:?} is not in the alphabet")]
+    InvalidCharacter(char),
+}
+
+const MAX_RADIX: u32 = 1 << 16;
+
+// validate_radix checks the radix, and returns the minimum input length: the
+// smallest length with at least a million possible inputs, and at least 2.
+fn validate_radix(radix: u32) -> Result<usize, FpeError> {
+    if !(2..=MAX_RADIX).contains(&radix) {
+        return Err(FpeError::InvalidRadix(radix));
+    }
+
+    let mut min_len = 0;
+    let mut domain = 1u64;
+    while domain < 1_000_000 {
+        domain *= radix as u64;
+        min_len += 1;
+    }
+
+    Ok(min_len.max(2))
+}
+
+fn validate_input(input: &[u16], radix: u32, min: usize, max: usize) -> Result<(), FpeError> {
+    if input.len() < min || input.len() > max {
+        return Err(FpeError::InvalidInputLength {
+            len: input.len(),
+            min,
+            max,
+        });
+    }
+
+    match input.iter().find(|&&numeral| numeral as u32 >= radix) {
+        Some(&numeral) => Err(FpeError::InvalidNumeral { numeral, radix }),
+        None => Ok(()),
+    }
+}
+
+// num returns NUM_radix(X), the value of a numeral string, as big-endian
+// bytes with at least len bytes.
+fn num(numerals: &[u16], radix: u32, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    for &numeral in numerals {
+        // bytes = bytes * radix + numeral
+        let mut carry = numeral as u32;
+        for byte in bytes.iter_mut().rev() {
+            let value = *byte as u32 * radix + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    bytes
+}
+
+// byte_len returns the number of bytes needed to hold any len numeral string,
+// ceil(ceil(len * log2(radix)) / 8), computed as the byte length of
+// radix^len - 1.
+fn byte_len(radix: u32, len: usize) -> usize {
+    let max = vec![(radix - 1) as u16; len];
+    let bytes = num(&max, radix, 0);
+
+    bytes.len()
+}
+
+// str_radix returns STR^m_radix(NUM(bytes) mod radix^m), the m least
+// significant numerals of a big-endian byte string.
+fn str_radix(bytes: &[u8], radix: u32, m: usize) -> Vec<u16> {
+    let mut value = bytes.to_vec();
+    let mut numerals = vec![0u16; m];
+    for numeral in numerals.iter_mut().rev() {
+        // value, remainder = value / radix, value % radix
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / radix) as u8;
+            remainder = current % radix;
+        }
+        *numeral = remainder as u16;
+    }
+
+    numerals
+}
+
+// add_mod returns (NUM_radix(a) + NUM_radix(b)) mod radix^m for numeral
+// strings of length m.
+fn add_mod(a: &[u16], b: &[u16], radix: u32) -> Vec<u16> {
+    let mut carry = 0u32;
+    let mut sum = vec![0u16; a.len()];
+    for i in (0..a.len()).rev() {
+        let value = a[i] as u32 + b[i] as u32 + carry;
+        sum[i] = (value % radix) as u16;
+        carry = value / radix;
+    }
+
+    sum
+}
+
+// sub_mod returns (NUM_radix(a) - NUM_radix(b)) mod radix^m for numeral
+// strings of length m.
+fn sub_mod(a: &[u16], b: &[u16], radix: u32) -> Vec<u16> {
+    let mut borrow = 0u32;
+    let mut difference = vec![0u16; a.len()];
+    for i in (0..a.len()).rev() {
+        let subtrahend = b[i] as u32 + borrow;
+        if (a[i] as u32) < subtrahend {
+            difference[i] = (a[i] as u32 + radix - subtrahend) as u16;
+            borrow = 1;
+        } else {
+            difference[i] = (a[i] as u32 - subtrahend) as u16;
+            borrow = 0;
+        }
+    }
+
+    difference
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeral_conversion() {
+        let numerals = [1, 2, 3, 4, 5];
+        assert_eq!(num(&numerals, 10, 0), 12345u32.to_be_bytes()[2..].to_vec());
+        assert_eq!(num(&numerals, 10, 4), 12345u32.to_be_bytes().to_vec());
+        assert_eq!(str_radix(&12345u32.to_be_bytes(), 10, 5), numerals);
+
+        // str_radix reduces mod radix^m
+        assert_eq!(str_radix(&12345u32.to_be_bytes(), 10, 3), [3, 4, 5]);
+        assert_eq!(str_radix(&[0xff, 0xff], 256, 3), [0, 255, 255]);
+
+        assert_eq!(byte_len(10, 2), 1); // 99
+        assert_eq!(byte_len(10, 3), 2); // 999
+        assert_eq!(byte_len(256, 2), 2);
+        assert_eq!(byte_len(1 << 16, 3), 6);
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        assert_eq!(add_mod(&[9, 9, 5], &[0, 0, 7], 10), [0, 0, 2]);
+        assert_eq!(sub_mod(&[0, 0, 2], &[0, 0, 7], 10), [9, 9, 5]);
+        assert_eq!(sub_mod(&[1, 2], &[1, 2], 10), [0, 0]);
+
+        let max = (MAX_RADIX - 1) as u16;
+        assert_eq!(add_mod(&[max, max], &[0, 1], MAX_RADIX), [0, 0]);
+    }
+
+    #[test]
+    fn test_validate_radix() {
+        assert_eq!(validate_radix(10), Ok(6));
+        assert_eq!(validate_radix(2), Ok(20));
+        assert_eq!(validate_radix(1 << 16), Ok(2));
+        assert_eq!(validate_radix(1), Err(FpeError::InvalidRadix(1)));
+        assert_eq!(
+            validate_radix((1 << 16) + 1),
+            Err(FpeError::InvalidRadix((1 << 16) + 1))
+        );
+    }
+}
@@ -1,4 +1,52 @@
+use thiserror::Error;
+
+pub mod aead;
 pub mod block;
+pub mod fpe;
 pub mod stream;
 
-pub trait StreamCipher {}
+/// `StreamCipher` is a trait representing a cipher that encrypts by XORing its
+/// input with a keystream, so ciphertext is always the same length as plaintext
+/// and needs no padding.
+///
+/// Consecutive calls continue the same stream, so a message can be processed
+/// in pieces of any size, and the pieces concatenated.
+///
+/// Block ciphers are turned into stream ciphers by the keystream modes CTR,
+/// OFB and CFB. As with any stream cipher, reusing a (key, IV) pair for two
+/// messages leaks their XOR.
+pub trait StreamCipher {
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8>;
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8>;
+}
+
+/// Errors that can occur when setting up a cipher from a key.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyError {
+    /// Occurs when the key length is not one the cipher supports.
+    #[error("key length {actual} is not supported, expected one of {expected:?}")]
+    InvalidKeyLength {
+        expected: &'static [usize],
+        actual: usize,
+    },
+
+    /// Occurs when a DES key is one of the four weak keys, under which
+    /// encryption and decryption are the same permutation.
+    #[error("key is a weak key")]
+    WeakKey,
+
+    /// Occurs when a DES key is one of the twelve semi-weak keys, which pair up
+    /// so that encrypting under one key decrypts under the other.
+    #[error("key is a semi-weak key")]
+    SemiWeakKey,
+
+    /// Occurs when a byte of a DES key does not have odd parity.
+    #[error("key byte {0} does not have odd parity")]
+    InvalidParity(usize),
+
+    /// Occurs when the cost of an expensive key schedule, such as EksBlowfish's,
+    /// is larger than it supports.
+    #[error("cost {0} is not supported")]
+    InvalidCost(u32),
+}
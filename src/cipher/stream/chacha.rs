@@ -0,0 +1,413 @@
+use super::StreamCipherError;
+use crate::cipher::{KeyError, StreamCipher};
+
+// ChaCha20 implements the ChaCha20 stream cipher as defined in RFC 8439, and
+// Bernstein's original variant with a 64 bit nonce.
+
+// The 512 bit state is a 4x4 matrix of words, filled with a constant, the key,
+// a block counter and a nonce:
+// cccccccc  cccccccc  cccccccc  cccccccc
+// kkkkkkkk  kkkkkkkk  kkkkkkkk  kkkkkkkk
+// kkkkkkkk  kkkkkkkk  kkkkkkkk  kkkkkkkk
+// bbbbbbbb  nnnnnnnn  nnnnnnnn  nnnnnnnn    (IETF: 32 bit counter, 96 bit nonce)
+// bbbbbbbb  bbbbbbbb  nnnnnnnn  nnnnnnnn    (original: 64 bit counter, 64 bit nonce)
+
+// Each keystream block is the state after 20 rounds of quarter rounds (10
+// column rounds alternating with 10 diagonal rounds), added to the input state.
+// Only additions, rotations and XORs are used, so ChaCha20 runs in constant
+// time without table lookups.
+
+// The counter wraps after 2^32 blocks (256 GiB) with IETF nonces, repeating
+// the keystream. Reusing a (key, nonce) pair reuses the keystream.
+pub struct ChaCha20 {
+    state: [u32; 16],
+    counter_words: usize, // 1 for IETF nonces, 2 for original nonces
+    keystream: [u8; 64],
+    offset: usize, // offset of the next byte within keystream
+}
+
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]; // "expand 32-byte k"
+const DOUBLE_ROUNDS: usize = 10;
+
+impl ChaCha20 {
+    // new takes a 256 bit key, and either a 96 bit IETF nonce or a 64 bit
+    // original nonce. The block counter starts at 0.
+    pub fn new(key: Vec<u8>, nonce: Vec<u8>) -> Result<Self, StreamCipherError> {
+        let key = key_words(&key)?;
+        let counter_words = match nonce.len() {
+            12 => 1,
+            8 => 2,
+            len => return Err(StreamCipherError::InvalidNonceLength(len)),
+        };
+
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&CONSTANTS);
+        state[4..12].copy_from_slice(&key);
+        for (word, chunk) in state[12 + counter_words..]
+            .iter_mut()
+            .zip(nonce.chunks_exact(4))
+        {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        Ok(ChaCha20 {
+            state,
+            counter_words,
+            keystream: [0u8; 64],
+            offset: 64,
+        })
+    }
+
+    // seek moves the stream to the start of the given block. With IETF nonces
+    // the counter is 32 bits, and only the low 32 bits of block are used
+    // (e.g. ChaCha20-Poly1305 encrypts its payload starting from block 1).
+    pub fn seek(&mut self, block: u64) {
+        self.state[12] = block as u32;
+        if self.counter_words == 2 {
+            self.state[13] = (block >> 32) as u32;
+        }
+        self.offset = 64;
+    }
+
+    fn apply_keystream(&mut self, input: Vec<u8>) -> Vec<u8> {
+        input
+            .into_iter()
+            .map(|byte| {
+                if self.offset == 64 {
+                    self.keystream = serialize(&block(&self.state));
+                    self.offset = 0;
+                    self.increment_counter();
+                }
+
+                let output = byte ^ self.keystream[self.offset];
+                self.offset += 1;
+
+                output
+            })
+            .collect()
+    }
+
+    fn increment_counter(&mut self) {
+        let (low, carry) = self.state[12].overflowing_add(1);
+        self.state[12] = low;
+        if carry && self.counter_words == 2 {
+            self.state[13] = self.state[13].wrapping_add(1);
+        }
+    }
+}
+
+impl StreamCipher for ChaCha20 {
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(ciphertext)
+    }
+}
+
+// XChaCha20 extends ChaCha20's nonce to 192 bits, as defined in
+// draft-irtf-cfrg-xchacha. The first 128 bits of the nonce derive a subkey
+// with HChaCha20, and the remaining 64 bits are used as an IETF nonce
+// (prefixed with 4 zero bytes) under the subkey.
+
+// Nonces this long can be drawn at random for every message without a
+// meaningful chance of collision.
+pub struct XChaCha20 {
+    chacha: ChaCha20,
+}
+
+impl XChaCha20 {
+    pub fn new(key: Vec<u8>, nonce: Vec<u8>) -> Result<Self, StreamCipherError> {
+        if key.len() != 32 {
+            return Err(KeyError::InvalidKeyLength {
+                expected: &[32],
+                actual: key.len(),
+            }
+            .into());
+        }
+        if nonce.len() != 24 {
+            return Err(StreamCipherError::InvalidNonceLength(nonce.len()));
+        }
+
+        // SAFETY: lengths were checked above
+        let subkey = hchacha20(
+            key.as_slice().try_into().unwrap(),
+            nonce[..16].try_into().unwrap(),
+        );
+
+        let mut chacha_nonce = vec![0u8; 4];
+        chacha_nonce.extend_from_slice(&nonce[16..]);
+
+        Ok(XChaCha20 {
+            chacha: ChaCha20::new(subkey.to_vec(), chacha_nonce)?,
+        })
+    }
+
+    pub fn seek(&mut self, block: u64) {
+        self.chacha.seek(block);
+    }
+}
+
+impl StreamCipher for XChaCha20 {
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.chacha.encrypt(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.chacha.decrypt(ciphertext)
+    }
+}
+
+/// Computes the ChaCha20 block function of RFC 8439 section 2.3 for a 256 bit
+/// key, a 32 bit block counter and a 96 bit nonce.
+pub fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut chacha = ChaCha20::new(key.to_vec(), nonce.to_vec()).unwrap(); // SAFETY: lengths are typed
+    chacha.seek(counter as u64);
+
+    serialize(&block(&chacha.state))
+}
+
+/// Computes HChaCha20, which maps a 256 bit key and a 128 bit nonce to a 256
+/// bit subkey. HChaCha20 is ChaCha20's rounds without the final addition of
+/// the input state, keeping the first and last rows, which an attacker can't
+/// recover without the key.
+///
+/// see: draft-irtf-cfrg-xchacha, section 2.2
+pub fn hchacha20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&CONSTANTS);
+    state[4..12].copy_from_slice(&key_words(key).unwrap()); // SAFETY: the length is typed
+    for (word, chunk) in state[12..].iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    let state = rounds(state);
+    let mut subkey = [0u8; 32];
+    for (chunk, word) in subkey
+        .chunks_exact_mut(4)
+        .zip(state[..4].iter().chain(&state[12..]))
+    {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    subkey
+}
+
+fn key_words(key: &[u8]) -> Result<[u32; 8], KeyError> {
+    if key.len() != 32 {
+        return Err(KeyError::InvalidKeyLength {
+            expected: &[32],
+            actual: key.len(),
+        });
+    }
+
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    Ok(words)
+}
+
+fn block(state: &[u32; 16]) -> [u32; 16] {
+    let mut output = rounds(*state);
+    for (word, input) in output.iter_mut().zip(state) {
+        *word = word.wrapping_add(*input);
+    }
+
+    output
+}
+
+fn rounds(mut state: [u32; 16]) -> [u32; 16] {
+    for _ in 0..DOUBLE_ROUNDS {
+        // column rounds
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        // diagonal rounds
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    state
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn serialize(state: &[u32; 16]) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    // RFC 8439 section 2.1.1
+    #[test]
+    fn test_quarter_round() {
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&[0x11111111, 0x01020304, 0x9b8d6f43, 0x01234567]);
+        quarter_round(&mut state, 0, 1, 2, 3);
+
+        assert_eq!(state[..4], [0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb]);
+    }
+
+    // RFC 8439 section 2.3.2
+    #[test]
+    fn test_block_function() {
+        let key = decode(KEY).try_into().unwrap();
+        let nonce = decode("000000090000004a00000000").try_into().unwrap();
+
+        assert_eq!(
+            chacha20_block(&key, 1, &nonce).to_vec(),
+            decode(concat!(
+                "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e",
+                "d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+            ))
+        );
+    }
+
+    // RFC 8439 section 2.4.2
+    #[test]
+    fn test_encrypt() {
+        let mut chacha = ChaCha20::new(decode(KEY), decode("000000000000004a00000000")).unwrap();
+        chacha.seek(1);
+
+        let expected_ciphertext = decode(concat!(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b",
+            "f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8",
+            "07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736",
+            "5af90bbf74a35be6b40b8eedf2785e42874d",
+        ));
+
+        // split across calls, including mid-block
+        let mut encrypted = chacha.encrypt(SUNSCREEN[..10].to_vec());
+        encrypted.extend(chacha.encrypt(SUNSCREEN[10..].to_vec()));
+        assert_eq!(encrypted, expected_ciphertext);
+
+        let mut chacha = ChaCha20::new(decode(KEY), decode("000000000000004a00000000")).unwrap();
+        chacha.seek(1);
+        assert_eq!(chacha.decrypt(encrypted), SUNSCREEN);
+    }
+
+    // RFC 8439 appendix A.1 test vector #1 (also the original ChaCha20's
+    // keystream under an all zero key and nonce)
+    #[test]
+    fn test_zero_keystream() {
+        let expected = decode(concat!(
+            "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7",
+            "da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586",
+        ));
+
+        let mut ietf = ChaCha20::new(vec![0u8; 32], vec![0u8; 12]).unwrap();
+        assert_eq!(ietf.encrypt(vec![0u8; 64]), expected);
+
+        let mut original = ChaCha20::new(vec![0u8; 32], vec![0u8; 8]).unwrap();
+        assert_eq!(original.encrypt(vec![0u8; 64]), expected);
+    }
+
+    #[test]
+    fn test_original_nonce_counter() {
+        // the original layout's 64 bit counter carries into the word that
+        // holds the first nonce word of the IETF layout
+        let mut original = ChaCha20::new(decode(KEY), decode("0001020304050607")).unwrap();
+        original.seek(u32::MAX as u64);
+
+        let mut ietf = ChaCha20::new(decode(KEY), decode("000000000001020304050607")).unwrap();
+        ietf.seek(u32::MAX as u64);
+        let mut carried = ChaCha20::new(decode(KEY), decode("010000000001020304050607")).unwrap();
+
+        let mut expected = ietf.encrypt(vec![0u8; 64]);
+        expected.extend(carried.encrypt(vec![0u8; 64]));
+        assert_eq!(original.encrypt(vec![0u8; 128]), expected);
+    }
+
+    // draft-irtf-cfrg-xchacha-03 section 2.2.1
+    #[test]
+    fn test_hchacha20() {
+        let key = decode(KEY).try_into().unwrap();
+        let nonce = decode("000000090000004a0000000031415927")
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            hchacha20(&key, &nonce).to_vec(),
+            decode("82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc")
+        );
+    }
+
+    // draft-irtf-cfrg-xchacha-03 appendix A.3.2
+    #[test]
+    fn test_xchacha20() {
+        let key = (0x80..=0x9f).collect::<Vec<u8>>();
+        let nonce = decode("404142434445464748494a4b4c4d4e4f5051525354555658");
+        let plaintext = concat!(
+            "The dhole (pronounced \"dole\") is also known as the Asiatic wild dog, ",
+            "red dog, and whistling dog. It is about the size of a German shepherd ",
+            "but looks more like a long-legged fox. This highly elusive and skilled ",
+            "jumper is classified with wolves, coyotes, jackals, and foxes in the ",
+            "taxonomic family Canidae.",
+        );
+        let expected_ciphertext = decode(concat!(
+            "4559abba4e48c16102e8bb2c05e6947f50a786de162f9b0b7e592a9b53d0d4e9",
+            "8d8d6410d540a1a6375b26d80dace4fab52384c731acbf16a5923c0c48d3575d",
+            "4d0d2c673b666faa731061277701093a6bf7a158a8864292a41c48e3a9b4c0da",
+            "ece0f8d98d0d7e05b37a307bbb66333164ec9e1b24ea0d6c3ffddcec4f68e744",
+            "3056193a03c810e11344ca06d8ed8a2bfb1e8d48cfa6bc0eb4e2464b74814240",
+            "7c9f431aee769960e15ba8b96890466ef2457599852385c661f752ce20f9da0c",
+            "09ab6b19df74e76a95967446f8d0fd415e7bee2a12a114c20eb5292ae7a349ae",
+            "577820d5520a1f3fb62a17ce6a7e68fa7c79111d8860920bc048ef43fe84486c",
+            "cb87c25f0ae045f0cce1e7989a9aa220a28bdd4827e751a24a6d5c62d790a663",
+            "93b93111c1a55dd7421a10184974c7c5",
+        ));
+
+        let mut xchacha = XChaCha20::new(key.clone(), nonce.clone()).unwrap();
+        let encrypted = xchacha.encrypt(plaintext.as_bytes().to_vec());
+        assert_eq!(encrypted, expected_ciphertext);
+
+        let mut xchacha = XChaCha20::new(key, nonce).unwrap();
+        assert_eq!(xchacha.decrypt(encrypted), plaintext.as_bytes());
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert!(matches!(
+            ChaCha20::new(vec![0u8; 16], vec![0u8; 12]),
+            Err(StreamCipherError::InvalidKey(KeyError::InvalidKeyLength {
+                actual: 16,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            ChaCha20::new(vec![0u8; 32], vec![0u8; 16]),
+            Err(StreamCipherError::InvalidNonceLength(16))
+        ));
+        assert!(matches!(
+            XChaCha20::new(vec![0u8; 32], vec![0u8; 12]),
+            Err(StreamCipherError::InvalidNonceLength(12))
+        ));
+    }
+}
@@ -1,3 +1,24 @@
+use thiserror::Error;
+
+use crate::cipher::KeyError;
+
+mod chacha;
+mod salsa;
 mod vernam;
 
+pub use chacha::*;
+pub use salsa::*;
 pub use vernam::*;
+
+/// Errors that can occur when setting up a stream cipher from a key and nonce.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamCipherError {
+    /// Occurs when the cipher rejects the key.
+    #[error(transparent)]
+    InvalidKey(#[from] KeyError),
+
+    /// Occurs when the nonce length is not supported by the cipher.
+    #[error("nonce length {0} is not supported")]
+    InvalidNonceLength(usize),
+}
@@ -0,0 +1,408 @@
+use super::StreamCipherError;
+use crate::cipher::{KeyError, StreamCipher};
+
+/// Number of rounds used by Salsa20. The reduced round variants are the
+/// targets of published cryptanalysis, and are only meant for studying it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SalsaRounds {
+    /// Salsa20/8
+    R8,
+    /// Salsa20/12, part of the eSTREAM portfolio.
+    R12,
+    /// Salsa20/20, the full cipher.
+    #[default]
+    R20,
+}
+
+impl SalsaRounds {
+    fn double_rounds(&self) -> usize {
+        match self {
+            SalsaRounds::R8 => 4,
+            SalsaRounds::R12 => 6,
+            SalsaRounds::R20 => 10,
+        }
+    }
+}
+
+// Salsa20 implements Bernstein's Salsa20 stream cipher with a 256 bit key,
+// a 64 bit nonce and a 64 bit block counter.
+
+// The 512 bit state is a 4x4 matrix of words, with the constant on the
+// diagonal:
+// cccccccc  kkkkkkkk  kkkkkkkk  kkkkkkkk
+// kkkkkkkk  cccccccc  nnnnnnnn  nnnnnnnn
+// bbbbbbbb  bbbbbbbb  cccccccc  kkkkkkkk
+// kkkkkkkk  kkkkkkkk  kkkkkkkk  cccccccc
+
+// Each keystream block is the state after alternating column and row rounds,
+// added to the input state. ChaCha is a later refinement of Salsa20 with
+// better diffusion per round.
+pub struct Salsa20 {
+    state: [u32; 16],
+    double_rounds: usize,
+    keystream: [u8; 64],
+    offset: usize, // offset of the next byte within keystream
+}
+
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]; // "expand 32-byte k"
+
+impl Salsa20 {
+    pub fn new(key: Vec<u8>, nonce: Vec<u8>) -> Result<Self, StreamCipherError> {
+        Salsa20::with_rounds(key, nonce, SalsaRounds::default())
+    }
+
+    pub fn with_rounds(
+        key: Vec<u8>,
+        nonce: Vec<u8>,
+        rounds: SalsaRounds,
+    ) -> Result<Self, StreamCipherError> {
+        let key = key_words(&key)?;
+        if nonce.len() != 8 {
+            return Err(StreamCipherError::InvalidNonceLength(nonce.len()));
+        }
+
+        let mut state = initial_state(&key);
+        state[6] = le32(&nonce[0..]);
+        state[7] = le32(&nonce[4..]);
+
+        Ok(Salsa20 {
+            state,
+            double_rounds: rounds.double_rounds(),
+            keystream: [0u8; 64],
+            offset: 64,
+        })
+    }
+
+    // seek moves the stream to the start of the given block.
+    pub fn seek(&mut self, block: u64) {
+        self.state[8] = block as u32;
+        self.state[9] = (block >> 32) as u32;
+        self.offset = 64;
+    }
+
+    fn apply_keystream(&mut self, input: Vec<u8>) -> Vec<u8> {
+        input
+            .into_iter()
+            .map(|byte| {
+                if self.offset == 64 {
+                    self.keystream = serialize(&block(&self.state, self.double_rounds));
+                    self.offset = 0;
+
+                    let counter =
+                        (self.state[8] as u64 | (self.state[9] as u64) << 32).wrapping_add(1);
+                    self.state[8] = counter as u32;
+                    self.state[9] = (counter >> 32) as u32;
+                }
+
+                let output = byte ^ self.keystream[self.offset];
+                self.offset += 1;
+
+                output
+            })
+            .collect()
+    }
+}
+
+impl StreamCipher for Salsa20 {
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.apply_keystream(ciphertext)
+    }
+}
+
+// XSalsa20 extends Salsa20's nonce to 192 bits, as defined in Bernstein's
+// "Extending the Salsa20 nonce". The first 128 bits of the nonce derive a
+// subkey with HSalsa20, and the remaining 64 bits are the Salsa20 nonce
+// under the subkey.
+
+// Nonces this long can be drawn at random for every message without a
+// meaningful chance of collision.
+pub struct XSalsa20 {
+    salsa: Salsa20,
+}
+
+impl XSalsa20 {
+    pub fn new(key: Vec<u8>, nonce: Vec<u8>) -> Result<Self, StreamCipherError> {
+        key_words(&key)?;
+        if nonce.len() != 24 {
+            return Err(StreamCipherError::InvalidNonceLength(nonce.len()));
+        }
+
+        // SAFETY: lengths were checked above
+        let subkey = hsalsa20(
+            key.as_slice().try_into().unwrap(),
+            nonce[..16].try_into().unwrap(),
+        );
+
+        Ok(XSalsa20 {
+            salsa: Salsa20::new(subkey.to_vec(), nonce[16..].to_vec())?,
+        })
+    }
+
+    pub fn seek(&mut self, block: u64) {
+        self.salsa.seek(block);
+    }
+}
+
+impl StreamCipher for XSalsa20 {
+    fn encrypt(&mut self, plaintext: Vec<u8>) -> Vec<u8> {
+        self.salsa.encrypt(plaintext)
+    }
+
+    fn decrypt(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        self.salsa.decrypt(ciphertext)
+    }
+}
+
+/// Computes HSalsa20, which maps a 256 bit key and a 128 bit nonce to a 256
+/// bit subkey. HSalsa20 is Salsa20's rounds without the final addition of the
+/// input state, keeping the diagonal and the words that held the nonce.
+///
+/// see: https://cr.yp.to/snuffle/xsalsa-20110204.pdf
+pub fn hsalsa20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let mut state = initial_state(&key_words(key).unwrap()); // SAFETY: the length is typed
+    for (i, chunk) in nonce.chunks_exact(4).enumerate() {
+        state[6 + i] = le32(chunk);
+    }
+
+    let state = rounds(state, SalsaRounds::R20.double_rounds());
+    let mut subkey = [0u8; 32];
+    for (chunk, i) in subkey.chunks_exact_mut(4).zip([0, 5, 10, 15, 6, 7, 8, 9]) {
+        chunk.copy_from_slice(&state[i].to_le_bytes());
+    }
+
+    subkey
+}
+
+fn initial_state(key: &[u32; 8]) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[0] = CONSTANTS[0];
+    state[1..5].copy_from_slice(&key[..4]);
+    state[5] = CONSTANTS[1];
+    state[10] = CONSTANTS[2];
+    state[11..15].copy_from_slice(&key[4..]);
+    state[15] = CONSTANTS[3];
+
+    state
+}
+
+fn key_words(key: &[u8]) -> Result<[u32; 8], KeyError> {
+    if key.len() != 32 {
+        return Err(KeyError::InvalidKeyLength {
+            expected: &[32],
+            actual: key.len(),
+        });
+    }
+
+    let mut words = [0u32; 8];
+    for (word, chunk) in words.iter_mut().zip(key.chunks_exact(4)) {
+        *word = le32(chunk);
+    }
+
+    Ok(words)
+}
+
+fn block(state: &[u32; 16], double_rounds: usize) -> [u32; 16] {
+    let mut output = rounds(*state, double_rounds);
+    for (word, input) in output.iter_mut().zip(state) {
+        *word = word.wrapping_add(*input);
+    }
+
+    output
+}
+
+fn rounds(mut state: [u32; 16], double_rounds: usize) -> [u32; 16] {
+    for _ in 0..double_rounds {
+        // column round
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 5, 9, 13, 1);
+        quarter_round(&mut state, 10, 14, 2, 6);
+        quarter_round(&mut state, 15, 3, 7, 11);
+        // row round
+        quarter_round(&mut state, 0, 1, 2, 3);
+        quarter_round(&mut state, 5, 6, 7, 4);
+        quarter_round(&mut state, 10, 11, 8, 9);
+        quarter_round(&mut state, 15, 12, 13, 14);
+    }
+
+    state
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[b] ^= state[a].wrapping_add(state[d]).rotate_left(7);
+    state[c] ^= state[b].wrapping_add(state[a]).rotate_left(9);
+    state[d] ^= state[c].wrapping_add(state[b]).rotate_left(13);
+    state[a] ^= state[d].wrapping_add(state[c]).rotate_left(18);
+}
+
+fn serialize(state: &[u32; 16]) -> [u8; 64] {
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    output
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    // Salsa20 specification, section 3
+    #[test]
+    fn test_quarter_round() {
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&[0x00000001, 0x00000000, 0x00000000, 0x00000000]);
+        quarter_round(&mut state, 0, 1, 2, 3);
+
+        assert_eq!(state[..4], [0x08008145, 0x00000080, 0x00010200, 0x20500000]);
+    }
+
+    // Salsa20 specification, section 8
+    #[test]
+    fn test_salsa20_hash() {
+        #[rustfmt::skip]
+        let input: [u8; 64] = [
+            211, 159, 13, 115, 76, 55, 82, 183, 3, 117, 222, 37, 191, 187, 234, 136,
+            49, 237, 179, 48, 1, 106, 178, 219, 175, 199, 166, 48, 86, 16, 179, 207,
+            31, 240, 32, 63, 15, 83, 93, 161, 116, 147, 48, 113, 238, 55, 204, 36,
+            79, 201, 235, 79, 3, 81, 156, 47, 203, 26, 244, 243, 88, 118, 104, 54,
+        ];
+        #[rustfmt::skip]
+        let expected: [u8; 64] = [
+            109, 42, 178, 168, 156, 240, 248, 238, 168, 196, 190, 203, 26, 110, 170, 154,
+            29, 29, 150, 26, 150, 30, 235, 249, 190, 163, 251, 48, 69, 144, 51, 57,
+            118, 40, 152, 157, 180, 57, 27, 94, 107, 42, 236, 35, 27, 111, 114, 114,
+            219, 236, 232, 135, 111, 155, 110, 18, 24, 232, 95, 158, 179, 19, 48, 202,
+        ];
+
+        let mut state = [0u32; 16];
+        for (word, chunk) in state.iter_mut().zip(input.chunks_exact(4)) {
+            *word = le32(chunk);
+        }
+
+        assert_eq!(serialize(&block(&state, 10)), expected);
+    }
+
+    // eSTREAM set 1, vector 0
+    #[test]
+    fn test_estream_vector() {
+        let mut key = vec![0u8; 32];
+        key[0] = 0x80;
+
+        let mut salsa = Salsa20::new(key, vec![0u8; 8]).unwrap();
+        assert_eq!(
+            salsa.encrypt(vec![0u8; 64]),
+            decode(concat!(
+                "e3be8fdd8beca2e3ea8ef9475b29a6e7003951e1097a5c38d23b7a5fad9f6844",
+                "b22c97559e2723c7cbbd3fe4fc8d9a0744652a83e72a9c461876af4d7ef1a117",
+            ))
+        );
+    }
+
+    // RFC 7914 section 8, the Salsa20/8 core that scrypt's BlockMix is built on
+    #[test]
+    fn test_salsa20_8_core() {
+        let input = decode(concat!(
+            "7e879a214f3ec9867ca940e641718f26baee555b8c61c1b50df846116dcd3b1d",
+            "ee24f319df9b3d8514121e4b5ac5aa3276021d2909c74829edebc68db8b8c25e",
+        ));
+
+        let mut state = [0u32; 16];
+        for (word, chunk) in state.iter_mut().zip(input.chunks_exact(4)) {
+            *word = le32(chunk);
+        }
+
+        assert_eq!(
+            serialize(&block(&state, 4)).to_vec(),
+            decode(concat!(
+                "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29",
+                "b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81",
+            ))
+        );
+    }
+
+    // eSTREAM set 1, vector 0 under Salsa20/8 and Salsa20/12, as produced by
+    // libsodium's crypto_stream_salsa208 and crypto_stream_salsa2012
+    #[test]
+    fn test_reduced_rounds() {
+        let mut key = vec![0u8; 32];
+        key[0] = 0x80;
+
+        for (rounds, expected) in [
+            (
+                SalsaRounds::R8,
+                concat!(
+                    "b1f599e9b0d96df436ae31f5ef589565b92d245db5a1d4c7a78e5e8d0146f8a4",
+                    "9d326c1a3bf50c052c9c8f114dc74972c4469591e31c9ed11927aa9871f38583",
+                ),
+            ),
+            (
+                SalsaRounds::R12,
+                concat!(
+                    "afe411ed1c4e07e4d0cde3b33e31ec190fa4cc796a58bafb848ead8d07d02cd2",
+                    "d4b6f9f30cb0b57007e3733895cc8d1060107975acaeeb689b6cf614ab64a3d6",
+                ),
+            ),
+        ] {
+            let mut salsa = Salsa20::with_rounds(key.clone(), vec![0u8; 8], rounds).unwrap();
+            assert_eq!(salsa.encrypt(vec![0u8; 64]), decode(expected));
+        }
+    }
+
+    #[test]
+    fn test_seek() {
+        let key = (0..32).collect::<Vec<u8>>();
+        let mut salsa = Salsa20::new(key.clone(), vec![0u8; 8]).unwrap();
+        let keystream = salsa.encrypt(vec![0u8; 192]);
+
+        let mut salsa = Salsa20::new(key, vec![0u8; 8]).unwrap();
+        salsa.seek(2);
+        assert_eq!(salsa.encrypt(vec![0u8; 64]), keystream[128..]);
+    }
+
+    // NaCl tests/core1.c and tests/stream3.c
+    #[test]
+    fn test_xsalsa20() {
+        let shared = decode("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        let firstkey = hsalsa20(&shared.try_into().unwrap(), &[0u8; 16]);
+        assert_eq!(
+            firstkey.to_vec(),
+            decode("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389")
+        );
+
+        let nonce = decode("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+        let mut xsalsa = XSalsa20::new(firstkey.to_vec(), nonce).unwrap();
+        assert_eq!(
+            xsalsa.encrypt(vec![0u8; 32]),
+            decode("eea6a7251c1e72916d11c2cb214d3c252539121d8e234e652d651fa4c8cff880")
+        );
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert!(matches!(
+            Salsa20::new(vec![0u8; 16], vec![0u8; 8]),
+            Err(StreamCipherError::InvalidKey(KeyError::InvalidKeyLength {
+                actual: 16,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            Salsa20::new(vec![0u8; 32], vec![0u8; 12]),
+            Err(StreamCipherError::InvalidNonceLength(12))
+        ));
+        assert!(matches!(
+            XSalsa20::new(vec![0u8; 32], vec![0u8; 8]),
+            Err(StreamCipherError::InvalidNonceLength(8))
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use generic_array::{
+    typenum::{U16, U64},
+    GenericArray,
+};
+
+use super::{Compression, Endianness, MerkleDamgard};
+
+// Md4 implements the MD4 message-digest algorithm as defined in RFC 1320,
+// which produces a 128 bit digest, and introduced the design that MD5, SHA-1
+// and SHA-2 refine: a Merkle–Damgård hash over 512 bit blocks, whose
+// compression function mixes 32 bit words with additions, rotations and
+// bitwise boolean functions.
+
+// MD4 is thoroughly broken: collisions can be found by hand, in fewer
+// operations than it takes to compute the hash (Wang et al., 2005). It is
+// still used by NTLM password hashes, and here for cryptanalysis exercises.
+pub type Md4 = MerkleDamgard<Md4Compression>;
+
+pub struct Md4Compression;
+
+impl Compression for Md4Compression {
+    type BlockSize = U64;
+    type OutputSize = U16;
+    type State = [u32; 4];
+
+    const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    const LENGTH_ENDIANNESS: Endianness = Endianness::Little;
+
+    // compress runs three rounds of sixteen operations over the block's words.
+    // Rounds 2 and 3 add the constants floor(2^30 * sqrt(2)) and
+    // floor(2^30 * sqrt(3)).
+    fn compress(state: &mut [u32; 4], block: &GenericArray<u8, U64>) {
+        let mut x = [0u32; 16];
+        for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let [mut a, mut b, mut c, mut d] = *state;
+        for i in 0..48 {
+            let (f, k) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((b & c) | (b & d) | (c & d), ROUND_2_ORDER[i % 16]),
+                _ => (b ^ c ^ d, ROUND_3_ORDER[i % 16]),
+            };
+
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(x[k])
+                .wrapping_add(K[i / 16])
+                .rotate_left(S[i / 16][i % 4]);
+            (a, b, c, d) = (d, rotated, b, c);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    fn output(state: &[u32; 4]) -> GenericArray<u8, U16> {
+        state.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn state_from_output(output: &GenericArray<u8, U16>) -> Option<[u32; 4]> {
+        let mut state = [0u32; 4];
+        for (word, chunk) in state.iter_mut().zip(output.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        Some(state)
+    }
+}
+
+const K: [u32; 3] = [0, 0x5a827999, 0x6ed9eba1];
+
+// S holds each round's rotation amounts.
+const S: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+
+const ROUND_2_ORDER: [usize; 16] = [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15];
+const ROUND_3_ORDER: [usize; 16] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        digest::{tests::check_incremental, Digest},
+        encode::hex::decode,
+    };
+
+    // RFC 1320 appendix A.5 test suite
+    const TEST_SUITE: [(&str, &str); 7] = [
+        ("", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+        ("a", "bde52cb31de33e46245e05fbdbd6fb24"),
+        ("abc", "a448017aaf21d8525fc10ae87aa6729d"),
+        ("message digest", "d9130a8164549fe818874806e1c7014b"),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            "d79e1c308aa5bbcdeea8ed63df412da9",
+        ),
+        (
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            "043f8582f241db351ce627e153e7f0e4",
+        ),
+        (
+            "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "e33b4ddc9c38f2199c3e7b164fcc0536",
+        ),
+    ];
+
+    #[test]
+    fn test_rfc1320_suite() {
+        for (message, digest) in TEST_SUITE {
+            assert_eq!(Md4::digest(message.as_bytes()).to_vec(), decode(digest));
+        }
+    }
+
+    #[test]
+    fn test_incremental() {
+        check_incremental::<Md4>();
+    }
+}
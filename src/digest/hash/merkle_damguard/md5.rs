@@ -1,3 +1,10 @@
+use generic_array::{
+    typenum::{U16, U64},
+    GenericArray,
+};
+
+use super::{Compression, Endianness, MerkleDamgard};
+
 // The MD5 message-digest algorithm takes as input a message of arbitrary length
 // and produces as output a 128-bit "fingerprint" or "message digest" of the input.
 
@@ -14,35 +21,159 @@
 // for review and possible adoption as a standard.
 
 // see more: https://www.ietf.org/rfc/rfc1321.txt
-fn md5(input: Vec<u8>) -> Vec<u8> {
-    // 1. padding
-    let mut output = input.clone();
-    output.push(0x01);
-    while input.len() % 512 != 448 {
-        output.push(0x00);
+
+// MD5 has been broken: Wang et al. found collisions in 2004, and chosen-prefix
+// collisions now take seconds. It remains here for interoperability and
+// cryptanalysis exercises.
+pub type Md5 = MerkleDamgard<Md5Compression>;
+
+pub struct Md5Compression;
+
+impl Compression for Md5Compression {
+    type BlockSize = U64;
+    type OutputSize = U16;
+    type State = [u32; 4];
+
+    const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    const LENGTH_ENDIANNESS: Endianness = Endianness::Little;
+
+    // compress runs four rounds of sixteen operations over the block's words,
+    // each round with its own nonlinear function and message word order.
+    fn compress(state: &mut [u32; 4], block: &GenericArray<u8, U64>) {
+        let mut x = [0u32; 16];
+        for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let [mut a, mut b, mut c, mut d] = *state;
+        for i in 0..64 {
+            let (f, k) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((b & d) | (c & !d), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(T[i])
+                .wrapping_add(x[k])
+                .rotate_left(S[i / 16][i % 4]);
+            (a, b, c, d) = (d, b.wrapping_add(rotated), b, c);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(value);
+        }
     }
 
-    // 2. append length
-    let len: u64 = input.len().try_into().unwrap();
-    let len_bytes = len.to_be_bytes();
+    fn output(state: &[u32; 4]) -> GenericArray<u8, U16> {
+        state.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn state_from_output(output: &GenericArray<u8, U16>) -> Option<[u32; 4]> {
+        let mut state = [0u32; 4];
+        for (word, chunk) in state.iter_mut().zip(output.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
 
-    for i in (0..4).rev() {
-        output.push(len_bytes[i]);
+        Some(state)
     }
+}
+
+// S holds each round's rotation amounts.
+const S: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+// T[i] is the integer part of 2^32 * abs(sin(i + 1)).
+#[rustfmt::skip]
+const T: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee,
+    0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be,
+    0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa,
+    0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+    0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c,
+    0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05,
+    0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039,
+    0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1,
+    0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
 
-    for i in (4..8).rev() {
-        output.push(len_bytes[i]);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        digest::{
+            tests::{check_all_lengths, check_incremental},
+            Digest,
+        },
+        encode::hex::decode,
+    };
+
+    // RFC 1321 appendix A.5 test suite
+    const TEST_SUITE: [(&str, &str); 7] = [
+        ("", "d41d8cd98f00b204e9800998ecf8427e"),
+        ("a", "0cc175b9c0f1b6a831c399e269772661"),
+        ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+        ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            "c3fcd3d76192e4007dfb496cca67e13b",
+        ),
+        (
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            "d174ab98d277d9f5a5611c2c9f419d9f",
+        ),
+        (
+            "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "57edf4a22be3c955ac49da2e2107b67a",
+        ),
+    ];
+
+    #[test]
+    fn test_rfc1321_suite() {
+        for (message, digest) in TEST_SUITE {
+            assert_eq!(Md5::digest(message.as_bytes()).to_vec(), decode(digest));
+        }
     }
 
-    // 3. init buffer
-    let word_a = vec![0x01u8, 0x23u8, 0x45u8, 0x67u8];
-    let word_b = vec![0x89u8, 0xabu8, 0xcdu8, 0xefu8];
-    let word_c = vec![0xfeu8, 0xdcu8, 0xbau8, 0x98u8];
-    let word_d = vec![0x76u8, 0x54u8, 0x32u8, 0x10u8];
-    let buffer = [word_a, word_b, word_c, word_d];
+    #[test]
+    fn test_all_lengths() {
+        check_all_lengths::<Md5>(&decode("a9cf41e638496a4d8f14abe6ae258128"));
+    }
 
-    // 4. process message in 16 word blocks
-    // 5. output
+    #[test]
+    fn test_incremental() {
+        check_incremental::<Md5>();
+    }
 
-    todo!()
+    #[test]
+    fn test_padding_boundaries() {
+        // 55 bytes leave room for the length in the same block, 56 do not
+        assert_eq!(
+            Md5::digest(&[b'a'; 55]).to_vec(),
+            decode("ef1772b6dff9a122358552954ad0df65")
+        );
+        assert_eq!(
+            Md5::digest(&[b'a'; 56]).to_vec(),
+            decode("3b0c8ac703f828b04c6c197006d17218")
+        );
+        assert_eq!(
+            Md5::digest(&[b'a'; 64]).to_vec(),
+            decode("014842d480b571495a4a0363793f7367")
+        );
+    }
 }
@@ -1,9 +1,259 @@
+use std::fmt;
+
+use generic_array::{typenum::Unsigned, ArrayLength, GenericArray};
+
+use crate::digest::{impl_write, Digest, DigestError, Output};
+
+mod md4;
 mod md5;
 mod ripemd;
 mod sha1;
 mod sha2;
 
+pub use md4::*;
 pub use md5::*;
+#[allow(unused_imports)] // ripemd is empty, and exports nothing yet
 pub use ripemd::*;
 pub use sha1::*;
 pub use sha2::*;
+
+// The Merkle–Damgård construction builds a hash function from a compression
+// function, which maps a chaining state and a fixed size message block to a
+// new chaining state. The message is padded with a 1 bit, zeros, and its
+// length in bits, so that it fills a whole number of blocks:
+// H_0 = IV
+// H_i = compress(H_{i-1}, M_i)
+// digest = H_n
+// If the compression function is collision resistant, so is the hash.
+
+// The digest is the final chaining state, so anyone who knows H(M) and the
+// length of M can continue hashing from it, and compute H(M || padding || M')
+// without knowing M. This is the length extension property, which makes
+// H(key || message) an insecure MAC. MerkleDamgard exposes its chaining state
+// and can be resumed from one, so such attacks can be written against any
+// hash built on it (see attack::length_extension).
+
+/// `Compression` is a trait representing the compression function of a
+/// Merkle–Damgård hash, along with the parameters the construction needs:
+/// the block size, the chaining state and its initial value, and how the
+/// message length is encoded by padding.
+///
+/// Implementing `Compression` is all it takes to add a hash:
+/// `MerkleDamgard<C>` provides buffering, padding and `Digest`.
+pub trait Compression {
+    /// The size of the blocks the compression function takes.
+    type BlockSize: ArrayLength<u8>;
+    /// The size of the digest produced from the final chaining state.
+    type OutputSize: ArrayLength<u8>;
+    /// The chaining state carried between blocks.
+    type State: Copy + fmt::Debug;
+
+    /// The initial chaining state (IV).
+    const INITIAL_STATE: Self::State;
+    /// The byte order of the message length appended by padding.
+    const LENGTH_ENDIANNESS: Endianness;
+    /// The size of the message length appended by padding, in bytes.
+    const LENGTH_SIZE: usize = 8;
+
+    fn compress(state: &mut Self::State, block: &GenericArray<u8, Self::BlockSize>);
+    fn output(state: &Self::State) -> GenericArray<u8, Self::OutputSize>;
+
+    /// Recovers the chaining state from a digest. Hashes whose digest is
+    /// truncated from the state return `None`.
+    fn state_from_output(_output: &GenericArray<u8, Self::OutputSize>) -> Option<Self::State> {
+        None
+    }
+}
+
+/// The byte order of a Merkle–Damgård hash's words and length encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// `MerkleDamgard` hashes messages of any length with a compression function,
+/// buffering partial blocks between updates.
+pub struct MerkleDamgard<C: Compression> {
+    state: C::State,
+    buffer: GenericArray<u8, C::BlockSize>,
+    buffer_len: usize,
+    len: u128, // bytes absorbed
+}
+
+impl<C: Compression> MerkleDamgard<C> {
+    /// Resumes hashing from a chaining state, as if `len` bytes had already
+    /// been absorbed. `len` must be a whole number of blocks, since the
+    /// chaining state only changes at block boundaries.
+    pub fn from_state(state: C::State, len: u128) -> Result<Self, DigestError> {
+        if len % C::BlockSize::USIZE as u128 != 0 {
+            return Err(DigestError::UnalignedLength(len));
+        }
+
+        Ok(MerkleDamgard {
+            state,
+            len,
+            ..Self::default()
+        })
+    }
+
+    /// Resumes hashing from a digest of a message, as if the message and its
+    /// padding had been absorbed. `len` is the padded message length.
+    pub fn from_digest(
+        digest: &GenericArray<u8, C::OutputSize>,
+        len: u128,
+    ) -> Result<Self, DigestError> {
+        let state = C::state_from_output(digest).ok_or(DigestError::TruncatedOutput)?;
+
+        Self::from_state(state, len)
+    }
+
+    /// Returns the chaining state after the last complete block.
+    pub fn state(&self) -> C::State {
+        self.state
+    }
+
+    /// Returns the number of bytes compressed into the chaining state, which
+    /// excludes a buffered partial block.
+    pub fn compressed_len(&self) -> u128 {
+        self.len - self.buffer_len as u128
+    }
+
+    /// Returns the padding appended to a message of `len` bytes: 0x80, zeros
+    /// up to the last LENGTH_SIZE bytes of a block, and the message length in
+    /// bits modulo 2^(8 * LENGTH_SIZE).
+    pub fn padding(len: u128) -> Vec<u8> {
+        let block_size = C::BlockSize::USIZE;
+        let length_start = block_size - C::LENGTH_SIZE;
+
+        let mut padding = vec![0x80];
+        let used = (len % block_size as u128) as usize + 1;
+        let zeros = (length_start + block_size - used) % block_size;
+        padding.resize(1 + zeros, 0);
+
+        let bit_len = len.wrapping_mul(8);
+        match C::LENGTH_ENDIANNESS {
+            Endianness::Big => {
+                padding.extend_from_slice(&bit_len.to_be_bytes()[16 - C::LENGTH_SIZE..])
+            }
+            Endianness::Little => {
+                padding.extend_from_slice(&bit_len.to_le_bytes()[..C::LENGTH_SIZE])
+            }
+        }
+
+        padding
+    }
+
+    fn process_buffer(&mut self) {
+        C::compress(&mut self.state, &self.buffer);
+        self.buffer_len = 0;
+    }
+}
+
+impl<C: Compression> Default for MerkleDamgard<C> {
+    fn default() -> Self {
+        MerkleDamgard {
+            state: C::INITIAL_STATE,
+            buffer: GenericArray::default(),
+            buffer_len: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<C: Compression> Clone for MerkleDamgard<C> {
+    fn clone(&self) -> Self {
+        MerkleDamgard {
+            state: self.state,
+            buffer: self.buffer.clone(),
+            buffer_len: self.buffer_len,
+            len: self.len,
+        }
+    }
+}
+
+impl<C: Compression> Digest for MerkleDamgard<C> {
+    type OutputSize = C::OutputSize;
+    type BlockSize = C::BlockSize;
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u128);
+
+        let block_size = self.buffer.len();
+        while !data.is_empty() {
+            let taken = data.len().min(block_size - self.buffer_len);
+            self.buffer[self.buffer_len..self.buffer_len + taken].copy_from_slice(&data[..taken]);
+            self.buffer_len += taken;
+            data = &data[taken..];
+
+            if self.buffer_len == block_size {
+                self.process_buffer();
+            }
+        }
+    }
+
+    fn finalize(mut self) -> Output<Self> {
+        let padding = Self::padding(self.len);
+        self.update(&padding);
+
+        C::output(&self.state)
+    }
+}
+
+impl_write!(impl<C: Compression> MerkleDamgard<C>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding() {
+        // 55 bytes leave room for the 8 byte length in the same block, 56 do not
+        assert_eq!(Md5::padding(55), [0x80, 0xb8, 0x01, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(Md5::padding(56).len(), 72);
+        assert_eq!(Md5::padding(64).len(), 64);
+        assert_eq!(Sha1::padding(3)[53..], [0, 0, 0, 0, 0, 0, 0, 0x18]);
+
+        // SHA-512 encodes a 16 byte length in 128 byte blocks
+        let padding = Sha512::padding(3);
+        assert_eq!(padding.len(), 125);
+        assert_eq!(padding[109..], [[0; 15].as_slice(), &[0x18]].concat());
+    }
+
+    #[test]
+    fn test_resume_from_state() {
+        let message = (0..200).collect::<Vec<u8>>();
+
+        let mut sha1 = Sha1::new();
+        sha1.update(&message[..100]);
+        assert_eq!(sha1.compressed_len(), 64);
+
+        let mut resumed = Sha1::from_state(sha1.state(), sha1.compressed_len()).unwrap();
+        resumed.update(&message[64..]);
+        assert_eq!(resumed.finalize(), Sha1::digest(&message));
+
+        assert_eq!(
+            Sha1::from_state(Sha1Compression::INITIAL_STATE, 100).err(),
+            Some(DigestError::UnalignedLength(100))
+        );
+    }
+
+    #[test]
+    fn test_resume_from_digest() {
+        // resuming from H(M) continues from the padded message M || padding
+        let message = b"message";
+        let mut padded = message.to_vec();
+        padded.extend(Md4::padding(message.len() as u128));
+
+        let mut resumed = Md4::from_digest(&Md4::digest(message), padded.len() as u128).unwrap();
+        resumed.update(b"extension");
+        padded.extend(b"extension");
+        assert_eq!(resumed.finalize(), Md4::digest(&padded));
+
+        // truncated digests do not determine the state
+        assert_eq!(
+            Sha224::from_digest(&Sha224::digest(message), 64).err(),
+            Some(DigestError::TruncatedOutput)
+        );
+    }
+}
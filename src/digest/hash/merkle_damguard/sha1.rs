@@ -1,5 +1,12 @@
+use generic_array::{
+    typenum::{U20, U64},
+    GenericArray,
+};
+
+use super::{Compression, Endianness, MerkleDamgard};
+
 // SHA-1, is a secure hashing algorithm for computing a condensed representation
-// of a message or a data file. When a message of any length < 2Mbits is input,
+// of a message or a data file. When a message of any length < 2^64 bits is input,
 // the SHA-1 produces a 160-bit output called a message digest.
 
 // SHA-1 is a technical revision of SHA (FIPS 180). A circular left shift operation
@@ -9,6 +16,174 @@
 // similar to those used by Professor Ronald L. Rivest of MIT when designing the
 // MD4 message digest algorithm1, and is closely modelled after that algorithm.
 
-fn sha1(input: Vec<u8>) -> Vec<u8> {
-    todo!()
+// SHA-1 has been broken: the SHAttered attack (Stevens et al., 2017) found
+// the first collision, and chosen-prefix collisions followed in 2020. It
+// remains here for interoperability and cryptanalysis exercises.
+pub type Sha1 = MerkleDamgard<Sha1Compression>;
+
+pub struct Sha1Compression;
+
+impl Compression for Sha1Compression {
+    type BlockSize = U64;
+    type OutputSize = U20;
+    type State = [u32; 5];
+
+    const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    const LENGTH_ENDIANNESS: Endianness = Endianness::Big;
+
+    // compress expands the block's 16 words into an 80 word message schedule,
+    // W_t = ROTL^1(W_{t-3} XOR W_{t-8} XOR W_{t-14} XOR W_{t-16}), and runs
+    // four rounds of twenty steps over it. The rotation in the schedule is
+    // what distinguishes SHA-1 from the original SHA.
+    fn compress(state: &mut [u32; 5], block: &GenericArray<u8, U64>) {
+        let mut w = [0u32; 80];
+        for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        for t in 16..80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        for (t, &word) in w.iter().enumerate() {
+            let (f, k) = match t / 20 {
+                0 => ((b & c) | (!b & d), 0x5a827999),
+                1 => (b ^ c ^ d, 0x6ed9eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            (a, b, c, d, e) = (temp, a, b.rotate_left(30), c, d);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    fn output(state: &[u32; 5]) -> GenericArray<u8, U20> {
+        state.iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+
+    fn state_from_output(output: &GenericArray<u8, U20>) -> Option<[u32; 5]> {
+        let mut state = [0u32; 5];
+        for (word, chunk) in state.iter_mut().zip(output.chunks_exact(4)) {
+            // SAFETY: chunks_exact(4)
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        digest::{
+            tests::{check_incremental, check_monte_carlo, check_shavs},
+            Digest,
+        },
+        encode::hex::decode,
+    };
+
+    // FIPS 180 example messages
+    const TEST_VECTORS: [(&str, &str); 4] = [
+        ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        ("abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        ),
+        (
+            "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+            "a49b2446a02c645bf419f995b67091253a04a259",
+        ),
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for (message, digest) in TEST_VECTORS {
+            assert_eq!(Sha1::digest(message.as_bytes()).to_vec(), decode(digest));
+        }
+    }
+
+    #[test]
+    fn test_million_a() {
+        let mut sha1 = Sha1::new();
+        for _ in 0..1000 {
+            sha1.update(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            sha1.finalize().to_vec(),
+            decode("34aa973cd4c4daa4f61eeb2bdbad27316534016f")
+        );
+    }
+
+    // NIST SHAVS SHA1ShortMsg.rsp at Len = 0, 8, 440, 448 and 512, around the
+    // padding boundary, and the first message of SHA1LongMsg.rsp
+    #[test]
+    fn test_shavs() {
+        check_shavs::<Sha1>(&[
+            ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            ("36", "c1dfd96eea8cc2b62785275bca38ac261256e278"),
+            (
+                concat!(
+                    "ec6b4a88713df27c0f2d02e738b69db43abda3921317259c864c1c386e9a5a3f",
+                    "533dc05f3beeb2bec2aac8e06db4c6cb3cddcf697e03d5",
+                ),
+                "a7272e2308622ff7a339460adc61efd0ea8dabdc",
+            ),
+            (
+                concat!(
+                    "0321736beba578e90abc1a90aa56157d871618f6de0d764cc8c91e06c68ecd3b",
+                    "9de3824064503384db67beb7fe012232dacaef93a000fba7",
+                ),
+                "aef843b86916c16f66c84d83a6005d23fd005c9e",
+            ),
+            (
+                concat!(
+                    "45927e32ddf801caf35e18e7b5078b7f5435278212ec6bb99df884f49b327c64",
+                    "86feae46ba187dc1cc9145121e1492e6b06e9007394dc33b7748f86ac3207cfe",
+                ),
+                "a70cfbfe7563dd0e665c7c6715a96a8d756950c0",
+            ),
+            (
+                concat!(
+                    "7c9c67323a1df1adbfe5ceb415eaef0155ece2820f4d50c1ec22cba4928ac656",
+                    "c83fe585db6a78ce40bc42757aba7e5a3f582428d6ca68d0c3978336a6efb729",
+                    "613e8d9979016204bfd921322fdd5222183554447de5e6e9bbe6edf76d7b71e1",
+                    "8dc2e8d6dc89b7398364f652fafc734329aafa3dcd45d4f31e388e4fafd7fc64",
+                    "95f37ca5cbab7f54d586463da4bfeaa3bae09f7b8e9239d832b4f0a733aa609c",
+                    "c1f8d4",
+                ),
+                "d8fd6a91ef3b6ced05b98358a99107c1fac8c807",
+            ),
+        ]);
+    }
+
+    // NIST SHAVS SHA1Monte.rsp
+    #[test]
+    fn test_monte_carlo() {
+        check_monte_carlo::<Sha1>(
+            "dd4df644eaf3d85bace2b21accaa22b28821f5cd",
+            &[
+                (0, "11f5c38b4479d4ad55cb69fadf62de0b036d5163"),
+                (1, "5c26de848c21586bec36995809cb02d3677423d9"),
+                (99, "01b7be5b70ef64843a03fdbb3b247a6278d2cbe1"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        check_incremental::<Sha1>();
+    }
 }
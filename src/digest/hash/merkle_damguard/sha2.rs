@@ -0,0 +1,963 @@
+use generic_array::{
+    typenum::{Unsigned, U128, U28, U32, U48, U64},
+    GenericArray,
+};
+
+use super::{Compression, Endianness, MerkleDamgard};
+
+// SHA-2 is the family of hash functions defined in FIPS 180-4, in two sizes:
+// SHA-256 works on 32 bit words and 512 bit blocks, and SHA-512 on 64 bit
+// words and 1024 bit blocks with a 128 bit message length. Both expand each
+// block into a message schedule and run it through rounds of
+// T_1 = h + Sigma_1(e) + Ch(e, f, g) + K_t + W_t
+// T_2 = Sigma_0(a) + Maj(a, b, c)
+// (a, ..., h) = (T_1 + T_2, a, b, c, d + T_1, e, f, g)
+// where the round constants K are the first bits of the fractional parts of
+// the cube roots of the first primes.
+
+// SHA-224 and SHA-384 are SHA-256 and SHA-512 with different initial states,
+// truncated. SHA-512/224 and SHA-512/256 likewise truncate SHA-512, and are
+// faster than SHA-224 and SHA-256 on 64 bit platforms.
+
+// Truncation also stops length extension attacks, since the final chaining
+// state is no longer part of the digest. SHA-256 and SHA-512 themselves are
+// vulnerable, like every untruncated Merkle–Damgård hash.
+
+// sha2 defines a SHA-2 variant's compression function and hash.
+macro_rules! sha2 {
+    (
+        $hash:ident,
+        $compression:ident,
+        $output_size:ty,
+        $initial_state:ident,
+        $compress:ident,
+        $block_size:ty,
+        $word:ty,
+        $length_size:literal
+    ) => {
+        pub type $hash = MerkleDamgard<$compression>;
+
+        pub struct $compression;
+
+        impl Compression for $compression {
+            type BlockSize = $block_size;
+            type OutputSize = $output_size;
+            type State = [$word; 8];
+
+            const INITIAL_STATE: [$word; 8] = $initial_state;
+            const LENGTH_ENDIANNESS: Endianness = Endianness::Big;
+            const LENGTH_SIZE: usize = $length_size;
+
+            fn compress(state: &mut [$word; 8], block: &GenericArray<u8, $block_size>) {
+                $compress(state, block)
+            }
+
+            fn output(state: &Self::State) -> GenericArray<u8, $output_size> {
+                state
+                    .iter()
+                    .flat_map(|word| word.to_be_bytes())
+                    .take(<$output_size>::USIZE)
+                    .collect()
+            }
+
+            // only SHA-256 and SHA-512 output their whole state
+            fn state_from_output(output: &GenericArray<u8, $output_size>) -> Option<[$word; 8]> {
+                const WORD_SIZE: usize = core::mem::size_of::<$word>();
+                if output.len() != 8 * WORD_SIZE {
+                    return None;
+                }
+
+                let mut state = [0; 8];
+                for (word, chunk) in state.iter_mut().zip(output.chunks_exact(WORD_SIZE)) {
+                    // SAFETY: chunks_exact(WORD_SIZE)
+                    *word = <$word>::from_be_bytes(chunk.try_into().unwrap());
+                }
+
+                Some(state)
+            }
+        }
+    };
+}
+
+sha2!(
+    Sha224,
+    Sha224Compression,
+    U28,
+    H224,
+    compress_256,
+    U64,
+    u32,
+    8
+);
+sha2!(
+    Sha256,
+    Sha256Compression,
+    U32,
+    H256,
+    compress_256,
+    U64,
+    u32,
+    8
+);
+sha2!(
+    Sha384,
+    Sha384Compression,
+    U48,
+    H384,
+    compress_512,
+    U128,
+    u64,
+    16
+);
+sha2!(
+    Sha512,
+    Sha512Compression,
+    U64,
+    H512,
+    compress_512,
+    U128,
+    u64,
+    16
+);
+sha2!(
+    Sha512_224,
+    Sha512_224Compression,
+    U28,
+    H512_224,
+    compress_512,
+    U128,
+    u64,
+    16
+);
+sha2!(
+    Sha512_256,
+    Sha512_256Compression,
+    U32,
+    H512_256,
+    compress_512,
+    U128,
+    u64,
+    16
+);
+
+// compress_256 is the SHA-224 and SHA-256 compression function.
+fn compress_256(state: &mut [u32; 8], block: &GenericArray<u8, U64>) {
+    let mut w = [0u32; 64];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        // SAFETY: chunks_exact(4)
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for t in 16..64 {
+        let sigma_0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let sigma_1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = w[t - 16]
+            .wrapping_add(sigma_0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(sigma_1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (&k, &word) in K256.iter().zip(w.iter()) {
+        let big_sigma_1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t_1 = h
+            .wrapping_add(big_sigma_1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(word);
+
+        let big_sigma_0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t_2 = big_sigma_0.wrapping_add(maj);
+
+        (a, b, c, d, e, f, g, h) = (t_1.wrapping_add(t_2), a, b, c, d.wrapping_add(t_1), e, f, g);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+// compress_512 is the compression function of SHA-384, SHA-512 and SHA-512/t.
+fn compress_512(state: &mut [u64; 8], block: &GenericArray<u8, U128>) {
+    let mut w = [0u64; 80];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+        // SAFETY: chunks_exact(8)
+        *word = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for t in 16..80 {
+        let sigma_0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+        let sigma_1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+        w[t] = w[t - 16]
+            .wrapping_add(sigma_0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(sigma_1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (&k, &word) in K512.iter().zip(w.iter()) {
+        let big_sigma_1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t_1 = h
+            .wrapping_add(big_sigma_1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(word);
+
+        let big_sigma_0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t_2 = big_sigma_0.wrapping_add(maj);
+
+        (a, b, c, d, e, f, g, h) = (t_1.wrapping_add(t_2), a, b, c, d.wrapping_add(t_1), e, f, g);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+// The initial states are the first bits of the fractional parts of the square
+// roots of the first eight primes (SHA-256, SHA-512) and of the ninth through
+// sixteenth primes (SHA-224, SHA-384). SHA-512/t's initial states are
+// generated by hashing "SHA-512/t" with a modified SHA-512 (FIPS 180-4 5.3.6.2).
+const H224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+const H256: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const H384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+const H512: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const H512_224: [u64; 8] = [
+    0x8c3d37c819544da2,
+    0x73e1996689dcd4d6,
+    0x1dfab7ae32ff9c82,
+    0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8,
+    0x77e36f7304c48942,
+    0x3f9d85a86a1d36c8,
+    0x1112e6ad91d692a1,
+];
+
+const H512_256: [u64; 8] = [
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2,
+];
+
+#[rustfmt::skip]
+const K256: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[rustfmt::skip]
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        digest::{
+            tests::{check_incremental, check_monte_carlo, check_shavs},
+            Digest,
+        },
+        encode::hex::decode,
+    };
+
+    // FIPS 180 example messages: the one block message "abc", and two block
+    // messages for 512 and 1024 bit blocks
+    const ABC: &str = "abc";
+    const TWO_BLOCKS_256: &str = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    const TWO_BLOCKS_512: &str = "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    fn check<D: Digest>(vectors: &[(&str, &str)]) {
+        for (message, digest) in vectors {
+            assert_eq!(
+                D::digest(message.as_bytes()).to_vec(),
+                decode(digest),
+                "{:?}",
+                message
+            );
+        }
+    }
+
+    // check_million_a checks the digest of one million repetitions of 'a'.
+    fn check_million_a<D: Digest>(digest: &str) {
+        let mut hasher = D::new();
+        for _ in 0..1000 {
+            hasher.update(&[b'a'; 1000]);
+        }
+        assert_eq!(hasher.finalize().to_vec(), decode(digest));
+    }
+
+    #[test]
+    fn test_sha224() {
+        check::<Sha224>(&[
+            (
+                "",
+                "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            ),
+            (
+                ABC,
+                "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            ),
+            (
+                TWO_BLOCKS_256,
+                "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            ),
+        ]);
+        check_million_a::<Sha224>("20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67");
+    }
+
+    #[test]
+    fn test_sha256() {
+        check::<Sha256>(&[
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                ABC,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                TWO_BLOCKS_256,
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                TWO_BLOCKS_512,
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            ),
+        ]);
+        check_million_a::<Sha256>(
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
+        );
+    }
+
+    #[test]
+    fn test_sha384() {
+        check::<Sha384>(&[
+            ("", "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"),
+            (ABC, "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"),
+            (TWO_BLOCKS_512, "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"),
+        ]);
+        check_million_a::<Sha384>("9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985");
+    }
+
+    #[test]
+    fn test_sha512() {
+        check::<Sha512>(&[
+            ("", "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"),
+            (ABC, "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+            (TWO_BLOCKS_512, "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"),
+        ]);
+        check_million_a::<Sha512>("e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b");
+    }
+
+    #[test]
+    fn test_sha512_t() {
+        check::<Sha512_224>(&[
+            (
+                ABC,
+                "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+            ),
+            (
+                TWO_BLOCKS_512,
+                "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9",
+            ),
+        ]);
+        check::<Sha512_256>(&[
+            (
+                ABC,
+                "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+            ),
+            (
+                TWO_BLOCKS_512,
+                "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a",
+            ),
+        ]);
+    }
+
+    // SHA-512/t's initial state is SHA-512("SHA-512/t"), computed from SHA-512's
+    // initial state XORed with a5a5a5a5a5a5a5a5 (FIPS 180-4 5.3.6)
+    struct Sha512IvGeneration;
+
+    impl Compression for Sha512IvGeneration {
+        type BlockSize = U128;
+        type OutputSize = U64;
+        type State = [u64; 8];
+
+        const INITIAL_STATE: [u64; 8] = {
+            let mut state = H512;
+            let mut i = 0;
+            while i < 8 {
+                state[i] ^= 0xa5a5a5a5a5a5a5a5;
+                i += 1;
+            }
+            state
+        };
+        const LENGTH_ENDIANNESS: Endianness = Endianness::Big;
+        const LENGTH_SIZE: usize = 16;
+
+        fn compress(state: &mut [u64; 8], block: &GenericArray<u8, U128>) {
+            compress_512(state, block)
+        }
+
+        fn output(state: &[u64; 8]) -> GenericArray<u8, U64> {
+            state.iter().flat_map(|word| word.to_be_bytes()).collect()
+        }
+    }
+
+    #[test]
+    fn test_sha512_t_initial_states() {
+        for (name, initial_state) in [("SHA-512/224", H512_224), ("SHA-512/256", H512_256)] {
+            let digest = MerkleDamgard::<Sha512IvGeneration>::digest(name.as_bytes());
+            let expected = initial_state
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<u8>>();
+            assert_eq!(digest.to_vec(), expected);
+        }
+    }
+
+    // NIST SHAVS ShortMsg response files at Len = 0 and 8 and around the
+    // padding boundary (440, 448 and 512 for 512 bit blocks, 888, 896 and
+    // 1024 for 1024 bit blocks), and the first message of the LongMsg files
+    #[test]
+    fn test_shavs() {
+        check_shavs::<Sha224>(&[
+            (
+                "",
+                "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            ),
+            (
+                "84",
+                "3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a",
+            ),
+            (
+                concat!(
+                    "445e8698eeb8accbaac4ffa7d934fffd16014a430ef70f3a9174c6cfe96d1e3f",
+                    "6ab1377f4a7212dbb30146dd17d9f470c4dffc45b8e871",
+                ),
+                "4c7ae028c0fe61f2a9cada61fae30685b77f04c6442576e912af9fa6",
+            ),
+            (
+                concat!(
+                    "52839f2f0853a30df14ec897a1914c685c1ac21470d00654c8c37663bfb65fa7",
+                    "32dbb694d9dd09ced723b48d8f545846ba168988b61cc724",
+                ),
+                "2f755a57674b49d5c25cb37348f35b6fd2de2552c749f2645ba63d20",
+            ),
+            (
+                concat!(
+                    "a3310ba064be2e14ad32276e18cd0310c933a6e650c3c754d0243c6c61207865",
+                    "b4b65248f66a08edf6e0832689a9dc3a2e5d2095eeea50bd862bac88c8bd318d",
+                ),
+                "b2a5586d9cbf0baa999157b4af06d88ae08d7c9faab4bc1a96829d65",
+            ),
+            (
+                concat!(
+                    "f149e41d848f59276cfddd743bafa9a90e1ee4a263a118142b33e3702176ef0a",
+                    "59f8237a1cb51b42f3ded6b202d9af0997898fdd03cf60bda951c514547a0850",
+                    "cec25444ae2f24cb711bfbafcc3956c941d3de69f155e3f8b10f06db5f37359b",
+                    "772ddd43e1035a0a0d3db33242d5843033833b0dd43b870c6bf60e8deab55f31",
+                    "7cc3273f5e3ba747f0cb65050cb7228796210d9254873643008d45f29cfd6c5b",
+                    "060c9a",
+                ),
+                "9db6dc3a23abd7b6c3d72c38f4843c7de48a71d0ba91a86b18393e5f",
+            ),
+        ]);
+        check_shavs::<Sha256>(&[
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "d3",
+                "28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1",
+            ),
+            (
+                concat!(
+                    "3ebfb06db8c38d5ba037f1363e118550aad94606e26835a01af05078533cc25f",
+                    "2f39573c04b632f62f68c294ab31f2a3e2a1a0d8c2be51",
+                ),
+                "6595a2ef537a69ba8583dfbf7f5bec0ab1f93ce4c8ee1916eff44a93af5749c4",
+            ),
+            (
+                concat!(
+                    "2d52447d1244d2ebc28650e7b05654bad35b3a68eedc7f8515306b496d75f3e7",
+                    "3385dd1b002625024b81a02f2fd6dffb6e6d561cb7d0bd7a",
+                ),
+                "cfb88d6faf2de3a69d36195acec2e255e2af2b7d933997f348e09f6ce5758360",
+            ),
+            (
+                concat!(
+                    "5a86b737eaea8ee976a0a24da63e7ed7eefad18a101c1211e2b3650c5187c2a8",
+                    "a650547208251f6d4237e661c7bf4c77f335390394c37fa1a9f9be836ac28509",
+                ),
+                "42e61e174fbb3897d6dd6cef3dd2802fe67b331953b06114a65c772859dfc1aa",
+            ),
+            (
+                concat!(
+                    "451101250ec6f26652249d59dc974b7361d571a8101cdfd36aba3b5854d3ae08",
+                    "6b5fdd4597721b66e3c0dc5d8c606d9657d0e323283a5217d1f53f2f284f57b8",
+                    "5c8a61ac8924711f895c5ed90ef17745ed2d728abd22a5f7a13479a462d71b56",
+                    "c19a74a40b655c58edfe0a188ad2cf46cbf30524f65d423c837dd1ff2bf462ac",
+                    "4198007345bb44dbb7b1c861298cdf61982a833afc728fae1eda2f87aa2c9480",
+                    "858bec",
+                ),
+                "3c593aa539fdcdae516cdf2f15000f6634185c88f505b39775fb9ab137a10aa2",
+            ),
+        ]);
+        check_shavs::<Sha384>(&[
+            (
+                "",
+                concat!(
+                    "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da",
+                    "274edebfe76f65fbd51ad2f14898b95b",
+                ),
+            ),
+            (
+                "c5",
+                concat!(
+                    "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860",
+                    "ba432edfd86b4cb1cb8a75b46076e3b1",
+                ),
+            ),
+            (
+                concat!(
+                    "a04f390a9cc2effad05db80d9076a8d4b6cc8bba97b27b423670b290b8e69c2b",
+                    "187230011c1481ac88d090f39154659494db5e410851c6e8b2b8a93717cae760",
+                    "37e0881978124fe7e1a0929d8891491f4e99646cc94062dc82411fa66130eda4",
+                    "6560e75b98048236439465125e737b",
+                ),
+                concat!(
+                    "e7089d72945cef851e689b4409cfb63d135f0b5cdfb0dac6c3a292dd70371ab4",
+                    "b79da1997d7992906ac7213502662920",
+                ),
+            ),
+            (
+                concat!(
+                    "f419494c3c6d0727b3395a483a2167182a7252f4fd099c2d4b71b053f94bb8b3",
+                    "adf3b51e8460cfec084ce9415c95798fbae4975c208c544645b54c44d2b97f2e",
+                    "cfce5c805be61f5ba1d35dcc07afdd51a87baa990506668cf710e18be9b0ebf9",
+                    "43f366fa29c69f7a6616de72a3353b66",
+                ),
+                concat!(
+                    "aead8688c58c6ba4e9cadb4756b465dce0fb06f1cfaa478197f2ea89414e47e9",
+                    "572034adfed160703c79b82b3fd7ab78",
+                ),
+            ),
+            (
+                concat!(
+                    "3bf52cc5ee86b9a0190f390a5c0366a560b557000dbe5115fd9ee11630a62769",
+                    "011575f15881198f227876e8fe685a6939bc8b89fd48a34ec5e71e131462b288",
+                    "6794dffa68ccc6d564733e67ffef25e627c6f4b5460796e3bce67bf58ca6e8e5",
+                    "55bc916a8531697ac948b90dc8616f25101db90b50c3d3dbc9e21e42ff387187",
+                ),
+                concat!(
+                    "12b6cb35eda92ee37356ddee77781a17b3d90e563824a984faffc6fdd1693bd7",
+                    "626039635563cfc3b9a2b00f9c65eefd",
+                ),
+            ),
+            (
+                concat!(
+                    "62c6a169b9be02b3d7b471a964fc0bcc72b480d26aecb2ed460b7f50016ddaf0",
+                    "4c51218783f3aadfdff5a04ded030d7b3fb7376b61ba30b90e2da921a4470740",
+                    "d63fb99fa16cc8ed81abaf8ce4016e50df81da832070372c24a80890aa3a26fa",
+                    "675710b8fb718266249d496f313c55d0bada101f8f56eeccee4345a8f98f60a3",
+                    "6662cfda794900d12f9414fcbdfdeb85388a814996b47e24d5c8086e7a8edcc5",
+                    "3d299d0d033e6bb60c58b83d6e8b57f6c258d6081dd10eb942fdf8ec157ec3e7",
+                    "5371235a8196eb9d22b1de3a2d30c2abbe0db7650cf6c7159bacbe29b3a93c92",
+                    "100508",
+                ),
+                concat!(
+                    "0730e184e7795575569f87030260bb8e54498e0e5d096b18285e988d245b6f34",
+                    "86d1f2447d5f85bcbe59d5689fc49425",
+                ),
+            ),
+        ]);
+        check_shavs::<Sha512>(&[
+            (
+                "",
+                concat!(
+                    "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce",
+                    "47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+                ),
+            ),
+            (
+                "21",
+                concat!(
+                    "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee2388",
+                    "9f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a",
+                ),
+            ),
+            (
+                concat!(
+                    "324533e685f1852e358eea8ea8b81c288b3f3beb1f2bc2b8d3fdbac318382e3d",
+                    "7120de30c9c237aa0a34831deb1e5e060a7969cd3a9742ec1e64b354f7eb290c",
+                    "ba1c681c66cc7ea994fdf5614f604d1a2718aab581c1c94931b1387e4b7dc736",
+                    "35bf3a7301174075fa70a9227d85d3",
+                ),
+                concat!(
+                    "3b26c5170729d0814153becb95f1b65cd42f9a6d0649d914e4f69d938b5e9dc0",
+                    "41cd0f5c8da0b484d7c7bc7b1bdefb08fe8b1bfedc81109345bc9e9a399feedf",
+                ),
+            ),
+            (
+                concat!(
+                    "518985977ee21d2bf622a20567124fcbf11c72df805365835ab3c041f4a9cd8a",
+                    "0ad63c9dee1018aa21a9fa3720f47dc48006f1aa3dba544950f87e627f369bc2",
+                    "793ede21223274492cceb77be7eea50e5a509059929a16d33a9f54796cde5770",
+                    "c74bd3ecc25318503f1a41976407aff2",
+                ),
+                concat!(
+                    "c00926a374cde55b8fbd77f50da1363da19744d3f464e07ce31794c5a61b6f9c",
+                    "85689fa1cfe136553527fd876be91673c2cac2dd157b2defea360851b6d92cf4",
+                ),
+            ),
+            (
+                concat!(
+                    "fd2203e467574e834ab07c9097ae164532f24be1eb5d88f1af7748ceff0d2c67",
+                    "a21f4e4097f9d3bb4e9fbf97186e0db6db0100230a52b453d421f8ab9c9a6043",
+                    "aa3295ea20d2f06a2f37470d8a99075f1b8a8336f6228cf08b5942fc1fb4299c",
+                    "7d2480e8e82bce175540bdfad7752bc95b577f229515394f3ae5cec870a4b2f8",
+                ),
+                concat!(
+                    "a21b1077d52b27ac545af63b32746c6e3c51cb0cb9f281eb9f3580a6d4996d5c",
+                    "9917d2a6e484627a9d5a06fa1b25327a9d710e027387fc3e07d7c4d14c6086cc",
+                ),
+            ),
+            (
+                concat!(
+                    "4f05600950664d5190a2ebc29c9edb89c20079a4d3e6bc3b27d75e34e2fa3d02",
+                    "768502bd69790078598d5fcf3d6779bfed1284bbe5ad72fb456015181d9587d6",
+                    "e864c940564eaafb4f2fead4346ea09b6877d9340f6b82eb1515880872213da3",
+                    "ad88feba9f4f13817a71d6f90a1a17c43a15c038d988b5b29edffe2d6a062813",
+                    "cedbe852cde302b3e33b696846d2a8e36bd680efcc6cd3f9e9a4c1ae8cac10cc",
+                    "5244d131677140399176ed46700019a004a163806f7fa467fc4e17b4617bbd76",
+                    "41aaff7ff56396ba8c08a8be100b33a20b5daf134a2aefa5e1c3496770dcf6ba",
+                    "a4f7bb",
+                ),
+                concat!(
+                    "a9db490c708cc72548d78635aa7da79bb253f945d710e5cb677a474efc7c65a2",
+                    "aab45bc7ca1113c8ce0f3c32e1399de9c459535e8816521ab714b2a6cd200525",
+                ),
+            ),
+        ]);
+        check_shavs::<Sha512_224>(&[
+            (
+                "",
+                "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4",
+            ),
+            (
+                "cf",
+                "4199239e87d47b6feda016802bf367fb6e8b5655eff6225cb2668f4a",
+            ),
+            (
+                concat!(
+                    "15cb777ef3e451b928dbf288e46a3627044ff5de42add884a9af6b424d6e7399",
+                    "381581a6a743c7a577b02bb5da149ada4e449f48d09e34df4ca8d8f259f4e14c",
+                    "23471475a8f97331289f564ad6e8bd8fd4c5e51d5ecd19dd46dfcb4ea009e385",
+                    "bea857725fd1fc6423f09ccf42af48",
+                ),
+                "9fa12561f1df9a2d793292e0f4df5327af529336b5b2118952f5c24e",
+            ),
+            (
+                concat!(
+                    "d24df75a00cf92677bb41a620fae519723937ebfe1f7b430970056505d76db4f",
+                    "f91acf16ff391a7a3d8085b655127a18acd80bfa831837f4644a6850c0273fbe",
+                    "d6029449d65bb98a47b2ff1ca6997c50500d0b21a206936a5e4d8d56508ec018",
+                    "32ae4fddce5ef6ff62f1917c486adea6",
+                ),
+                "5cae12ea9652269ea2aafc656cb83424746ea1d5d491f9a159594b2a",
+            ),
+            (
+                concat!(
+                    "9e127870be2431bcb4f4eb4efd5c2a6c5870c55e7a5e3b7503994a4cb136be4e",
+                    "d396887801450f600b22cb772fc00f8b8f0d2690e231a29f69b9f13f24f531e4",
+                    "479e45b5e8bc2992fac782567e0d7a59f853ca3a20bf18dbdbf684ac69817e2d",
+                    "e075daaed9532659692d3b73530a12df7b8cd9e49ed0463041962c1ce7a24c31",
+                ),
+                "7e2cf6226623535784c59cd6a7b27dac60ee23fdce8a804dbd6dfedd",
+            ),
+            (
+                concat!(
+                    "9625ae618ea633fd7ae5b20ceafd6b1f3ab1a6aa20aded66810e78f38925e9c2",
+                    "fa783a32c40af3f9d7dda0c635b482254b1d85a281af7231109166cd133c8360",
+                    "e281e5e39bcdd7c601ac47928a8c78cdb3c4f71e97d4d0b1c0ee01dd3db62f04",
+                    "f44798bb3a76492ba15a91b7110cb5e01babe56589a36fae3a2f336a2d1d5778",
+                    "dbd23c03ca8db0f25ff0657ff4bca1252adc38c080a5b8f0255ce3be0bf86282",
+                    "3d2ab704729b74e1e275aa305824a566895ed677a460113e2a7bf91f00d0b8eb",
+                    "c358f3035b27fcc1d3f14a1367cd2769df39a9d21c5ee361f1965cd6342cc17a",
+                    "1463d6",
+                ),
+                "72640a79fbb1cfb26e09b4b35385389ed633a55e092906d01a7186e1",
+            ),
+        ]);
+        check_shavs::<Sha512_256>(&[
+            (
+                "",
+                "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+            ),
+            (
+                "fa",
+                "c4ef36923c64e51e875720e550298a5ab8a3f2f875b1e1a4c9b95babf7344fef",
+            ),
+            (
+                concat!(
+                    "5731f467c5b923c43af9c5fa849aad21ab8dd7db1ca1a687065571b705ea3ee4",
+                    "febdcd614ad4d98e16b79a4e09818ebb28367918f757ab06e1b481fbda822ef1",
+                    "43adbb5b0e704d5d2222a73c0153ef14a817b5c9b7a2313fd115ccce4698e3f0",
+                    "efa9c73d5ab3089a27e3f3adb23759",
+                ),
+                "f5253b5c69db9c724aebf762ec51c221f8a4d4e2174a4b7f56e4d69aa44adfa3",
+            ),
+            (
+                concat!(
+                    "92b23c0bc4d8d07d22e28812710dff06cb9bbecea2c960ac0200f480164fa2e1",
+                    "ee19926c7f0b095cec51d55c040aec990bf9501abd7d355490c366f93a3ae512",
+                    "7347d14dfc3b8d98e0821feefa1cd671b75230ba1da1fa6d0cfbb910c42f491d",
+                    "a8a5c455424ea65886db2e735b2d07b9",
+                ),
+                "55a0597f11ff71c426201715beb585f254bb31c1dbade533f04e499c3391ff79",
+            ),
+            (
+                concat!(
+                    "bc8173c878ca60e9a0f823f9a589d4ff84547b389b117fb6bb1b614e7e75a9b1",
+                    "db0b21d9f73b42a73e94eccab3de5ae2845a54e5e24ba6c20fb4d245b964023b",
+                    "863040d6f080e953530d5fd944e8ffa525bf5364f65c88e06e6e22df4b8cee48",
+                    "e67738880a9f3f3406e9e6f001b0ac8f8e0ade7c814c0c5800d0b9e4ddf55622",
+                ),
+                "f691d01ee9ab675f3872313b77e6a4543c71e3e89aa94c48f91d6ee7fa1ab4fb",
+            ),
+            (
+                concat!(
+                    "97e003903bb971a523ce0c82bda5d6733c76b90deb307559c1bddd35368743f6",
+                    "563b315214cd5a7ee0bccf937c9776360bc0b9786b707bfbc4fb50576155edbb",
+                    "bfd5ddd8e43a76faf2ec0c78fc84644f188d6b0ab68c28e5303ff031a223d9fa",
+                    "fb3871e85408af6381e629fae67488068c68398a758f665e2c12258d9ff8effb",
+                    "31ec534b0c40ebffb43390e1e26fcaa28fd68ac24f7e1cafe0fa573103dc1705",
+                    "8a77edc9b3ea1418b45aa7f5977e126d4861c778ed6332217581eee674d73962",
+                    "2e63a529f10c11f4a9e3d8feaea848ade0905675f6458ffa132f52749af23d58",
+                    "4438e5",
+                ),
+                "00ce3b592d4e1a65f780df351fa7b2c01b49df4ea913c3fab24297f5791b18e5",
+            ),
+        ]);
+    }
+
+    // NIST SHAVS Monte response files
+    #[test]
+    fn test_monte_carlo_sha224() {
+        check_monte_carlo::<Sha224>(
+            "ed2b70d575d9d0b4196ae84a03eed940057ea89cdd729b95b7d4e6a5",
+            &[
+                (
+                    0,
+                    "cd94d7da13c030208b2d0d78fcfe9ea22fa8906df66aa9a1f42afa70",
+                ),
+                (
+                    1,
+                    "555846e884633639565d5e0c01dd93ba58edb01ee18e68ccca28f7b8",
+                ),
+                (
+                    99,
+                    "27033d2d89329ba9d2a39c0292552a5f1f945c115d5abf2064e93754",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_sha256() {
+        check_monte_carlo::<Sha256>(
+            "6d1e72ad03ddeb5de891e572e2396f8da015d899ef0e79503152d6010a3fe691",
+            &[
+                (
+                    0,
+                    "e93c330ae5447738c8aa85d71a6c80f2a58381d05872d26bdd39f1fcd4f2b788",
+                ),
+                (
+                    1,
+                    "2e78f8c8772ea7c9331d41ed3f9cdf27d8f514a99342ee766ee3b8b0d0b121c0",
+                ),
+                (
+                    99,
+                    "6a912ba4188391a78e6f13d88ed2d14e13afce9db6f7dcbf4a48c24f3db02778",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_sha384() {
+        check_monte_carlo::<Sha384>(
+            concat!(
+                "edff07255c71b54a9beae52cdfa083569a08be89949cbba73ddc8acf429359ca",
+                "5e5be7a673633ca0d9709848f522a9df",
+            ),
+            &[
+                (
+                    0,
+                    concat!(
+                        "e81b86c49a38feddfd185f71ca7da6732a053ed4a2640d52d27f53f9f7642265",
+                        "0b0e93645301ac99f8295d6f820f1035",
+                    ),
+                ),
+                (
+                    1,
+                    concat!(
+                        "1d6bd21713bffd50946a10c39a7742d740e8f271f0c8f643d4c95375094fd9bf",
+                        "29d89ee61a76053f22e44a4b058a64ed",
+                    ),
+                ),
+                (
+                    99,
+                    concat!(
+                        "ccde4359f23e64579c5c0380df837ee950928aa82937a2d2ed33d216e707c46d",
+                        "847efa5ca52dcbda551145e164fbd594",
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_sha512() {
+        check_monte_carlo::<Sha512>(
+            concat!(
+                "5c337de5caf35d18ed90b5cddfce001ca1b8ee8602f367e7c24ccca6f893802f",
+                "b1aca7a3dae32dcd60800a59959bc540d63237876b799229ae71a2526fbc52cd",
+            ),
+            &[
+                (
+                    0,
+                    concat!(
+                        "ada69add0071b794463c8806a177326735fa624b68ab7bcab2388b9276c036e4",
+                        "eaaff87333e83c81c0bca0359d4aeebcbcfd314c0630e0c2af68c1fb19cc470e",
+                    ),
+                ),
+                (
+                    1,
+                    concat!(
+                        "ef219b37c24ae507a2b2b26d1add51b31fb5327eb8c3b19b882fe38049433dbe",
+                        "ccd63b3d5b99ba2398920bcefb8aca98cd28a1ee5d2aaf139ce58a15d71b06b4",
+                    ),
+                ),
+                (
+                    99,
+                    concat!(
+                        "4aa7dad74eb51d09a6ae7735c4b795b078f51c314f14f42a0d63071e13bdc5fd",
+                        "9f51612e77b36d44567502a3b5eb66c609ec017e51d8df93e58d1a44f3c1e375",
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_sha512_224() {
+        check_monte_carlo::<Sha512_224>(
+            "2e325bf8c98c0be54493d04c329e706343aebe4968fdd33b37da9c0a",
+            &[
+                (
+                    0,
+                    "9ee006873962aa0842d636c759646a4ef4b65bcbebcc35430b20f7f4",
+                ),
+                (
+                    1,
+                    "87726eda4570734b396f4c253146ecb9770b8591739240f02a4f2a02",
+                ),
+                (
+                    99,
+                    "5d8fc89761e82efe7188596a52eb43efd9492038bbc47bc0df5e9843",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_sha512_256() {
+        check_monte_carlo::<Sha512_256>(
+            "f41ece2613e4573915696b5adcd51ca328be3bf566a9ca99c9ceb0279c1cb0a7",
+            &[
+                (
+                    0,
+                    "b1d97a6536896aa01098fb2b9e15d8692621c84077051fc1f70a8a48baa6dfaf",
+                ),
+                (
+                    1,
+                    "a008d2c5adce31a95b30397ac691d8606c6769a47b801441ba3afb7f727c8a9c",
+                ),
+                (
+                    99,
+                    "1822ede971ca1407fbcb1dff487ea9d93d71d756cdf32945703de673f4b3d816",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        check_incremental::<Sha224>();
+        check_incremental::<Sha256>();
+        check_incremental::<Sha384>();
+        check_incremental::<Sha512>();
+    }
+}
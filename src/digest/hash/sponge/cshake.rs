@@ -0,0 +1,191 @@
+use super::{Sponge, SpongeReader, KECCAK_ROUNDS, KECCAK_WIDTH};
+
+// cSHAKE (NIST SP 800-185) is SHAKE customized with a function name N and a
+// customization string S. Before the message, the sponge absorbs
+// bytepad(encode_string(N) || encode_string(S), rate)
+// and a different domain suffix (00) is used, so cSHAKE with any N or S
+// produces output unrelated to SHAKE and to cSHAKE with other strings. N is
+// reserved for functions defined by NIST (e.g. "KMAC"), and S is free for
+// applications to separate their uses of the same XOF. When both are empty,
+// cSHAKE is SHAKE.
+
+const CSHAKE_SUFFIX: u8 = 0x04;
+const SHAKE_SUFFIX: u8 = 0x1f;
+
+/// Encodes `x` as the byte length of its big-endian encoding, followed by the
+/// encoding. The encoding is at least one byte, so 0 encodes as `01 00`.
+pub fn left_encode(x: u64) -> Vec<u8> {
+    let mut encoded = minimal_be_bytes(x);
+    encoded.insert(0, encoded.len() as u8);
+    encoded
+}
+
+/// Encodes `x` as its big-endian encoding, followed by the byte length of the
+/// encoding. This can be appended to a message whose length is not yet known.
+pub fn right_encode(x: u64) -> Vec<u8> {
+    let mut encoded = minimal_be_bytes(x);
+    encoded.push(encoded.len() as u8);
+    encoded
+}
+
+/// Encodes a string as the `left_encode` of its length in bits, followed by
+/// the string, so that concatenated encodings can be parsed unambiguously.
+pub fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(s.len() as u64 * 8);
+    encoded.extend_from_slice(s);
+    encoded
+}
+
+/// Prepends `left_encode(w)` to `x`, and pads the result with zeros to a
+/// multiple of `w` bytes.
+pub fn bytepad(x: &[u8], w: usize) -> Vec<u8> {
+    let mut padded = left_encode(w as u64);
+    padded.extend_from_slice(x);
+
+    let len = padded.len() + (w - padded.len() % w) % w;
+    padded.resize(len, 0);
+    padded
+}
+
+fn minimal_be_bytes(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let leading = (x.leading_zeros() / 8).min(7) as usize;
+    bytes[leading..].to_vec()
+}
+
+macro_rules! cshake {
+    ($(#[$attr:meta])* $xof:ident, $capacity:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $xof {
+            sponge: Sponge,
+            suffix: u8,
+        }
+
+        impl $xof {
+            /// The number of bytes absorbed per permutation call.
+            pub const RATE: usize = KECCAK_WIDTH - $capacity;
+
+            pub fn new(function_name: &[u8], customization: &[u8]) -> Self {
+                // SAFETY: the rate and round count are valid
+                let mut sponge = Sponge::new(Self::RATE, KECCAK_ROUNDS).unwrap();
+
+                if function_name.is_empty() && customization.is_empty() {
+                    return $xof {
+                        sponge,
+                        suffix: SHAKE_SUFFIX,
+                    };
+                }
+
+                let mut prefix = encode_string(function_name);
+                prefix.extend(encode_string(customization));
+                sponge.absorb(&bytepad(&prefix, Self::RATE));
+
+                $xof {
+                    sponge,
+                    suffix: CSHAKE_SUFFIX,
+                }
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            /// Finishes absorbing, and returns a reader squeezing the output.
+            pub fn finalize_xof(self) -> SpongeReader {
+                self.sponge.finalize(self.suffix)
+            }
+        }
+
+        impl std::io::Write for $xof {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+cshake!(
+    /// cSHAKE128, SHAKE128 customized with a function name and customization string.
+    CShake128, 32
+);
+cshake!(
+    /// cSHAKE256, SHAKE256 customized with a function name and customization string.
+    CShake256, 64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{digest::hash::sponge::Shake128, encode::hex::decode};
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(left_encode(0), [1, 0]);
+        assert_eq!(left_encode(168), [1, 168]);
+        assert_eq!(left_encode(256), [2, 1, 0]);
+        assert_eq!(right_encode(0), [0, 1]);
+        assert_eq!(right_encode(256), [1, 0, 2]);
+        assert_eq!(left_encode(u64::MAX)[..2], [8, 0xff]);
+
+        assert_eq!(encode_string(b""), [1, 0]);
+        assert_eq!(encode_string(b"abc"), [1, 24, b'a', b'b', b'c']);
+
+        assert_eq!(bytepad(b"", 4), [1, 4, 0, 0]);
+        assert_eq!(bytepad(&[7, 7], 4), [1, 4, 7, 7]);
+        assert_eq!(bytepad(&[7, 7, 7], 4), [1, 4, 7, 7, 7, 0, 0, 0]);
+    }
+
+    // NIST SP 800-185 cSHAKE samples
+    #[test]
+    fn test_cshake128() {
+        let mut cshake = CShake128::new(b"", b"Email Signature");
+        cshake.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            cshake.finalize_xof().squeeze_vec(32),
+            decode("c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5")
+        );
+
+        let mut cshake = CShake128::new(b"", b"Email Signature");
+        cshake.update(&(0..200).collect::<Vec<u8>>());
+        assert_eq!(
+            cshake.finalize_xof().squeeze_vec(32),
+            decode("c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b")
+        );
+    }
+
+    #[test]
+    fn test_cshake256() {
+        let mut cshake = CShake256::new(b"", b"Email Signature");
+        cshake.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            cshake.finalize_xof().squeeze_vec(64),
+            decode(concat!(
+                "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd1",
+                "64020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_empty_strings_are_shake() {
+        let mut cshake = CShake128::new(b"", b"");
+        cshake.update(b"abc");
+        assert_eq!(
+            cshake.finalize_xof().squeeze_vec(64),
+            Shake128::digest(b"abc", 64)
+        );
+
+        let mut cshake = CShake128::new(b"", b"customized");
+        cshake.update(b"abc");
+        assert_ne!(
+            cshake.finalize_xof().squeeze_vec(64),
+            Shake128::digest(b"abc", 64)
+        );
+    }
+}
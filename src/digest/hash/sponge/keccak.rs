@@ -0,0 +1,173 @@
+// Keccak-f[1600] is the permutation underlying SHA-3. The 1600 bit state is a
+// 5x5 array of 64-bit lanes, where lane (x, y) is stored at index x + 5y. Each
+// of the 24 rounds applies five steps:
+// theta: XOR each bit with the parity of two neighbouring columns
+// rho:   rotate each lane by a fixed offset
+// pi:    permute the positions of the lanes
+// chi:   XOR each bit with a non-linear function of two others in its row
+// iota:  XOR a round constant into lane (0, 0)
+
+// Keccak-p[1600, n_r] is the same permutation reduced to its last n_r rounds,
+// so Keccak-p[1600, 24] is Keccak-f[1600]. Reduced-round variants are much
+// weaker: chi is the only non-linear step and has degree 2, so the output of
+// n_r rounds has degree at most 2^n_r in the input bits. Cube attacks exploit
+// this by summing the output over every assignment to a set of input bits.
+
+/// The number of rounds in Keccak-f[1600].
+pub const KECCAK_ROUNDS: usize = 24;
+
+/// `KeccakState` is the Keccak-f[1600] state, 25 lanes of 64 bits.
+pub type KeccakState = [u64; 25];
+
+#[rustfmt::skip]
+const ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// rho rotation offsets, indexed by x + 5y
+#[rustfmt::skip]
+const ROTATION_OFFSETS: [u32; 25] = [
+     0,  1, 62, 28, 27,
+    36, 44,  6, 55, 20,
+     3, 10, 43, 25, 39,
+    41, 45, 15, 21,  8,
+    18,  2, 61, 56, 14,
+];
+
+/// Applies the full 24 round Keccak-f[1600] permutation to the state.
+pub fn keccak_f1600(state: &mut KeccakState) {
+    keccak_p1600(state, KECCAK_ROUNDS);
+}
+
+/// Applies Keccak-p[1600, rounds], the last `rounds` rounds of Keccak-f[1600].
+///
+/// # Panics
+///
+/// Panics if `rounds` is greater than 24.
+pub fn keccak_p1600(state: &mut KeccakState, rounds: usize) {
+    assert!(rounds <= KECCAK_ROUNDS, "Keccak-f[1600] has 24 rounds");
+
+    for round_constant in &ROUND_CONSTANTS[KECCAK_ROUNDS - rounds..] {
+        round(state, *round_constant);
+    }
+}
+
+fn round(a: &mut KeccakState, round_constant: u64) {
+    // theta
+    let mut c = [0u64; 5];
+    for x in 0..5 {
+        c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+    }
+    for x in 0..5 {
+        let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+        for y in 0..5 {
+            a[x + 5 * y] ^= d;
+        }
+    }
+
+    // rho and pi: B[y, 2x + 3y] = rot(A[x, y], r[x, y])
+    let mut b = [0u64; 25];
+    for x in 0..5 {
+        for y in 0..5 {
+            b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                a[x + 5 * y].rotate_left(ROTATION_OFFSETS[x + 5 * y]);
+        }
+    }
+
+    // chi
+    for y in 0..5 {
+        for x in 0..5 {
+            a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+        }
+    }
+
+    // iota
+    a[0] ^= round_constant;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_state() {
+        // Keccak team's intermediate values for Keccak-f[1600] on the zero state
+        let mut state = [0u64; 25];
+        keccak_f1600(&mut state);
+        assert_eq!(
+            state[..4],
+            [
+                0xf1258f7940e1dde7,
+                0x84d5ccf933c0478a,
+                0xd598261ea65aa9ee,
+                0xbd1547306f80494d,
+            ]
+        );
+    }
+
+    // cube_sum XORs the state after `rounds` rounds over every assignment to
+    // the cube bits, with the remaining bits fixed by `base`
+    fn cube_sum(base: &KeccakState, cube: &[usize], rounds: usize) -> KeccakState {
+        let mut sum = [0u64; 25];
+        for assignment in 0..1u32 << cube.len() {
+            let mut state = *base;
+            for (i, &bit) in cube.iter().enumerate() {
+                state[bit / 64] ^= ((assignment >> i) as u64 & 1) << (bit % 64);
+            }
+
+            keccak_p1600(&mut state, rounds);
+            sum.iter_mut().zip(state).for_each(|(s, x)| *s ^= x);
+        }
+
+        sum
+    }
+
+    #[test]
+    fn test_cube_sums() {
+        let mut base = [0u64; 25];
+        for (i, lane) in base.iter_mut().enumerate() {
+            *lane = (i as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15);
+        }
+
+        // n rounds have degree at most 2^n, so summing over a cube of
+        // dimension 2^n + 1 cancels every output bit
+        for rounds in 1..=3 {
+            let cube = (0..(1 << rounds) + 1)
+                .map(|i| i * 67)
+                .collect::<Vec<usize>>();
+            assert_eq!(cube_sum(&base, &cube, rounds), [0; 25], "{} rounds", rounds);
+        }
+
+        let cube = (0..9).map(|i| i * 67).collect::<Vec<usize>>();
+        assert_ne!(cube_sum(&base, &cube, KECCAK_ROUNDS), [0; 25]);
+    }
+
+    #[test]
+    fn test_reduced_rounds() {
+        let mut state = [1u64; 25];
+        keccak_p1600(&mut state, 0);
+        assert_eq!(state, [1u64; 25]);
+
+        // Keccak-p uses the last rounds, so 24 rounds is 20 rounds followed by 4
+        let mut full = [1u64; 25];
+        keccak_f1600(&mut full);
+
+        let mut split = [1u64; 25];
+        for round_constant in &ROUND_CONSTANTS[..20] {
+            round(&mut split, *round_constant);
+        }
+        keccak_p1600(&mut split, 4);
+        assert_eq!(split, full);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_rounds() {
+        keccak_p1600(&mut [0; 25], 25);
+    }
+}
@@ -1,3 +1,226 @@
+use crate::digest::DigestError;
+
+mod cshake;
+mod keccak;
 mod sha3;
 
+pub use cshake::*;
+pub use keccak::*;
 pub use sha3::*;
+
+// The sponge construction builds a hash function from a fixed width
+// permutation f. The b bit state is split into an outer part of r bits (the
+// rate) and an inner part of c = b - r bits (the capacity):
+// absorbing: XOR the next r bits of the padded message into the state, apply f
+// squeezing: output the first r bits of the state, apply f, repeat
+// Messages are padded with pad10*1: a 1 bit, zeros, and a final 1 bit, so the
+// last block is never all zero.
+
+// The capacity is never directly read or written, so it bounds the security
+// of the sponge at c/2 bits against generic attacks. A larger rate processes
+// more data per permutation call, at the cost of capacity. Unlike
+// Merkle–Damgård, the output reveals only the outer part of the state, so
+// sponges are not vulnerable to length extension, and the output can be
+// squeezed to any length.
+
+// A few domain separation bits are appended to the message before padding,
+// so that SHA-3, SHAKE and cSHAKE never hash to the same state. In the
+// byte-oriented interface below, the domain bits and the first padding bit
+// are combined into a single suffix byte, e.g. 0x06 for SHA-3 (01 || 1).
+
+/// The width of the Keccak-f[1600] state in bytes.
+pub const KECCAK_WIDTH: usize = 200;
+
+/// `Sponge` absorbs messages into a Keccak-p[1600] state at a configurable
+/// rate, and is finalized into a `SpongeReader` to squeeze the output.
+#[derive(Clone, Debug)]
+pub struct Sponge {
+    state: KeccakState,
+    rate: usize,     // bytes
+    rounds: usize,   // rounds of the permutation
+    position: usize, // bytes absorbed into the current block
+}
+
+impl Sponge {
+    /// Creates a sponge absorbing `rate` bytes per call to Keccak-p[1600,
+    /// rounds], leaving a capacity of 200 - `rate` bytes. The rate has to be
+    /// between 1 and 199 bytes, and the permutation at most 24 rounds.
+    pub fn new(rate: usize, rounds: usize) -> Result<Self, DigestError> {
+        if rate == 0 || rate >= KECCAK_WIDTH {
+            return Err(DigestError::InvalidRate(rate));
+        }
+        if rounds > KECCAK_ROUNDS {
+            return Err(DigestError::InvalidRounds(rounds));
+        }
+
+        Ok(Sponge {
+            state: [0; 25],
+            rate,
+            rounds,
+            position: 0,
+        })
+    }
+
+    /// Creates a sponge over the full 24 round Keccak-f[1600] with a capacity
+    /// of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Result<Self, DigestError> {
+        Self::new(KECCAK_WIDTH.saturating_sub(capacity), KECCAK_ROUNDS)
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn capacity(&self) -> usize {
+        KECCAK_WIDTH - self.rate
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Returns the current permutation state.
+    pub fn state(&self) -> KeccakState {
+        self.state
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            xor_byte(&mut self.state, self.position, byte);
+            self.position += 1;
+
+            if self.position == self.rate {
+                self.permute();
+                self.position = 0;
+            }
+        }
+    }
+
+    /// Pads the absorbed message and switches to squeezing. `suffix` holds the
+    /// domain separation bits followed by the first padding bit, least
+    /// significant bit first: 0x01 for Keccak, 0x06 for SHA-3, 0x1f for SHAKE
+    /// and 0x04 for cSHAKE.
+    pub fn finalize(mut self, suffix: u8) -> SpongeReader {
+        xor_byte(&mut self.state, self.position, suffix);
+        xor_byte(&mut self.state, self.rate - 1, 0x80);
+        self.permute();
+
+        SpongeReader {
+            sponge: self,
+            position: 0,
+        }
+    }
+
+    fn permute(&mut self) {
+        keccak_p1600(&mut self.state, self.rounds);
+    }
+}
+
+/// `SpongeReader` squeezes output of any length from a finalized sponge.
+/// Reads continue where the previous read stopped, so the output can be read
+/// in pieces of any size.
+#[derive(Clone, Debug)]
+pub struct SpongeReader {
+    sponge: Sponge,
+    position: usize, // bytes squeezed from the current block
+}
+
+impl SpongeReader {
+    pub fn squeeze(&mut self, output: &mut [u8]) {
+        for byte in output {
+            if self.position == self.sponge.rate {
+                self.sponge.permute();
+                self.position = 0;
+            }
+
+            let lane = self.sponge.state[self.position / 8];
+            *byte = (lane >> (8 * (self.position % 8))) as u8;
+            self.position += 1;
+        }
+    }
+
+    /// Squeezes the next `len` bytes of output.
+    pub fn squeeze_vec(&mut self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.squeeze(&mut output);
+        output
+    }
+}
+
+impl std::io::Read for SpongeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.squeeze(buf);
+        Ok(buf.len())
+    }
+}
+
+// lanes are little-endian, so byte i of the state is byte i % 8 of lane i / 8
+fn xor_byte(state: &mut KeccakState, index: usize, byte: u8) {
+    state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::digest::Digest;
+
+    #[test]
+    fn test_sha3_from_sponge() {
+        let mut sponge = Sponge::with_capacity(64).unwrap();
+        assert_eq!(sponge.rate(), 136);
+        assert_eq!(sponge.rounds(), 24);
+
+        sponge.absorb(b"abc");
+        let mut reader = sponge.finalize(0x06);
+        assert_eq!(reader.squeeze_vec(32), Sha3_256::digest(b"abc").as_slice());
+    }
+
+    #[test]
+    fn test_rate_boundary() {
+        // a message one byte short of the rate puts the suffix and the final
+        // padding bit in the same byte
+        let mut short = Sponge::new(8, 24).unwrap();
+        short.absorb(&[0; 7]);
+
+        let mut state = [0u64; 25];
+        state[0] = 0x86 << 56;
+        keccak_f1600(&mut state);
+        assert_eq!(short.finalize(0x06).sponge.state(), state);
+    }
+
+    #[test]
+    fn test_read() {
+        let mut sponge = Sponge::new(17, 24).unwrap();
+        sponge.absorb(b"message");
+        let mut reader = sponge.finalize(0x1f);
+        let expected = reader.clone().squeeze_vec(100);
+
+        let mut output = [0u8; 100];
+        reader.read_exact(&mut output[..33]).unwrap();
+        reader.read_exact(&mut output[33..]).unwrap();
+        assert_eq!(output.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert_eq!(Sponge::new(0, 24).unwrap_err(), DigestError::InvalidRate(0));
+        assert_eq!(
+            Sponge::new(200, 24).unwrap_err(),
+            DigestError::InvalidRate(200)
+        );
+        assert_eq!(
+            Sponge::new(136, 25).unwrap_err(),
+            DigestError::InvalidRounds(25)
+        );
+        assert_eq!(
+            Sponge::with_capacity(0).unwrap_err(),
+            DigestError::InvalidRate(200)
+        );
+        assert_eq!(
+            Sponge::with_capacity(200).unwrap_err(),
+            DigestError::InvalidRate(0)
+        );
+    }
+}
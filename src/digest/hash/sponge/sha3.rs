@@ -0,0 +1,331 @@
+use generic_array::typenum::{Unsigned, U104, U136, U144, U28, U32, U48, U64, U72};
+
+use super::{Sponge, SpongeReader, KECCAK_ROUNDS, KECCAK_WIDTH};
+use crate::digest::{impl_write, Digest, DigestError, Output};
+
+// SHA-3 (FIPS 202) is the sponge over Keccak-f[1600], with a capacity of twice
+// the digest size, i.e. the generic security level against collisions:
+// SHA3-224  rate 144 bytes, capacity 56 bytes
+// SHA3-256  rate 136 bytes, capacity 64 bytes
+// SHA3-384  rate 104 bytes, capacity 96 bytes
+// SHA3-512  rate  72 bytes, capacity 128 bytes
+
+const SHA3_SUFFIX: u8 = 0x06;
+const SHAKE_SUFFIX: u8 = 0x1f;
+
+macro_rules! sha3 {
+    ($(#[$attr:meta])* $hash:ident, $output_size:ty, $rate:ty) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $hash {
+            sponge: Sponge,
+        }
+
+        impl $hash {
+            /// Creates the hash over Keccak-p[1600, rounds], for experimenting
+            /// with reduced-round variants.
+            pub fn with_rounds(rounds: usize) -> Result<Self, DigestError> {
+                Ok($hash {
+                    sponge: Sponge::new(<$rate>::USIZE, rounds)?,
+                })
+            }
+        }
+
+        impl Default for $hash {
+            fn default() -> Self {
+                // SAFETY: the rate and round count are valid
+                Self::with_rounds(KECCAK_ROUNDS).unwrap()
+            }
+        }
+
+        impl Digest for $hash {
+            type OutputSize = $output_size;
+            type BlockSize = $rate;
+
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            fn finalize(self) -> Output<Self> {
+                let mut output = Output::<Self>::default();
+                self.sponge.finalize(SHA3_SUFFIX).squeeze(&mut output);
+                output
+            }
+        }
+
+        impl_write!($hash);
+    };
+}
+
+sha3!(
+    /// SHA3-224, the SHA-3 hash with a 224 bit digest.
+    Sha3_224, U28, U144
+);
+sha3!(
+    /// SHA3-256, the SHA-3 hash with a 256 bit digest.
+    Sha3_256, U32, U136
+);
+sha3!(
+    /// SHA3-384, the SHA-3 hash with a 384 bit digest.
+    Sha3_384, U48, U104
+);
+sha3!(
+    /// SHA3-512, the SHA-3 hash with a 512 bit digest.
+    Sha3_512, U64, U72
+);
+
+// SHAKE128 and SHAKE256 are extendable-output functions (XOFs): the same
+// sponge is squeezed for as long as output is needed. Their security is
+// min(d/2, 128) or min(d/2, 256) bits for a d bit output, so they use the
+// capacities of SHA3-256 and SHA3-512. The output for a shorter length is a
+// prefix of the output for a longer one, so unlike a hash, outputs of
+// different lengths are related.
+
+macro_rules! shake {
+    ($(#[$attr:meta])* $xof:ident, $capacity:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $xof {
+            sponge: Sponge,
+        }
+
+        impl $xof {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Creates the XOF over Keccak-p[1600, rounds], for experimenting
+            /// with reduced-round variants.
+            pub fn with_rounds(rounds: usize) -> Result<Self, DigestError> {
+                Ok($xof {
+                    sponge: Sponge::new(KECCAK_WIDTH - $capacity, rounds)?,
+                })
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            /// Finishes absorbing, and returns a reader squeezing the output.
+            pub fn finalize_xof(self) -> SpongeReader {
+                self.sponge.finalize(SHAKE_SUFFIX)
+            }
+
+            /// Hashes a complete message in one call, returning `len` bytes.
+            pub fn digest(data: &[u8], len: usize) -> Vec<u8> {
+                Self::digest_reader(data).squeeze_vec(len)
+            }
+
+            /// Hashes a complete message in one call, returning a reader
+            /// squeezing the output.
+            pub fn digest_reader(data: &[u8]) -> SpongeReader {
+                let mut xof = Self::default();
+                xof.update(data);
+                xof.finalize_xof()
+            }
+        }
+
+        impl Default for $xof {
+            fn default() -> Self {
+                // SAFETY: the rate and round count are valid
+                Self::with_rounds(KECCAK_ROUNDS).unwrap()
+            }
+        }
+
+        impl std::io::Write for $xof {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+shake!(
+    /// SHAKE128, the SHA-3 XOF with 128 bits of security.
+    Shake128, 32
+);
+shake!(
+    /// SHAKE256, the SHA-3 XOF with 256 bits of security.
+    Shake256, 64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        digest::tests::{check_all_lengths, check_incremental},
+        encode::hex::decode,
+    };
+
+    // FIPS 202 example values, for the empty message, "abc", and the 1600 bit
+    // message of repeated 0xa3 bytes
+    #[rustfmt::skip]
+    const SHA3_224_CASES: [(&[u8], &str); 2] = [
+        (b"", "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7"),
+        (b"abc", "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"),
+    ];
+
+    #[rustfmt::skip]
+    const SHA3_256_CASES: [(&[u8], &str); 2] = [
+        (b"", "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"),
+        (b"abc", "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"),
+    ];
+
+    #[rustfmt::skip]
+    const SHA3_384_CASES: [(&[u8], &str); 2] = [
+        (b"", "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004"),
+        (b"abc", "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25"),
+    ];
+
+    #[rustfmt::skip]
+    const SHA3_512_CASES: [(&[u8], &str); 2] = [
+        (b"", "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"),
+        (b"abc", "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"),
+    ];
+
+    fn check_cases<D: Digest>(cases: &[(&[u8], &str)], a3: &str) {
+        for (message, expected) in cases {
+            assert_eq!(D::digest(message).as_slice(), decode(expected));
+        }
+        assert_eq!(D::digest(&[0xa3; 200]).as_slice(), decode(a3));
+    }
+
+    #[test]
+    fn test_sha3_224() {
+        check_cases::<Sha3_224>(
+            &SHA3_224_CASES,
+            "9376816aba503f72f96ce7eb65ac095deee3be4bf9bbc2a1cb7e11e0",
+        );
+    }
+
+    #[test]
+    fn test_sha3_256() {
+        check_cases::<Sha3_256>(
+            &SHA3_256_CASES,
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+        );
+    }
+
+    #[test]
+    fn test_sha3_384() {
+        check_cases::<Sha3_384>(
+            &SHA3_384_CASES,
+            concat!(
+                "1881de2ca7e41ef95dc4732b8f5f002b189cc1e42b74168e",
+                "d1732649ce1dbcdd76197a31fd55ee989f2d7050dd473e8f",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_sha3_512() {
+        check_cases::<Sha3_512>(
+            &SHA3_512_CASES,
+            concat!(
+                "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca8",
+                "1b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_incremental() {
+        check_incremental::<Sha3_224>();
+        check_incremental::<Sha3_256>();
+        check_incremental::<Sha3_384>();
+        check_incremental::<Sha3_512>();
+    }
+
+    #[test]
+    fn test_all_lengths() {
+        check_all_lengths::<Sha3_224>(&decode(
+            "221d9b6c939acd11db7250bb139edd5e65ddc18bdfe5df33180a5d98",
+        ));
+        check_all_lengths::<Sha3_256>(&decode(
+            "69fd44e84eaa578a3fbe34556c6fb7edc16373be06260aadfb158090326efefd",
+        ));
+        check_all_lengths::<Sha3_384>(&decode(concat!(
+            "5abc6eff7e90d5d38e9e83f22afb2e3cdaf2ae6c1050ff33",
+            "9f577390f0c1c70df881413bb59710e715d0c43036a18111",
+        )));
+        check_all_lengths::<Sha3_512>(&decode(concat!(
+            "b32985df6c8042bded2d632b2216e39e4f502a38817064ceeb4fdbe5fd5d5fbc",
+            "4b0c84cf6815c531567e8919184e6ddacc0eddcd620be11c88536507f4c8c7a9",
+        )));
+    }
+
+    #[test]
+    fn test_shake() {
+        assert_eq!(
+            Shake128::digest(b"", 32),
+            decode("7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26")
+        );
+        assert_eq!(
+            Shake256::digest(b"", 64),
+            decode(concat!(
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f",
+                "d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be",
+            ))
+        );
+    }
+
+    #[test]
+    fn test_shake_long_output() {
+        // 4096 bits of output from the 1600 bit message, spanning several
+        // squeezes; the first and last 32 bytes are checked
+        let mut reader = Shake128::digest_reader(&[0xa3; 200]);
+        assert_eq!(
+            reader.squeeze_vec(32),
+            decode("131ab8d2b594946b9c81333f9bb6e0ce75c3b93104fa3469d3917457385da037")
+        );
+        reader.squeeze_vec(448);
+        assert_eq!(
+            reader.squeeze_vec(32),
+            decode("44c9fb359fd56ac0a9a75a743cff6862f17d7259ab075216c0699511643b6439")
+        );
+
+        let mut reader = Shake256::digest_reader(&[0xa3; 200]);
+        assert_eq!(
+            reader.squeeze_vec(32),
+            decode("cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d")
+        );
+        reader.squeeze_vec(448);
+        assert_eq!(
+            reader.squeeze_vec(32),
+            decode("6a1a9d7846436e4dca5728b6f760eef0ca92bf0be5615e96959d767197a0beeb")
+        );
+    }
+
+    #[test]
+    fn test_shake_output_prefix() {
+        // shorter outputs are prefixes of longer ones, however they are read
+        let expected = Shake128::digest(b"abc", 400);
+        for piece_len in 1..=200 {
+            let mut reader = Shake128::digest_reader(b"abc");
+            let mut output = Vec::new();
+            while output.len() < expected.len() {
+                output.extend(reader.squeeze_vec(piece_len));
+            }
+            assert_eq!(output[..400], expected, "piece length {}", piece_len);
+        }
+    }
+
+    #[test]
+    fn test_reduced_rounds() {
+        let full = Sha3_256::with_rounds(24).unwrap();
+        assert_eq!(full.finalize(), Sha3_256::digest(b""));
+
+        let reduced = Sha3_256::with_rounds(4).unwrap();
+        assert_ne!(reduced.finalize(), Sha3_256::digest(b""));
+
+        assert_eq!(
+            Shake128::with_rounds(25).unwrap_err(),
+            DigestError::InvalidRounds(25)
+        );
+    }
+}
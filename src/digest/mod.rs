@@ -1 +1,225 @@
+use generic_array::{ArrayLength, GenericArray};
+use thiserror::Error;
+
 pub mod hash;
+
+/// `Digest` is a trait representing a cryptographic hash function, which
+/// maps messages of any length to a fixed size digest.
+///
+/// This trait has two associated types:
+/// * `OutputSize` is a type implementing `ArrayLength<u8>`, specifying the
+///   digest size.
+/// * `BlockSize` is a type implementing `ArrayLength<u8>`, specifying the size
+///   of the blocks the hash function processes, which HMAC pads keys to.
+///
+/// Messages are absorbed incrementally with `update`, so they can be hashed
+/// in pieces of any size, and the pieces concatenated. Hashes also implement
+/// `std::io::Write`, so a reader can be hashed with `std::io::copy`.
+///
+/// Hash functions are expected to be *preimage resistant* (given a digest, it
+/// is infeasible to find a message hashing to it), *second preimage resistant*
+/// and *collision resistant*. MD4, MD5 and SHA-1 are no longer collision
+/// resistant, and are only suitable for legacy interoperability and
+/// cryptanalysis exercises.
+pub trait Digest: Default {
+    type OutputSize: ArrayLength<u8>;
+    type BlockSize: ArrayLength<u8>;
+
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Output<Self>;
+
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the hash to its initial state, discarding any absorbed data.
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Hashes a complete message in one call.
+    fn digest(data: &[u8]) -> Output<Self> {
+        let mut hasher = Self::default();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// `Output` is the digest produced by a hash function `D`.
+pub type Output<D> = GenericArray<u8, <D as Digest>::OutputSize>;
+
+/// Errors that can occur when constructing or resuming a hash.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DigestError {
+    /// Occurs when a hash is resumed after a length that is not a whole number of blocks.
+    #[error("length {0} is not a whole number of blocks")]
+    UnalignedLength(u128),
+
+    /// Occurs when the internal state cannot be recovered from a truncated digest.
+    #[error("digest is truncated from the internal state")]
+    TruncatedOutput,
+
+    /// Occurs when a sponge's rate leaves no room for capacity, or is zero.
+    #[error("invalid sponge rate: {0} bytes")]
+    InvalidRate(usize),
+
+    /// Occurs when a permutation is given more rounds than it has.
+    #[error("invalid number of rounds: {0}")]
+    InvalidRounds(usize),
+}
+
+// impl_write implements std::io::Write for hashes, absorbing everything
+// written with Digest::update.
+macro_rules! impl_write {
+    (impl<$($param:ident: $bound:path),*> $digest:ty) => {
+        impl<$($param: $bound),*> std::io::Write for $digest {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                $crate::digest::Digest::update(self, buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+    ($digest:ty) => {
+        impl_write!(impl<> $digest);
+    };
+}
+
+pub(crate) use impl_write;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{self, Write};
+
+    use generic_array::typenum::{U1, U4};
+
+    use super::*;
+    use crate::encode::hex::decode;
+
+    // check_incremental checks that hashing a message in pieces of every size
+    // matches hashing it in one call, across block boundaries.
+    pub(crate) fn check_incremental<D: Digest>() {
+        let message = (0..=255).cycle().take(300).collect::<Vec<u8>>();
+        let expected = D::digest(&message);
+
+        for piece_len in 1..=message.len() / 2 {
+            let mut hasher = D::new();
+            for piece in message.chunks(piece_len) {
+                hasher.update(piece);
+            }
+            assert_eq!(hasher.finalize(), expected, "piece length {}", piece_len);
+        }
+    }
+
+    // check_all_lengths hashes messages of every length from 0 to 300 bytes,
+    // covering each padding boundary, and compares the hash of their
+    // concatenated digests. Expected values were computed with OpenSSL.
+    pub(crate) fn check_all_lengths<D: Digest>(expected: &[u8]) {
+        let mut digests = Vec::new();
+        for len in 0..=300 {
+            let message = (0..len).map(|i| (i * 7 + len) as u8).collect::<Vec<u8>>();
+            digests.extend(D::digest(&message));
+        }
+
+        assert_eq!(D::digest(&digests).as_slice(), expected);
+    }
+
+    // check_shavs checks (message, digest) pairs, both in hex, sampled from
+    // the NIST SHAVS ShortMsg and LongMsg response files.
+    pub(crate) fn check_shavs<D: Digest>(vectors: &[(&str, &str)]) {
+        for (message, digest) in vectors {
+            assert_eq!(
+                D::digest(&decode(message)).to_vec(),
+                decode(digest),
+                "message {}",
+                message
+            );
+        }
+    }
+
+    // check_monte_carlo runs the NIST SHAVS Monte Carlo test from the seed of
+    // a Monte response file. Each checkpoint chains 1000 hashes of the previous
+    // three digests, starting from three copies of the seed:
+    // MD_i = H(MD_{i-3} || MD_{i-2} || MD_{i-1})
+    // and its last digest seeds the next checkpoint. checkpoints pairs COUNT
+    // with the expected digest, both as listed in the response file.
+    pub(crate) fn check_monte_carlo<D: Digest>(seed: &str, checkpoints: &[(usize, &str)]) {
+        let last = checkpoints
+            .iter()
+            .map(|(count, _)| *count)
+            .max()
+            .unwrap_or(0);
+
+        let mut seed = decode(seed);
+        for count in 0..=last {
+            let mut digests = [seed.clone(), seed.clone(), seed];
+            for _ in 0..1000 {
+                let mut hasher = D::new();
+                for digest in digests.iter() {
+                    hasher.update(digest);
+                }
+                digests.rotate_left(1);
+                digests[2] = hasher.finalize().to_vec();
+            }
+            seed = digests[2].clone();
+
+            for (_, digest) in checkpoints.iter().filter(|(c, _)| *c == count) {
+                assert_eq!(seed, decode(digest), "COUNT = {}", count);
+            }
+        }
+    }
+
+    // Checksum is a toy hash, the sum of the message bytes, exercising the
+    // provided methods.
+    #[derive(Default)]
+    struct Checksum(u32);
+
+    impl Digest for Checksum {
+        type OutputSize = U4;
+        type BlockSize = U1;
+
+        fn update(&mut self, data: &[u8]) {
+            for &byte in data {
+                self.0 = self.0.wrapping_add(byte as u32);
+            }
+        }
+
+        fn finalize(self) -> Output<Self> {
+            self.0.to_be_bytes().into()
+        }
+    }
+
+    impl_write!(Checksum);
+
+    #[test]
+    fn test_incremental() {
+        let mut hasher = Checksum::new();
+        hasher.update(&[1, 2]);
+        hasher.update(&[]);
+        hasher.update(&[3]);
+        assert_eq!(hasher.finalize(), Checksum::digest(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut hasher = Checksum::new();
+        hasher.update(&[0xff; 4]);
+        hasher.reset();
+        hasher.update(&[1]);
+        assert_eq!(hasher.finalize(), Checksum::digest(&[1]));
+    }
+
+    #[test]
+    fn test_write() {
+        let mut hasher = Checksum::new();
+        let copied = io::copy(&mut &[7u8; 300][..], &mut hasher).unwrap();
+        assert_eq!(copied, 300);
+
+        hasher.write_all(&[1]).unwrap();
+        assert_eq!(hasher.finalize().as_slice(), 2101u32.to_be_bytes());
+    }
+}
@@ -169,6 +169,15 @@ impl<I> ByteToHexEncoder<I> {
     }
 }
 
+/// Decodes a hex string, panicking if it is not valid hex. Used by tests to
+/// read test vectors.
+#[cfg(test)]
+pub(crate) fn decode(input: &str) -> Vec<u8> {
+    HexToByteDecoder::new(input.chars())
+        .collect::<Result<Vec<u8>, HexEncodingError>>()
+        .unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::{string::String, vec::Vec};
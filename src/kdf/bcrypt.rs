@@ -0,0 +1,276 @@
+use rand::RngCore;
+
+use super::KdfError;
+use crate::{cipher::block::ciphers::blowfish::Blowfish, utils::constant_time_eq};
+
+// bcrypt is the password hashing function from Provos and Mazières' "A
+// Future-Adaptable Password Scheme". It sets up Blowfish with the expensive
+// key schedule (EksBlowfish), keyed by the password and a random salt, and
+// then encrypts the 192 bit string "OrpheanBeholderScryDoubt" 64 times.
+
+// Hashes are stored as modular crypt strings:
+// $2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW
+//  \/ \/ \____________________/\_____________________________/
+//  |  |   salt (22 characters)  hash (31 characters)
+//  |  cost
+//  version
+// with the salt and the first 23 bytes of the ciphertext encoded in bcrypt's
+// own base64 alphabet, without padding.
+
+// The password is NUL terminated and truncated to 72 bytes, so longer
+// passwords only depend on their first 72 bytes. The $2b$ version fixed
+// OpenBSD's $2a$ implementation, which wrapped the password length at 255;
+// hashes of shorter passwords are identical across $2a$, $2b$ and
+// crypt_blowfish's $2y$, so all three verify.
+pub const BCRYPT_MIN_COST: u32 = 4;
+pub const BCRYPT_MAX_COST: u32 = 31;
+
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 23; // bcrypt drops the last byte of the ciphertext
+const MAX_PASSWORD_LEN: usize = 72;
+const MAGIC: &[u8; 24] = b"OrpheanBeholderScryDoubt";
+
+const ALPHABET: &[u8; 64] = b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+// bcrypt_hash hashes a password with a random salt, and returns the $2b$
+// modular crypt string. Each increment of cost doubles the work.
+pub fn bcrypt_hash<R: RngCore>(
+    rng: &mut R,
+    password: &[u8],
+    cost: u32,
+) -> Result<String, KdfError> {
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+
+    bcrypt_hash_with_salt(password, cost, salt)
+}
+
+pub fn bcrypt_hash_with_salt(
+    password: &[u8],
+    cost: u32,
+    salt: [u8; SALT_LEN],
+) -> Result<String, KdfError> {
+    let hash = bcrypt_raw(password, cost, &salt)?;
+
+    Ok(format!(
+        "$2b${:02}${}{}",
+        cost,
+        encode_base64(&salt),
+        encode_base64(&hash)
+    ))
+}
+
+// bcrypt_verify reports whether password matches a $2a$, $2b$ or $2y$ hash
+// string, comparing hashes in constant time.
+pub fn bcrypt_verify(password: &[u8], hash: &str) -> Result<bool, KdfError> {
+    let (cost, salt, expected) = parse(hash)?;
+    let hash = bcrypt_raw(password, cost, &salt)?;
+
+    Ok(constant_time_eq(&hash, &expected))
+}
+
+fn bcrypt_raw(
+    password: &[u8],
+    cost: u32,
+    salt: &[u8; SALT_LEN],
+) -> Result<[u8; HASH_LEN], KdfError> {
+    if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+        return Err(KdfError::InvalidCost(cost));
+    }
+
+    let mut key = password.to_vec();
+    key.push(0);
+    key.truncate(MAX_PASSWORD_LEN);
+
+    // SAFETY: the key is 1 to 72 bytes, and the cost was checked above
+    let blowfish = Blowfish::eks_setup(cost, salt, &key).unwrap();
+
+    let mut ctext = [0, 4, 8, 12, 16, 20].map(|i| {
+        // SAFETY: the slice is exactly 4 bytes
+        u32::from_be_bytes(MAGIC[i..i + 4].try_into().unwrap())
+    });
+    for _ in 0..64 {
+        blowfish.encrypt_words(&mut ctext);
+    }
+
+    let mut hash = [0u8; HASH_LEN];
+    for (chunk, word) in hash.chunks_mut(4).zip(ctext) {
+        chunk.copy_from_slice(&word.to_be_bytes()[..chunk.len()]);
+    }
+
+    Ok(hash)
+}
+
+// parse splits a modular crypt string into its cost, salt and hash.
+fn parse(hash: &str) -> Result<(u32, [u8; SALT_LEN], Vec<u8>), KdfError> {
+    let mut fields = hash.split('$');
+    let (Some(""), Some(version), Some(cost), Some(encoded), None) = (
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+        fields.next(),
+    ) else {
+        return Err(KdfError::MalformedHash);
+    };
+
+    match version {
+        "2a" | "2b" | "2y" => {}
+        _ => return Err(KdfError::UnsupportedVersion),
+    }
+
+    if cost.len() != 2 || !cost.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(KdfError::MalformedHash);
+    }
+    // SAFETY: cost is two ASCII digits
+    let cost = cost.parse::<u32>().unwrap();
+
+    if encoded.len() != 53 || !encoded.is_ascii() {
+        return Err(KdfError::MalformedHash);
+    }
+    let (salt, hash) = encoded.split_at(22);
+
+    let salt = decode_base64(salt, SALT_LEN)?;
+    let hash = decode_base64(hash, HASH_LEN)?;
+
+    // SAFETY: decode_base64 returns exactly SALT_LEN bytes
+    Ok((cost, salt.try_into().unwrap(), hash))
+}
+
+// encode_base64 encodes bytes in bcrypt's base64 alphabet, without padding.
+fn encode_base64(input: &[u8]) -> String {
+    let mut output = String::with_capacity((input.len() * 4 + 2) / 3);
+    for chunk in input.chunks(3) {
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+        for i in 0..chunk.len() + 1 {
+            output.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+
+    output
+}
+
+// decode_base64 decodes len bytes from bcrypt's base64 alphabet. Unused low
+// bits in the last character are ignored, as other implementations do.
+fn decode_base64(input: &str, len: usize) -> Result<Vec<u8>, KdfError> {
+    let mut output = Vec::with_capacity(len);
+    for chunk in input.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, &character) in chunk.iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|&c| c == character)
+                .ok_or(KdfError::MalformedHash)?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+
+        output.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+
+    if output.len() != len {
+        return Err(KdfError::MalformedHash);
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::MT;
+    use rand::SeedableRng;
+
+    // crypt_blowfish's test vectors
+    const TEST_VECTORS: [(&[u8], &str); 6] = [
+        (b"U*U", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW"),
+        (b"U*U*", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK"),
+        (b"U*U*U", "$2a$05$XXXXXXXXXXXXXXXXXXXXXOAcXxm9kjPGEMsLznoKqmqw7tc8WCx4a"),
+        (
+            b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789chars after 72 are ignored",
+            "$2a$05$abcdefghijklmnopqrstuu5s2v8.iXieOjg/.AySBTTZIIVFJeBui",
+        ),
+        (b"", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.7uG0VCzI2bS7j6ymqJi9CdcdxiRTWNy"),
+        (b"\xff\xff\xa3", "$2b$05$/OK.fbVrR/bpIqNJ5ianF.CE5elHaaO4EbggVDjb8P19RukzXSM3e"),
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for (password, hash) in TEST_VECTORS {
+            assert_eq!(bcrypt_verify(password, hash), Ok(true));
+            assert_eq!(bcrypt_verify(b"wrong", hash), Ok(false));
+
+            // rehashing with the same salt reproduces the hash as $2b$
+            let (cost, salt, _) = parse(hash).unwrap();
+            let rehashed = bcrypt_hash_with_salt(password, cost, salt).unwrap();
+            assert_eq!(rehashed[4..], hash[4..]);
+            assert!(rehashed.starts_with("$2b$"));
+        }
+    }
+
+    #[test]
+    fn test_hash_and_verify() {
+        let mut rng = MT::from_seed(1u32.to_be_bytes());
+
+        let first = bcrypt_hash(&mut rng, b"correct horse battery staple", 4).unwrap();
+        let second = bcrypt_hash(&mut rng, b"correct horse battery staple", 4).unwrap();
+        assert_eq!(first.len(), 60);
+        assert!(first.starts_with("$2b$04$"));
+
+        // random salts give different hashes of the same password
+        assert_ne!(first, second);
+        assert_eq!(
+            bcrypt_verify(b"correct horse battery staple", &first),
+            Ok(true)
+        );
+        assert_eq!(
+            bcrypt_verify(b"correct horse battery staple", &second),
+            Ok(true)
+        );
+        assert_eq!(bcrypt_verify(b"Tr0ub4dor&3", &first), Ok(false));
+    }
+
+    #[test]
+    fn test_base64() {
+        let salt = (0..16).collect::<Vec<u8>>();
+        let encoded = encode_base64(&salt);
+        assert_eq!(encoded.len(), 22);
+        assert_eq!(decode_base64(&encoded, 16).unwrap(), salt);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert_eq!(
+            bcrypt_hash_with_salt(b"password", 3, [0; 16]),
+            Err(KdfError::InvalidCost(3))
+        );
+        assert_eq!(
+            bcrypt_hash_with_salt(b"password", 32, [0; 16]),
+            Err(KdfError::InvalidCost(32))
+        );
+
+        let hash = "$2b$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        assert_eq!(
+            bcrypt_verify(b"U*U", &hash.replace("$2b$", "$2x$")),
+            Err(KdfError::UnsupportedVersion)
+        );
+        assert_eq!(
+            bcrypt_verify(b"U*U", &hash.replace("$05$", "$5$")),
+            Err(KdfError::MalformedHash)
+        );
+        assert_eq!(
+            bcrypt_verify(b"U*U", &hash[..59]),
+            Err(KdfError::MalformedHash)
+        );
+        assert_eq!(
+            bcrypt_verify(b"U*U", &hash.replace('E', "!")),
+            Err(KdfError::MalformedHash)
+        );
+        assert_eq!(
+            bcrypt_verify(b"U*U", &hash.replace("$05$", "$03$")),
+            Err(KdfError::InvalidCost(3))
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+mod bcrypt;
+
+pub use bcrypt::*;
+
+// Key derivation functions (KDFs) derive keys from secrets. Password hashing
+// functions such as bcrypt are KDFs which are deliberately expensive, so that
+// an attacker who steals the password hashes must pay that cost per guess.
+
+/// Errors that can occur when deriving or verifying a key.
+#[non_exhaustive]
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KdfError {
+    /// Occurs when the work factor is outside the range the function supports.
+    #[error("cost {0} is not supported")]
+    InvalidCost(u32),
+
+    /// Occurs when a hash string is not in the expected format.
+    #[error("hash string is malformed")]
+    MalformedHash,
+
+    /// Occurs when a hash string names a version of the function that is not supported.
+    #[error("hash version is not supported")]
+    UnsupportedVersion,
+}
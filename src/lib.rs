@@ -13,4 +13,8 @@ pub mod attack;
 pub mod cipher;
 pub mod digest;
 pub mod encode;
+pub mod kdf;
+pub mod mac;
 pub mod rng;
+
+mod utils;
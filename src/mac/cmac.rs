@@ -0,0 +1,129 @@
+use generic_array::{typenum::U16, GenericArray};
+
+use crate::cipher::block::{ciphers::BlockCipher, Block};
+
+// Cmac implements the cipher-based message authentication code defined in
+// NIST SP 800-38B (also RFC 4493 for AES). CMAC is CBC-MAC with a zero IV,
+// where the final block is masked with one of two subkeys derived from the
+// cipher key. The masking removes CBC-MAC's length-extension weakness on
+// variable length messages.
+
+// L   = encrypt_block(0^128, K)
+// K_1 = dbl(L)
+// K_2 = dbl(K_1)
+// M_n' = M_n XOR K_1            if M_n is a complete block
+//        (M_n || 10^i) XOR K_2  otherwise
+pub struct Cmac<C>
+where
+    C: BlockCipher<U16>,
+{
+    cipher: C,
+    k1: Block<U16>,
+    k2: Block<U16>,
+}
+
+impl<C> Cmac<C>
+where
+    C: BlockCipher<U16>,
+{
+    pub fn new(cipher: C) -> Self {
+        let l = cipher.encrypt_block(GenericArray::default());
+        let k1 = dbl(l);
+        let k2 = dbl(k1);
+
+        Cmac { cipher, k1, k2 }
+    }
+
+    pub fn mac(&self, message: &[u8]) -> Block<U16> {
+        let mut blocks = message.chunks(16).collect::<Vec<&[u8]>>();
+        let last = blocks.pop().unwrap_or(&[]);
+
+        let mut state: Block<U16> = GenericArray::default();
+        for block in blocks {
+            state = self.cipher.encrypt_block(xor(state, block));
+        }
+
+        let last_block = if last.len() == 16 {
+            xor(self.k1, last)
+        } else {
+            let mut padded = [0u8; 16];
+            padded[..last.len()].copy_from_slice(last);
+            padded[last.len()] = 0x80;
+
+            xor(self.k2, &padded)
+        };
+
+        self.cipher.encrypt_block(xor(state, &last_block))
+    }
+}
+
+/// Doubles a block in GF(2^128) with modulus x^128 + x^7 + x^2 + x + 1, i.e.
+/// shifts it left by one bit and reduces by 0x87 when the top bit carries out.
+/// Used for CMAC subkeys and by S2V in SIV mode.
+pub fn dbl(block: Block<U16>) -> Block<U16> {
+    let value = u128::from_be_bytes(block.into());
+    let carry = value >> 127;
+    let doubled = (value << 1) ^ (carry * 0x87);
+
+    GenericArray::clone_from_slice(&doubled.to_be_bytes())
+}
+
+fn xor(a: Block<U16>, b: &[u8]) -> Block<U16> {
+    a.into_iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::block::ciphers::aes::Aes;
+
+    // RFC 4493 section 4 test vectors
+    #[rustfmt::skip]
+    const KEY: [u8; 16] = [
+        0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6,
+        0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+    ];
+
+    #[rustfmt::skip]
+    const MESSAGE: [u8; 64] = [
+        0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+        0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+        0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+        0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10,
+    ];
+
+    #[test]
+    fn test_subkeys() {
+        let cmac = Cmac::new(Aes::new(KEY.to_vec()).unwrap());
+
+        #[rustfmt::skip]
+        let expected_k1 = [
+            0xfb, 0xee, 0xd6, 0x18, 0x35, 0x71, 0x33, 0x66,
+            0x7c, 0x85, 0xe0, 0x8f, 0x72, 0x36, 0xa8, 0xde,
+        ];
+        #[rustfmt::skip]
+        let expected_k2 = [
+            0xf7, 0xdd, 0xac, 0x30, 0x6a, 0xe2, 0x66, 0xcc,
+            0xf9, 0x0b, 0xc1, 0x1e, 0xe4, 0x6d, 0x51, 0x3b,
+        ];
+
+        assert_eq!(cmac.k1.as_slice(), expected_k1);
+        assert_eq!(cmac.k2.as_slice(), expected_k2);
+    }
+
+    #[test]
+    fn test_rfc4493_examples() {
+        #[rustfmt::skip]
+        let expected_macs: [(usize, [u8; 16]); 4] = [
+            (0, [0xbb, 0x1d, 0x69, 0x29, 0xe9, 0x59, 0x37, 0x28, 0x7f, 0xa3, 0x7d, 0x12, 0x9b, 0x75, 0x67, 0x46]),
+            (16, [0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44, 0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c]),
+            (40, [0xdf, 0xa6, 0x67, 0x47, 0xde, 0x9a, 0xe6, 0x30, 0x30, 0xca, 0x32, 0x61, 0x14, 0x97, 0xc8, 0x27]),
+            (64, [0x51, 0xf0, 0xbe, 0xbf, 0x7e, 0x3b, 0x9d, 0x92, 0xfc, 0x49, 0x74, 0x17, 0x79, 0x36, 0x3c, 0xfe]),
+        ];
+
+        let cmac = Cmac::new(Aes::new(KEY.to_vec()).unwrap());
+        for (len, expected_mac) in expected_macs {
+            assert_eq!(cmac.mac(&MESSAGE[..len]).as_slice(), expected_mac);
+        }
+    }
+}
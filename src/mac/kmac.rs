@@ -0,0 +1,125 @@
+use crate::digest::hash::sponge::{
+    bytepad, encode_string, right_encode, CShake128, CShake256, SpongeReader,
+};
+
+// Kmac implements the Keccak message authentication code defined in NIST
+// SP 800-185. Since sponges are not vulnerable to length extension, KMAC can
+// simply prefix the message with the key, without HMAC's nested construction:
+// KMAC(K, X, L, S) = cSHAKE(bytepad(encode_string(K), rate) || X || right_encode(L),
+//                           L, "KMAC", S)
+// The requested output length L is absorbed after the message, so tags of
+// different lengths are unrelated, unlike truncations of a single output.
+
+// KMACXOF absorbs right_encode(0) in place of L, and returns an XOF whose
+// output can be read to any length. As with SHAKE, shorter outputs of KMACXOF
+// are prefixes of longer ones.
+
+macro_rules! kmac {
+    ($(#[$attr:meta])* $mac:ident, $cshake:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $mac {
+            cshake: $cshake,
+        }
+
+        impl $mac {
+            pub fn new(key: &[u8], customization: &[u8]) -> Self {
+                let mut cshake = $cshake::new(b"KMAC", customization);
+                cshake.update(&bytepad(&encode_string(key), $cshake::RATE));
+
+                $mac { cshake }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.cshake.update(input);
+            }
+
+            /// Returns a tag of `len` bytes.
+            pub fn finalize(mut self, len: usize) -> Vec<u8> {
+                self.cshake.update(&right_encode(len as u64 * 8));
+                self.cshake.finalize_xof().squeeze_vec(len)
+            }
+
+            /// Finishes absorbing as KMACXOF, and returns a reader squeezing
+            /// output of any length.
+            pub fn finalize_xof(mut self) -> SpongeReader {
+                self.cshake.update(&right_encode(0));
+                self.cshake.finalize_xof()
+            }
+        }
+    };
+}
+
+kmac!(
+    /// KMAC128, the Keccak MAC with 128 bits of security.
+    Kmac128, CShake128
+);
+kmac!(
+    /// KMAC256, the Keccak MAC with 256 bits of security.
+    Kmac256, CShake256
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    fn key() -> Vec<u8> {
+        (0x40..0x60).collect()
+    }
+
+    // NIST SP 800-185 KMAC samples
+    #[test]
+    fn test_kmac128() {
+        let mut kmac = Kmac128::new(&key(), b"");
+        kmac.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            kmac.finalize(32),
+            decode("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e")
+        );
+
+        let mut kmac = Kmac128::new(&key(), b"My Tagged Application");
+        kmac.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            kmac.finalize(32),
+            decode("3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5")
+        );
+    }
+
+    #[test]
+    fn test_kmac256() {
+        let mut kmac = Kmac256::new(&key(), b"My Tagged Application");
+        kmac.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            kmac.finalize(64),
+            decode(concat!(
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7",
+                "f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+            ))
+        );
+    }
+
+    // NIST SP 800-185 KMACXOF sample
+    #[test]
+    fn test_kmac_xof() {
+        let mut kmac = Kmac128::new(&key(), b"");
+        kmac.update(&[0, 1, 2, 3]);
+        assert_eq!(
+            kmac.finalize_xof().squeeze_vec(32),
+            decode("cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35")
+        );
+    }
+
+    #[test]
+    fn test_output_lengths_unrelated() {
+        // the output length is absorbed, so a shorter tag is not a prefix
+        let long = Kmac128::new(&key(), b"").finalize(64);
+        let short = Kmac128::new(&key(), b"").finalize(32);
+        assert_ne!(long[..32], short);
+
+        // whereas KMACXOF outputs are prefixes of each other
+        let long = Kmac128::new(&key(), b"").finalize_xof().squeeze_vec(64);
+        let short = Kmac128::new(&key(), b"").finalize_xof().squeeze_vec(32);
+        assert_eq!(long[..32], short);
+    }
+}
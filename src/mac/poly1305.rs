@@ -0,0 +1,268 @@
+use crate::cipher::KeyError;
+
+// Poly1305 implements the one-time authenticator defined in RFC 8439.
+// Poly1305 evaluates the message as a polynomial at a secret point r, modulo
+// the prime 2^130 - 5, and masks the result with a secret s.
+
+// r     = le_bytes_to_num(key[..16]), clamped
+// s     = le_bytes_to_num(key[16..])
+// acc   = (acc + (m_i || 0x01)) * r      mod 2^130 - 5
+// tag   = (acc + s)                      mod 2^128
+
+// The key must only ever authenticate one message: two tags under the same
+// key reveal r and s. ChaCha20-Poly1305 derives a fresh key per nonce.
+
+// The 130 bit accumulator is held in five 26 bit limbs, so limb products fit
+// comfortably in a u64 (poly1305-donna's 32 bit representation).
+pub struct Poly1305 {
+    r: [u32; 5],
+    s: [u32; 4],
+    h: [u32; 5],
+    buffer: Vec<u8>, // a trailing partial block, which is only padded by finalize
+}
+
+const MASK: u32 = 0x3ffffff;
+
+impl Poly1305 {
+    pub fn new(key: Vec<u8>) -> Result<Self, KeyError> {
+        if key.len() != 32 {
+            return Err(KeyError::InvalidKeyLength {
+                expected: &[32],
+                actual: key.len(),
+            });
+        }
+
+        // clamping clears the top 4 bits of r[3], r[7], r[11] and r[15], and
+        // the bottom 2 bits of r[4], r[8] and r[12]
+        let r = [
+            le32(&key[0..]) & 0x3ffffff,
+            (le32(&key[3..]) >> 2) & 0x3ffff03,
+            (le32(&key[6..]) >> 4) & 0x3ffc0ff,
+            (le32(&key[9..]) >> 6) & 0x3f03fff,
+            (le32(&key[12..]) >> 8) & 0x00fffff,
+        ];
+        let s = [
+            le32(&key[16..]),
+            le32(&key[20..]),
+            le32(&key[24..]),
+            le32(&key[28..]),
+        ];
+
+        Ok(Poly1305 {
+            r,
+            s,
+            h: [0; 5],
+            buffer: Vec::with_capacity(16),
+        })
+    }
+
+    pub fn update(&mut self, input: &[u8]) {
+        self.buffer.extend_from_slice(input);
+
+        let full = self.buffer.len() / 16 * 16;
+        let buffer = std::mem::take(&mut self.buffer);
+        for block in buffer[..full].chunks_exact(16) {
+            self.absorb(block, 1 << 24);
+        }
+        self.buffer = buffer[full..].to_vec();
+    }
+
+    pub fn finalize(mut self) -> [u8; 16] {
+        // the final partial block is padded with 0x01 in place of the 2^128 bit
+        if !self.buffer.is_empty() {
+            let mut block = [0u8; 16];
+            block[..self.buffer.len()].copy_from_slice(&self.buffer);
+            block[self.buffer.len()] = 0x01;
+            self.absorb(&block, 0);
+        }
+
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+
+        // 1. fully carry h
+        let mut c = h1 >> 26;
+        h1 &= MASK;
+        h2 += c;
+        c = h2 >> 26;
+        h2 &= MASK;
+        h3 += c;
+        c = h3 >> 26;
+        h3 &= MASK;
+        h4 += c;
+        c = h4 >> 26;
+        h4 &= MASK;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= MASK;
+        h1 += c;
+
+        // 2. compute g = h - p = h + 5 - 2^130, and select g if it did not underflow
+        let mut g0 = h0.wrapping_add(5);
+        c = g0 >> 26;
+        g0 &= MASK;
+        let mut g1 = h1.wrapping_add(c);
+        c = g1 >> 26;
+        g1 &= MASK;
+        let mut g2 = h2.wrapping_add(c);
+        c = g2 >> 26;
+        g2 &= MASK;
+        let mut g3 = h3.wrapping_add(c);
+        c = g3 >> 26;
+        g3 &= MASK;
+        let g4 = h4.wrapping_add(c).wrapping_sub(1 << 26);
+
+        // selecting without branching keeps the tag computation constant time
+        let select_g = (g4 >> 31).wrapping_sub(1);
+        h0 = (h0 & !select_g) | (g0 & select_g);
+        h1 = (h1 & !select_g) | (g1 & select_g);
+        h2 = (h2 & !select_g) | (g2 & select_g);
+        h3 = (h3 & !select_g) | (g3 & select_g);
+        h4 = (h4 & !select_g) | (g4 & select_g);
+
+        // 3. repack the limbs into 32 bit words, and add s mod 2^128
+        let words = [
+            h0 | (h1 << 26),
+            (h1 >> 6) | (h2 << 20),
+            (h2 >> 12) | (h3 << 14),
+            (h3 >> 18) | (h4 << 8),
+        ];
+
+        let mut tag = [0u8; 16];
+        let mut carry = 0u64;
+        for ((chunk, word), s) in tag.chunks_exact_mut(4).zip(words).zip(self.s) {
+            let sum = word as u64 + s as u64 + carry;
+            chunk.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+
+        tag
+    }
+
+    // absorb adds a 16 byte block (with hibit as its 2^128 bit) to the
+    // accumulator, and multiplies by r.
+    fn absorb(&mut self, block: &[u8], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        // 2^130 = 5 mod p, so limb products that overflow 2^130 wrap around times 5
+        let [s1, s2, s3, s4] = [r1 * 5, r2 * 5, r3 * 5, r4 * 5];
+
+        let h0 = (self.h[0] + (le32(&block[0..]) & MASK)) as u64;
+        let h1 = (self.h[1] + ((le32(&block[3..]) >> 2) & MASK)) as u64;
+        let h2 = (self.h[2] + ((le32(&block[6..]) >> 4) & MASK)) as u64;
+        let h3 = (self.h[3] + ((le32(&block[9..]) >> 6) & MASK)) as u64;
+        let h4 = (self.h[4] + ((le32(&block[12..]) >> 8) | hibit)) as u64;
+
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        // partially carry, so every limb fits in 26 bits plus a little headroom
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let c = (d4 >> 26) as u32;
+
+        self.h = [
+            (d0 as u32) & MASK,
+            (d1 as u32) & MASK,
+            (d2 as u32) & MASK,
+            (d3 as u32) & MASK,
+            (d4 as u32) & MASK,
+        ];
+        self.h[0] += c * 5;
+        self.h[1] += self.h[0] >> 26;
+        self.h[0] &= MASK;
+    }
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::hex::decode;
+
+    fn mac(key: &str, message: &[u8]) -> Vec<u8> {
+        let mut poly1305 = Poly1305::new(decode(key)).unwrap();
+        poly1305.update(message);
+
+        poly1305.finalize().to_vec()
+    }
+
+    // RFC 8439 section 2.5.2
+    #[test]
+    fn test_rfc8439_example() {
+        let key = "85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b";
+        let message = b"Cryptographic Forum Research Group";
+
+        assert_eq!(
+            mac(key, message),
+            decode("a8061dc1305136c6c22b8baf0c0127a9")
+        );
+
+        // updates of any size give the same tag
+        let mut poly1305 = Poly1305::new(decode(key)).unwrap();
+        for chunk in message.chunks(5) {
+            poly1305.update(chunk);
+        }
+        assert_eq!(
+            poly1305.finalize().to_vec(),
+            decode("a8061dc1305136c6c22b8baf0c0127a9")
+        );
+    }
+
+    // RFC 8439 appendix A.3 test vectors, which exercise the edge cases of
+    // the modular reduction
+    #[test]
+    fn test_rfc8439_reduction_vectors() {
+        // #1: all zero key
+        assert_eq!(
+            mac(&"00".repeat(32), &[0u8; 64]),
+            decode("00000000000000000000000000000000")
+        );
+
+        // #5: h reaches p exactly, and reduces to 0
+        assert_eq!(
+            mac(
+                &format!("02{}", "00".repeat(31)),
+                &decode("ffffffffffffffffffffffffffffffff")
+            ),
+            decode("03000000000000000000000000000000")
+        );
+
+        // #6: adding s overflows 2^128
+        assert_eq!(
+            mac(
+                &format!("02{}{}", "00".repeat(15), "ff".repeat(16)),
+                &decode("02000000000000000000000000000000")
+            ),
+            decode("03000000000000000000000000000000")
+        );
+
+        // #10 and #11: the partially carried h exceeds 2^130 between blocks
+        let key = format!("01{}04{}", "00".repeat(7), "00".repeat(23));
+        let message = decode(concat!(
+            "e33594d7505e43b900000000000000003394d7505e4379cd0100000000000000",
+            "0000000000000000000000000000000001000000000000000000000000000000",
+        ));
+        assert_eq!(
+            mac(&key, &message),
+            decode("14000000000000005500000000000000")
+        );
+        assert_eq!(
+            mac(&key, &message[..48]),
+            decode("13000000000000000000000000000000")
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_length() {
+        assert!(matches!(
+            Poly1305::new(vec![0u8; 16]),
+            Err(KeyError::InvalidKeyLength { actual: 16, .. })
+        ));
+    }
+}
@@ -232,6 +232,38 @@ fn _test_runner() {
     // -------------xorshiro----------------------------------------------------
 
     // -------------chacha20 cipher---------------------------------------------
+    // chacha20 is a stream cipher built from an add-rotate-xor (ARX) block function
+    // each 64 byte keystream block hashes the key, a block counter and a nonce,
+    // so unlike mt19937 the keystream can't be recovered from its outputs
+
+    // rfc 8439 section 2.4.2
+    let key = (0..32).collect::<Vec<u8>>();
+    let nonce = vec![0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+    let mut chacha = kaidoku::cipher::stream::ChaCha20::new(key, nonce).unwrap();
+    chacha.seek(1);
+
+    let plaintext = "Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    let ciphertext =
+        kaidoku::cipher::StreamCipher::encrypt(&mut chacha, plaintext.as_bytes().to_vec());
+    let ciphertext_hex = kaidoku::encode::hex::ByteToHexEncoder::new(ciphertext.into_iter())
+        .collect::<Result<String, kaidoku::encode::hex::HexEncodingError>>()
+        .unwrap();
+    println!("chacha20 ciphertext_hex: {}", ciphertext_hex);
+
+    // paired with the poly1305 one-time authenticator, chacha20 becomes an AEAD scheme
+    let key = (0x80..=0x9f).collect::<Vec<u8>>();
+    let nonce = [
+        0x07, 0, 0, 0, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47,
+    ];
+    let aead = kaidoku::cipher::aead::ChaCha20Poly1305::new(key).unwrap();
+    let sealed = aead
+        .encrypt(&nonce, b"header", plaintext.as_bytes().to_vec())
+        .unwrap();
+    let opened = aead.decrypt(&nonce, b"header", sealed).unwrap();
+    println!(
+        "chacha20-poly1305 round trip: {}",
+        String::from_utf8(opened).unwrap()
+    );
 
     // ____ ____ ____ ____ ____ _________ ____ ____ ____ ____ ____ ____ ____
     // ||B |||L |||O |||C |||K |||       |||C |||I |||P |||H |||E |||R |||S ||
@@ -0,0 +1,23 @@
+// constant_time_eq compares two secrets, such as authentication tags or
+// password hashes, without short-circuiting on the first differing byte, so
+// comparison time does not leak the matching prefix length.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"tag", b"tag"));
+        assert!(!constant_time_eq(b"tag", b"tog"));
+        assert!(!constant_time_eq(b"tag", b"ta"));
+    }
+}